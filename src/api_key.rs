@@ -0,0 +1,133 @@
+use {
+    crate::{Error, Result},
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
+
+/// Number of leading characters left visible when an [`ApiKey`] is printed
+const VISIBLE_CHARS: usize = 8;
+
+/// Characters [`ApiKey::masked`] shows at each end
+const MASKED_EDGE_CHARS: usize = 4;
+
+/// Shortest key [`ApiKey::masked`] partially reveals, so at least half of it
+/// stays hidden
+const MASKED_MIN_LEN: usize = 4 * MASKED_EDGE_CHARS;
+
+/// A Fireblocks API key
+///
+/// `Display` and `Debug` mask everything after the first 8 characters, use
+/// [`ApiKey::expose`] for the full value.
+#[derive(Clone, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
+    /// The full, unmasked key
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The key for log lines: the first and last 4 characters with the
+    /// middle replaced by `*`, fully masked when shorter than 16 characters
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::ApiKey;
+    ///
+    /// let key = ApiKey::new("6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50");
+    /// assert_eq!(key.masked(), "6f0c****************************4f50");
+    /// assert_eq!(ApiKey::new("short").masked(), "*****");
+    /// ```
+    pub fn masked(&self) -> String {
+        let len = self.0.chars().count();
+        if len < MASKED_MIN_LEN {
+            return "*".repeat(len);
+        }
+        let head: String = self.0.chars().take(MASKED_EDGE_CHARS).collect();
+        let tail: String = self.0.chars().skip(len - MASKED_EDGE_CHARS).collect();
+        format!("{head}{}{tail}", "*".repeat(len - 2 * MASKED_EDGE_CHARS))
+    }
+
+    /// Check that the key is set and, unless `allow_nonstandard` is true,
+    /// shaped like the UUIDs Fireblocks issues
+    pub fn validate(&self, allow_nonstandard: bool) -> Result<()> {
+        if self.0.trim().is_empty() {
+            return Err(Error::InvalidApiKey {
+                key: self.masked(),
+                reason: "api_key is empty".to_string(),
+            });
+        }
+        if !allow_nonstandard && !is_uuid(&self.0) {
+            return Err(Error::InvalidApiKey {
+                key: self.masked(),
+                reason: "expected a UUID like 01234567-89ab-cdef-0123-456789abcdef, set \
+                         allow_nonstandard_api_key = true for on-prem setups"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn is_uuid(key: &str) -> bool {
+    let groups: Vec<&str> = key.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let visible: String = self.0.chars().take(VISIBLE_CHARS).collect();
+        let hidden = self.0.chars().count().saturating_sub(VISIBLE_CHARS);
+        write!(f, "{visible}{}", "*".repeat(hidden))
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey({self})")
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        Self(key.to_string())
+    }
+}
+
+impl PartialEq<str> for ApiKey {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ApiKey {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<ApiKey> for &str {
+    fn eq(&self, other: &ApiKey) -> bool {
+        *self == other.0
+    }
+}
@@ -0,0 +1,38 @@
+//! AWS Secrets Manager key source, enabled with the `aws` feature
+use {
+    crate::{Error, Result},
+    aws_config::BehaviorVersion,
+    aws_sdk_secretsmanager::error::DisplayErrorContext,
+};
+
+/// Fetch the value of a secret using the ambient AWS credential chain
+///
+/// String secrets are returned as UTF-8 bytes, binary secrets as-is. This
+/// drives the request on its own runtime, so it must not be called from
+/// within an async context; use `get_key_async` there.
+pub(crate) fn fetch_secret(arn: &str) -> Result<Vec<u8>> {
+    tracing::debug!("fetching secret {arn} from AWS Secrets Manager");
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let sdk_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+        let client = aws_sdk_secretsmanager::Client::new(&sdk_config);
+        let output = client
+            .get_secret_value()
+            .secret_id(arn)
+            .send()
+            .await
+            .map_err(|e| Error::AwsSecretsManager(DisplayErrorContext(&e).to_string()))?;
+
+        if let Some(value) = output.secret_string() {
+            Ok(value.as_bytes().to_vec())
+        } else if let Some(value) = output.secret_binary() {
+            Ok(value.as_ref().to_vec())
+        } else {
+            Err(Error::AwsSecretsManager(format!(
+                "secret {arn} has no value"
+            )))
+        }
+    })
+}
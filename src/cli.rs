@@ -0,0 +1,108 @@
+//! Command-line flags shared by CLIs built on this crate, enabled with the
+//! `cli` feature
+use {
+    crate::{Error, FireblocksConfig, OutputFormat, Result},
+    std::path::PathBuf,
+};
+
+/// Common Fireblocks flags, meant to be `#[command(flatten)]`-ed into an
+/// application's own `clap::Parser`
+///
+/// # Example
+/// ```rust,no_run
+/// use {
+///     clap::Parser,
+///     fireblocks_config::{FireblocksCliArgs, FireblocksConfig},
+/// };
+///
+/// #[derive(Parser)]
+/// struct Cli {
+///     #[command(flatten)]
+///     fireblocks: FireblocksCliArgs,
+/// }
+///
+/// let cli = Cli::parse();
+/// let config = FireblocksConfig::from_cli(&cli.fireblocks)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct FireblocksCliArgs {
+    /// Base config file, defaults to ~/.config/fireblocks/default.toml
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Profile layered over the base config, loaded from {profile}.toml next
+    /// to it. May be repeated.
+    #[arg(long = "profile")]
+    pub profiles: Vec<String>,
+
+    /// Output format
+    #[arg(long, value_enum)]
+    pub output: Option<OutputFormat>,
+
+    /// Vault id used for signing
+    #[arg(long)]
+    pub vault: Option<String>,
+
+    /// Use the Fireblocks mainnet
+    #[arg(long)]
+    pub mainnet: bool,
+
+    /// Enable debug mode
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Extra configuration value as key=value, parsed as JSON when possible.
+    /// May be repeated.
+    #[arg(long = "extra", value_name = "KEY=VALUE", value_parser = parse_extra)]
+    pub extra: Vec<(String, serde_json::Value)>,
+}
+
+fn parse_extra(arg: &str) -> std::result::Result<(String, serde_json::Value), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{arg}'"))?;
+    let value = serde_json::from_str(value)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+impl FireblocksConfig {
+    /// Load the config selected by the CLI flags and apply the flag values on
+    /// top, above files and environment variables
+    ///
+    /// Without `--config`, the XDG location is used as in
+    /// [`FireblocksConfig::init_with_profiles`].
+    pub fn from_cli(args: &FireblocksCliArgs) -> Result<Self> {
+        let mut cfg = match args.config {
+            Some(ref config) => {
+                let dir = config.parent().map(PathBuf::from).unwrap_or_default();
+                let mut profile_configs = Vec::with_capacity(args.profiles.len());
+                for profile in &args.profiles {
+                    let profile_file = format!("{profile}.toml");
+                    let profile_config = dir.join(&profile_file);
+                    if !profile_config.exists() {
+                        return Err(Error::ProfileConfigNotFound(profile_file));
+                    }
+                    profile_configs.push(profile_config);
+                }
+                Self::new(config.clone(), &profile_configs)?
+            }
+            None => Self::init_with_profiles(&args.profiles)?,
+        };
+
+        if let Some(output) = args.output {
+            cfg.display_config.output = output;
+        }
+        if let Some(ref vault) = args.vault {
+            cfg.signer.vault = vault.clone();
+        }
+        cfg.mainnet |= args.mainnet;
+        cfg.debug |= args.debug;
+        cfg.extra.extend(args.extra.iter().cloned());
+
+        cfg.validate()?;
+        Ok(cfg)
+    }
+}
@@ -1,252 +1,3801 @@
 #[cfg(feature = "gpg")]
 use gpgme::{Context, Protocol};
 use {
-    crate::{Error, OutputFormat, Result},
-    config::{Config, File, FileFormat},
-    serde::Deserialize,
+    crate::{
+        ApiKey,
+        Error,
+        FeeLevel,
+        OutputFormat,
+        Result,
+        SortOrder,
+        error::capture_backtrace,
+        loader::{
+            kv_layer,
+            layered_builder,
+            normalize_table,
+            parse_toml,
+            read_layer,
+            read_toml,
+            resolve_extends,
+            table_source,
+            take_profiles,
+            to_toml_string,
+        },
+        migrate::migrate_table,
+        schema::{resolve_aliases, unknown_keys},
+    },
+    config::{Config, ConfigBuilder, File, FileFormat, Source, ValueKind, builder::DefaultState},
+    serde::{Deserialize, Serialize},
     std::{
-        collections::HashMap,
+        collections::{BTreeMap, HashMap},
         fs,
-        path::{Path, PathBuf},
+        io::Read,
+        path::{Component, Path, PathBuf},
         str::FromStr,
-        time::Duration,
+        time::{Duration, Instant, SystemTime},
     },
 };
 
-pub(crate) fn expand_tilde(path: &str) -> PathBuf {
-    if path.starts_with('~') {
-        match dirs::home_dir() {
-            Some(mut home) => {
-                home.push(&path[2..]);
-                home
+#[cfg(feature = "async")]
+use crate::loader::{read_config_async, resolve_extends_async};
+
+/// Expand a leading `~` to the home directory, as config paths do
+///
+/// `~` alone, `~/` and `~\\` are expanded, other paths (including `~user`)
+/// are returned unchanged. On Windows a leading `%USERPROFILE%` is expanded as
+/// well. The path is never converted to a string, so non-UTF-8 paths survive.
+///
+/// Fails with [`Error::HomeDirUnavailable`] when the path needs the home
+/// directory and it is unknown, e.g. under systemd `DynamicUser`.
+///
+/// # Example
+/// ```rust
+/// use fireblocks_config::expand_path;
+///
+/// let key = expand_path("~/.fireblocks/key.pem")?;
+/// assert!(!key.starts_with("~"));
+/// # Ok::<(), fireblocks_config::Error>(())
+/// ```
+pub fn expand_path<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    expand_path_with(path.as_ref(), dirs::home_dir().as_deref(), cfg!(windows))
+}
+
+/// Whether a `secret_path` value is a URL such as `https://host/key` or
+/// `file:///key` rather than a file path
+pub(crate) fn has_url_scheme(path: &str) -> bool {
+    // A single letter is a Windows drive, not a scheme
+    path.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    })
+}
+
+/// [`expand_path`] against the given home directory
+pub(crate) fn expand_path_with(path: &Path, home: Option<&Path>, windows: bool) -> Result<PathBuf> {
+    const USERPROFILE: &str = "%USERPROFILE%";
+    let mut components = path.components();
+    // Only a UTF-8 first component can name the home directory, whatever
+    // follows it is joined as is
+    let Some(Component::Normal(first)) = components.next() else {
+        return Ok(path.to_path_buf());
+    };
+    let Some(first) = first.to_str() else {
+        return Ok(path.to_path_buf());
+    };
+    let rest = if let Some(rest) = first.strip_prefix('~') {
+        rest
+    } else if windows
+        && first
+            .get(..USERPROFILE.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(USERPROFILE))
+    {
+        &first[USERPROFILE.len()..]
+    } else {
+        return Ok(path.to_path_buf());
+    };
+    // `\\` only reaches here on Unix, where it is not a separator
+    let rest = match rest.strip_prefix('\\') {
+        Some(rest) => rest,
+        None if rest.is_empty() => rest,
+        None => return Ok(path.to_path_buf()),
+    };
+    let home = home.ok_or_else(|| Error::HomeDirUnavailable {
+        path: path.to_string_lossy().to_string(),
+    })?;
+    let mut expanded = home.to_path_buf();
+    for part in [Path::new(rest), components.as_path()] {
+        if !part.as_os_str().is_empty() {
+            expanded.push(part);
+        }
+    }
+    Ok(expanded)
+}
+
+/// Replace `$VAR` and `${VAR}` in `value` with the variable from `lookup`,
+/// `$$` being a literal `$`
+///
+/// Fails with the name of the first variable `lookup` does not know.
+fn expand_env_with(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> std::result::Result<String, String> {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, tail) = if let Some(after) = after.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+            continue;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.split_once('}') {
+                Some((name, tail)) if !name.is_empty() && name.chars().all(is_name) => (name, tail),
+                _ => return Err(format!("unterminated or invalid ${{...}} in {value}")),
             }
-            None => PathBuf::from(path),
+        } else {
+            let end = after.find(|c: char| !is_name(c)).unwrap_or(after.len());
+            after.split_at(end)
+        };
+        if name.is_empty() {
+            out.push('$');
+        } else {
+            out.push_str(
+                &lookup(name).ok_or_else(|| format!("environment variable {name} is not set"))?,
+            );
         }
-    } else {
-        PathBuf::from(path)
+        rest = tail;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// [`Error::Load`] for a failure layering `files`, and the environment when
+/// `env_used`
+fn load_error(files: &[ConfigSource], env_used: bool, source: config::ConfigError) -> Error {
+    let mut sources: Vec<String> = files.iter().map(ToString::to_string).collect();
+    if env_used {
+        sources.push(ConfigSource::Environment.to_string());
+    }
+    Error::Load {
+        sources,
+        source: Box::new(source),
+        backtrace: capture_backtrace(),
+    }
+}
+
+/// Span covering one load of a base file and its overrides, `env` is
+/// recorded once the environment overlay has been checked
+fn load_span(cfg: &Path, overrides: usize) -> tracing::Span {
+    tracing::debug_span!(
+        "fireblocks_config.load",
+        base = %cfg.display(),
+        overrides,
+        env = tracing::field::Empty
+    )
+}
+
+/// Debug event for a file added to the layering
+fn log_source(kind: SourceKind, path: &Path) {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    tracing::debug!(%kind, path = %absolute.display(), "adding config source");
+}
+
+/// Number of values in `table`, counting into nested tables
+fn count_keys(table: &config::Map<String, config::Value>) -> usize {
+    table
+        .values()
+        .map(|value| match &value.kind {
+            ValueKind::Table(table) => count_keys(table),
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Dotted keys of the non-table values in `table`, arrays included
+fn scalar_keys(prefix: &str, table: &toml::Table, out: &mut Vec<String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::Table(table) => scalar_keys(&key, table, out),
+            _ => out.push(key),
+        }
+    }
+}
+
+/// Remove the profiles and workspaces of a base file's table and return
+/// `inline`, normalized like a file of its own
+fn take_inline(table: &mut toml::Table, inline: &InlineTable) -> Result<toml::Table> {
+    let mut profiles = take_profiles(table);
+    table.remove("default_workspace");
+    let mut workspaces = match table.remove("workspaces") {
+        Some(toml::Value::Table(workspaces)) => workspaces,
+        _ => toml::Table::new(),
+    };
+    let (tables, name) = match inline {
+        InlineTable::Profile(name) => (&mut profiles, name),
+        InlineTable::Workspace(name) => (&mut workspaces, name),
+    };
+    if let Some(toml::Value::Table(mut overlay)) = tables.remove(name) {
+        normalize_table(&mut overlay)?;
+        return Ok(overlay);
+    }
+    let mut available: Vec<String> = tables
+        .iter()
+        .filter(|(_, table)| table.is_table())
+        .map(|(name, _)| name.clone())
+        .collect();
+    available.sort();
+    Err(match inline {
+        InlineTable::Profile(profile) => Error::ProfileNotFound {
+            profile: profile.clone(),
+            available,
+        },
+        InlineTable::Workspace(workspace) => Error::WorkspaceNotFound {
+            workspace: workspace.clone(),
+            available,
+        },
+    })
+}
+
+fn source_paths<P: AsRef<Path>>(cfg: &Path, cfg_overrides: &[P]) -> Vec<ConfigSource> {
+    std::iter::once(cfg.to_path_buf())
+        .chain(cfg_overrides.iter().map(|p| p.as_ref().to_path_buf()))
+        .map(ConfigSource::File)
+        .collect()
+}
+
+/// A layer a [`FireblocksConfig`] was loaded from, see
+/// [`FireblocksConfig::sources`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConfigSource {
+    /// A base or override file
+    File(PathBuf),
+    /// `FIREBLOCKS_*` environment variables, only listed when at least one
+    /// was set
+    Environment,
+}
+
+#[cfg(feature = "gpg")]
+fn is_gpg_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gpg"))
+}
+
+/// Run `secret_command` through the shell and return its stdout
+///
+/// stdin and stderr are inherited so tools like `pass` can prompt.
+fn run_secret_command(command: &str) -> Result<Vec<u8>> {
+    tracing::debug!("running secret_command");
+    #[cfg(windows)]
+    let mut shell = std::process::Command::new("cmd");
+    #[cfg(windows)]
+    shell.arg("/C");
+    #[cfg(not(windows))]
+    let mut shell = std::process::Command::new("sh");
+    #[cfg(not(windows))]
+    shell.arg("-c");
+    let output = shell
+        .arg(command)
+        .stderr(std::process::Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::SecretCommand {
+            status: output.status,
+        });
+    }
+    Ok(output.stdout)
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Environment => f.write_str("environment"),
+        }
+    }
+}
+
+/// Role of a [`ConfigSource`] in the layering
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SourceKind {
+    /// The base file
+    Base,
+    /// An override file applied on top of the base
+    Override,
+    /// `FIREBLOCKS_*` environment variables
+    Env,
+}
+
+impl std::fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Base => "base",
+            Self::Override => "override",
+            Self::Env => "env",
+        })
     }
 }
 
-#[derive(Clone, Debug, Default, Deserialize)]
+/// A source applied while loading, see
+/// [`FireblocksConfig::applied_sources`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceInfo {
+    /// Position in the load order, later sources win
+    pub index: usize,
+    pub kind: SourceKind,
+    pub source: ConfigSource,
+}
+
+impl SourceInfo {
+    /// Describe `sources` in load order, emitting a debug event for each
+    fn describe(sources: &[ConfigSource]) -> Vec<Self> {
+        sources
+            .iter()
+            .enumerate()
+            .map(|(index, source)| {
+                let kind = match source {
+                    ConfigSource::File(_) if index == 0 => SourceKind::Base,
+                    ConfigSource::File(_) => SourceKind::Override,
+                    ConfigSource::Environment => SourceKind::Env,
+                };
+                tracing::debug!(%source, %kind, index, "applied config source");
+                Self {
+                    index,
+                    kind,
+                    source: source.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A single place [`FireblocksConfig::get_key_from`] reads the key from, and
+/// the one [`FireblocksConfig::resolve_key`] reports
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SecretSource {
+    /// The `secret` value
+    Embedded,
+    /// The file at `secret_path`
+    Path,
+    /// The `FIREBLOCKS_SECRET` variable of the environment the config was
+    /// loaded with, read when the key is requested
+    Env,
+    /// The output of `secret_command`
+    Command,
+    /// A `.gpg` file at `secret_path`, decrypted with GnuPG
+    #[cfg(feature = "gpg")]
+    Gpg,
+    /// A `secret_path` URL other than `file://`, fetched when the key is
+    /// requested
+    Url,
+    /// The `secret_shares`, combined
+    #[cfg(feature = "sss")]
+    Shares,
+    /// The AWS Secrets Manager secret at `secret_aws_arn`
+    #[cfg(feature = "aws")]
+    AwsSecretsManager,
+    /// The base64 `secret_kms_ciphertext`, decrypted with KMS
+    #[cfg(feature = "kms")]
+    Kms,
+    /// The Vault secret at `vault_secret_path`
+    #[cfg(feature = "vault")]
+    Vault,
+}
+
+impl SecretSource {
+    /// Environment variable read by [`SecretSource::Env`]
+    pub const ENV_VAR: &str = "FIREBLOCKS_SECRET";
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DisplayConfig {
     pub output: OutputFormat,
 }
 
-// Serde deserializer wrapper for parse_duration
-fn deserialize_duration<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+/// Parse seconds given as an integer, a float or a string holding either
+///
+/// Fractional seconds are kept to the millisecond.
+struct DurationVisitor;
+
+impl DurationVisitor {
+    fn from_secs_f64<E: serde::de::Error>(secs: f64) -> std::result::Result<Duration, E> {
+        if secs.is_finite() && secs >= 0.0 && secs < u64::MAX as f64 / 1000.0 {
+            Ok(Duration::from_millis((secs * 1000.0).round() as u64))
+        } else {
+            Err(E::custom(format!("Invalid duration: {secs}")))
+        }
+    }
+}
+
+impl serde::de::Visitor<'_> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a non-negative number of seconds")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, secs: u64) -> std::result::Result<Duration, E> {
+        Ok(Duration::from_secs(secs))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, secs: i64) -> std::result::Result<Duration, E> {
+        u64::try_from(secs)
+            .map(Duration::from_secs)
+            .map_err(|_| E::custom(format!("Invalid duration: {secs}")))
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, secs: f64) -> std::result::Result<Duration, E> {
+        Self::from_secs_f64(secs)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, s: &str) -> std::result::Result<Duration, E> {
+        let s = s.trim();
+        if let Ok(secs) = u64::from_str(s) {
+            return Ok(Duration::from_secs(secs));
+        }
+        match f64::from_str(s) {
+            Ok(secs) => Self::from_secs_f64(secs),
+            Err(_) => Err(E::custom(format!("Invalid duration: {s}"))),
+        }
+    }
+}
+
+/// [`DurationVisitor`] that also takes `null`, as `None`
+struct OptionalDurationVisitor;
+
+impl<'de> serde::de::Visitor<'de> for OptionalDurationVisitor {
+    type Value = Option<Duration>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a non-negative number of seconds or null")
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(DurationVisitor).map(Some)
+    }
+}
+
+fn deserialize_optional_duration<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserializer.deserialize_option(OptionalDurationVisitor)
+}
+
+/// Deserialize a duration, taking `null` as `default`
+fn deserialize_duration_or<'de, D>(
+    deserializer: D,
+    default: fn() -> Duration,
+) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(deserialize_optional_duration(deserializer)?.unwrap_or_else(default))
+}
+
+/// [`OptionalDurationVisitor`] that also takes `"none"`, as zero
+struct PollTimeoutVisitor;
+
+impl<'de> serde::de::Visitor<'de> for PollTimeoutVisitor {
+    type Value = Option<Duration>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a non-negative number of seconds, \"none\" or null")
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, secs: u64) -> std::result::Result<Self::Value, E> {
+        DurationVisitor.visit_u64(secs).map(Some)
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, secs: i64) -> std::result::Result<Self::Value, E> {
+        DurationVisitor.visit_i64(secs).map(Some)
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, secs: f64) -> std::result::Result<Self::Value, E> {
+        DurationVisitor.visit_f64(secs).map(Some)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, s: &str) -> std::result::Result<Self::Value, E> {
+        if s.trim().eq_ignore_ascii_case("none") {
+            return Ok(Some(Duration::ZERO));
+        }
+        DurationVisitor.visit_str(s).map(Some)
+    }
+}
+
+fn deserialize_poll_timeout<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(deserializer
+        .deserialize_option(PollTimeoutVisitor)?
+        .unwrap_or_else(default_poll_timeout))
+}
+
+fn deserialize_poll_interval<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_duration_or(deserializer, default_poll_interval)
+}
+
+fn deserialize_initial_backoff<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_duration_or(deserializer, default_initial_backoff)
+}
+
+fn deserialize_max_backoff<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_duration_or(deserializer, default_max_backoff)
+}
+
+fn deserialize_connect_timeout<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_duration_or(deserializer, default_connect_timeout)
+}
+
+fn deserialize_request_timeout<'de, D>(deserializer: D) -> std::result::Result<Duration, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    let seconds = u64::from_str(&s)
-        .map_err(|_| serde::de::Error::custom(format!("Invalid duration: {s}")))?;
-    Ok(Duration::from_secs(seconds))
+    deserialize_duration_or(deserializer, default_request_timeout)
+}
+
+// Serialize durations as seconds, whole when possible, matching
+// DurationVisitor
+fn serialize_duration<S>(duration: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&duration_secs(duration))
+}
+
+/// `duration` as seconds, whole when possible
+fn duration_secs(duration: &Duration) -> String {
+    if duration.subsec_nanos() == 0 {
+        duration.as_secs().to_string()
+    } else {
+        duration.as_secs_f64().to_string()
+    }
+}
+
+fn serialize_optional_duration<S>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match duration {
+        Some(duration) => serialize_duration(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Lowercase `key` and drop `_` and `-`, so `rpc_url`, `rpcUrl` and
+/// `RPC-URL` compare equal
+fn normalize_extra_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Parse a byte size such as `1024`, `25MB` or `1.5 GiB`
+///
+/// Decimal units (`KB`, `MB`, `GB`, `TB`) are powers of 1000 and binary units
+/// (`KiB`, `MiB`, `GiB`, `TiB`) powers of 1024, case is ignored.
+fn parse_byte_size(value: &str) -> std::result::Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        unit => return Err(format!("unknown unit '{unit}'")),
+    };
+    if let Ok(n) = number.parse::<u64>() {
+        return n
+            .checked_mul(multiplier)
+            .ok_or_else(|| "size overflows u64".to_string());
+    }
+    let n: f64 = number
+        .parse()
+        .map_err(|_| format!("'{number}' is not a number"))?;
+    let bytes = n * multiplier as f64;
+    if bytes.is_finite() && bytes < u64::MAX as f64 {
+        Ok(bytes as u64)
+    } else {
+        Err("size overflows u64".to_string())
+    }
 }
 
 pub(crate) fn default_poll_timeout() -> Duration {
-    Duration::from_secs(180)
+    Signer::DEFAULT_POLL_TIMEOUT
 }
 
 pub(crate) fn default_poll_interval() -> Duration {
-    Duration::from_secs(5)
+    Signer::DEFAULT_POLL_INTERVAL
+}
+
+pub(crate) fn default_broadcast() -> bool {
+    false
+}
+
+pub(crate) fn default_fail_on_fee_exceeded() -> bool {
+    true
+}
+
+/// Replace each `{name}` in `template` with `lookup(name)`
+///
+/// Fails with [`Error::InvalidTemplate`] on a name `lookup` does not know or
+/// a `{` that is never closed.
+fn render_template(
+    template: &str,
+    mut lookup: impl FnMut(&str) -> Option<String>,
+) -> Result<String> {
+    let invalid = |placeholder: &str| Error::InvalidTemplate {
+        template: template.to_string(),
+        placeholder: placeholder.to_string(),
+    };
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start + 1..];
+        let end = tail.find('}').ok_or_else(|| invalid(&rest[start..]))?;
+        let name = &tail[..end];
+        out.push_str(&lookup(name).ok_or_else(|| invalid(name))?);
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Whether `value` is a plain non-negative decimal such as `12`, `0.005` or
+/// `.5`, without sign or exponent
+fn is_decimal(value: &str) -> bool {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    !(whole.is_empty() && fraction.is_empty())
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
+pub(crate) fn default_poll_backoff() -> Option<f64> {
+    Some(1.0)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Signer {
+    /// How long to poll for a transaction. Zero, or `"none"` in a config
+    /// file, disables polling; see [`Signer::polling_enabled`].
+    #[serde(
+        default = "default_poll_timeout",
+        deserialize_with = "deserialize_poll_timeout",
+        serialize_with = "serialize_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::duration_schema")
+    )]
+    pub poll_timeout: Duration,
+    /// Delay between polls, must be greater than zero and at most
+    /// `poll_timeout` while polling is enabled
+    #[serde(
+        default = "default_poll_interval",
+        deserialize_with = "deserialize_poll_interval",
+        serialize_with = "serialize_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::duration_schema")
+    )]
+    pub poll_interval: Duration,
+    /// Multiplier applied to `poll_interval` after each attempt, `1.0` keeps
+    /// the interval fixed
+    #[serde(default = "default_poll_backoff")]
+    pub poll_backoff: Option<f64>,
+    /// Upper bound for the backed-off poll interval
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_duration",
+        serialize_with = "serialize_optional_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::optional_duration_schema")
+    )]
+    pub poll_max_interval: Option<Duration>,
+    /// The vault id, empty when left out. See [`Signer::vault_id`].
+    #[serde(default)]
+    pub vault: String,
+    /// If true, Fireblocks will broadcast the transaction
+    #[serde(default = "default_broadcast")]
+    pub broadcast: bool,
+    /// If true, only produce a signature and never broadcast the transaction
+    #[serde(default)]
+    pub sign_only: bool,
+    /// Highest fee a transaction may pay, as a decimal string in the asset's
+    /// unit so no precision is lost to floats
+    #[serde(default)]
+    pub max_fee: Option<String>,
+    /// Fee level requested for new transactions
+    #[serde(default)]
+    pub fee_level: Option<FeeLevel>,
+    /// Confirmations to wait for before a transaction counts as completed
+    #[serde(default)]
+    pub confirmations: Option<u32>,
+    /// Reject transactions whose fee exceeds `max_fee` instead of only
+    /// warning
+    #[serde(default = "default_fail_on_fee_exceeded")]
+    pub fail_on_fee_exceeded: bool,
+    /// Template for the `externalTxId` of new transactions, see
+    /// [`Signer::external_tx_id`]
+    #[serde(default)]
+    pub external_tx_id_template: Option<String>,
+}
+
+impl Default for Signer {
+    fn default() -> Self {
+        Self {
+            poll_timeout: default_poll_timeout(),
+            poll_interval: default_poll_interval(),
+            poll_backoff: default_poll_backoff(),
+            poll_max_interval: None,
+            vault: String::new(),
+            broadcast: default_broadcast(),
+            sign_only: false,
+            max_fee: None,
+            fee_level: None,
+            confirmations: None,
+            fail_on_fee_exceeded: default_fail_on_fee_exceeded(),
+            external_tx_id_template: None,
+        }
+    }
 }
 
-pub(crate) fn default_broadcast() -> bool {
-    false
-}
+impl Signer {
+    /// `poll_interval` when the config does not set one
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+    /// `poll_timeout` when the config does not set one
+    pub const DEFAULT_POLL_TIMEOUT: Duration = Duration::from_secs(180);
+    /// Placeholders `external_tx_id_template` may use
+    pub const EXTERNAL_TX_ID_PLACEHOLDERS: &[&str] = &["uuid", "timestamp", "vault", "nonce"];
+
+    /// Check the signer settings for values that cannot work at runtime
+    pub fn validate(&self) -> Result<()> {
+        if self.polling_enabled() {
+            if self.poll_interval.is_zero() {
+                return Err(Error::InvalidDuration(
+                    "signer.poll_interval must be greater than zero".to_string(),
+                ));
+            }
+            if self.poll_interval > self.poll_timeout {
+                return Err(Error::InvalidDuration(format!(
+                    "signer.poll_interval ({}s) must not exceed signer.poll_timeout ({}s), set \
+                     poll_timeout = \"none\" to disable polling",
+                    duration_secs(&self.poll_interval),
+                    duration_secs(&self.poll_timeout)
+                )));
+            }
+        }
+        if let Some(ref max_fee) = self.max_fee
+            && !is_decimal(max_fee)
+        {
+            return Err(Error::InvalidValue {
+                key: "signer.max_fee".to_string(),
+                value: max_fee.clone(),
+                reason: "expected a non-negative decimal such as \"0.005\"".to_string(),
+            });
+        }
+        if let Some(ref template) = self.external_tx_id_template {
+            render_template(template, |name| {
+                Self::EXTERNAL_TX_ID_PLACEHOLDERS
+                    .contains(&name)
+                    .then(String::new)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Render `external_tx_id_template` for the transaction numbered `nonce`
+    ///
+    /// `{vault}` and `{nonce}` make the id deterministic, so a retried
+    /// transaction is rejected as a duplicate. `{uuid}` (a random v4 UUID) and
+    /// `{timestamp}` (Unix seconds) differ on every call. `None` when no
+    /// template is set or it uses a placeholder other than
+    /// [`Signer::EXTERNAL_TX_ID_PLACEHOLDERS`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::Signer;
+    ///
+    /// let signer = Signer {
+    ///     vault: "3".to_string(),
+    ///     external_tx_id_template: Some("payout-{vault}-{nonce}".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(signer.external_tx_id(42).as_deref(), Some("payout-3-42"));
+    /// ```
+    pub fn external_tx_id(&self, nonce: u64) -> Option<String> {
+        let template = self.external_tx_id_template.as_deref()?;
+        render_template(template, |name| match name {
+            "uuid" => Some(uuid::Uuid::new_v4().to_string()),
+            "timestamp" => Some(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+                    .to_string(),
+            ),
+            "vault" => Some(self.vault.clone()),
+            "nonce" => Some(nonce.to_string()),
+            _ => None,
+        })
+        .ok()
+    }
+
+    /// Highest fee a transaction may pay, see `max_fee`
+    pub fn max_fee(&self) -> Option<&str> {
+        self.max_fee.as_deref()
+    }
+
+    /// Fee level requested for new transactions
+    pub fn fee_level(&self) -> Option<FeeLevel> {
+        self.fee_level
+    }
+
+    /// Confirmations to wait for before a transaction counts as completed
+    pub fn confirmations(&self) -> Option<u32> {
+        self.confirmations
+    }
+
+    /// Whether a fee above `max_fee` rejects the transaction
+    pub fn fail_on_fee_exceeded(&self) -> bool {
+        self.fail_on_fee_exceeded
+    }
+
+    /// The vault id parsed as a number
+    ///
+    /// Fireblocks vault ids are numeric; the stored `vault` stays a string so
+    /// values like `"0"` round-trip unchanged.
+    pub fn vault_id(&self) -> Result<u64> {
+        self.vault.parse().map_err(|_| Error::InvalidVault {
+            value: self.vault.clone(),
+        })
+    }
+
+    /// Whether transactions should be polled at all, false when
+    /// `poll_timeout` is zero
+    pub fn polling_enabled(&self) -> bool {
+        !self.poll_timeout.is_zero()
+    }
+
+    /// How long to poll for a transaction, `None` when polling is disabled
+    ///
+    /// # Example
+    /// ```rust
+    /// use {fireblocks_config::Signer, std::time::Duration};
+    ///
+    /// let signer = Signer {
+    ///     poll_timeout: Duration::ZERO,
+    ///     ..Default::default()
+    /// };
+    /// assert!(!signer.polling_enabled());
+    /// assert_eq!(signer.effective_timeout(), None);
+    /// ```
+    pub fn effective_timeout(&self) -> Option<Duration> {
+        self.polling_enabled().then_some(self.poll_timeout)
+    }
+
+    /// Deadline for a polling loop starting now
+    pub fn poll_deadline(&self) -> Instant {
+        Instant::now() + self.poll_timeout
+    }
+
+    /// Expected number of polls within `poll_timeout` at a fixed
+    /// `poll_interval`
+    pub fn attempts(&self) -> u64 {
+        self.poll_timeout.as_secs() / self.poll_interval.as_secs().max(1)
+    }
+
+    /// Interval to wait before poll `attempt` (starting at 0)
+    ///
+    /// The interval is `poll_interval * poll_backoff^attempt`, capped at
+    /// `poll_max_interval` when set. A missing or non-positive backoff is
+    /// treated as `1.0`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use {fireblocks_config::Signer, std::time::Duration};
+    ///
+    /// let signer = Signer {
+    ///     poll_backoff: Some(2.0),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(signer.next_interval(2), Duration::from_secs(20));
+    /// ```
+    pub fn next_interval(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .poll_backoff
+            .filter(|b| b.is_finite() && *b > 0.0)
+            .unwrap_or(1.0);
+        let secs = self.poll_interval.as_secs_f64() * backoff.powf(f64::from(attempt));
+        let interval = Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX);
+        match self.poll_max_interval {
+            Some(max) => interval.min(max),
+            None => interval,
+        }
+    }
+}
+
+/// Where `key=value` or CLI overrides sit relative to `FIREBLOCKS_*`
+/// environment variables
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OverridePrecedence {
+    /// Overrides beat files, the environment beats overrides
+    #[default]
+    BelowEnv,
+    /// Overrides beat files and the environment
+    AboveEnv,
+}
+
+/// Where the `FIREBLOCKS_*` overlay of a load comes from
+#[derive(Clone, Debug, Default, PartialEq)]
+enum EnvSource {
+    /// The process environment, read at load time
+    #[default]
+    Process,
+    /// Variables given by the caller, see [`FireblocksConfig::new_with_env`]
+    Vars(BTreeMap<String, String>),
+    /// No overlay, see [`FireblocksConfig::new_no_env`]
+    Disabled,
+}
+
+impl EnvSource {
+    /// The variables to overlay, without the ones that pick files, `None`
+    /// when disabled
+    fn vars(&self) -> Option<config::Map<String, String>> {
+        let vars: config::Map<String, String> = match self {
+            Self::Process => std::env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+            Self::Vars(vars) => vars.clone().into_iter().collect(),
+            Self::Disabled => return None,
+        };
+        Some(
+            vars.into_iter()
+                .filter(|(key, _)| {
+                    key != FireblocksConfig::CONFIG_ENV_VAR
+                        && key != FireblocksConfig::PROFILE_ENV_VAR
+                })
+                .collect(),
+        )
+    }
+}
+
+/// How [`FireblocksConfig::new_layered`] builds a config, kept so
+/// [`FireblocksConfig::reload`] can replay it
+#[derive(Clone, Debug, Default, PartialEq)]
+struct LoadOptions {
+    /// `key=value` overrides
+    kv: Vec<String>,
+    /// Where `kv` goes relative to the environment
+    precedence: OverridePrecedence,
+    /// Where the environment overlay comes from
+    env: EnvSource,
+    /// Baseline defaults, see [`FireblocksConfig::new_with_defaults`]
+    defaults: Option<Defaults>,
+    /// Table of the base file layered over it
+    inline: Option<InlineTable>,
+}
+
+/// A table inside the base file that is layered over its top-level keys
+#[derive(Clone, Debug, PartialEq)]
+enum InlineTable {
+    /// `[profiles.<name>]`, see [`FireblocksConfig::with_profile`]
+    Profile(String),
+    /// `[workspaces.<name>]`, see [`crate::FireblocksWorkspaces`]
+    Workspace(String),
+}
+
+/// Baseline values for [`FireblocksConfig::new_with_defaults`], used only
+/// for keys no file or environment variable sets
+///
+/// Fields left `None` keep the built-in defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Defaults {
+    /// Default `url`
+    pub url: Option<String>,
+    /// Default `signer.poll_timeout`, [`Signer::DEFAULT_POLL_TIMEOUT`] when
+    /// unset
+    pub poll_timeout: Option<Duration>,
+    /// Default `signer.poll_interval`, [`Signer::DEFAULT_POLL_INTERVAL`] when
+    /// unset
+    pub poll_interval: Option<Duration>,
+    /// Default `display.output`
+    pub output: Option<OutputFormat>,
+}
+
+impl Defaults {
+    /// Add the set fields to `builder` as `set_default` values
+    fn seed(
+        &self,
+        mut builder: ConfigBuilder<DefaultState>,
+    ) -> Result<ConfigBuilder<DefaultState>> {
+        let defaults_error = |source| Error::Load {
+            sources: vec!["defaults".to_string()],
+            source: Box::new(source),
+            backtrace: capture_backtrace(),
+        };
+        if let Some(ref url) = self.url {
+            builder = builder
+                .set_default("url", url.as_str())
+                .map_err(defaults_error)?;
+        }
+        if let Some(timeout) = self.poll_timeout {
+            builder = builder
+                .set_default("signer.poll_timeout", duration_secs(&timeout))
+                .map_err(defaults_error)?;
+        }
+        if let Some(interval) = self.poll_interval {
+            builder = builder
+                .set_default("signer.poll_interval", duration_secs(&interval))
+                .map_err(defaults_error)?;
+        }
+        if let Some(output) = self.output {
+            builder = builder
+                .set_default("display.output", format!("{output:?}"))
+                .map_err(defaults_error)?;
+        }
+        Ok(builder)
+    }
+}
+
+pub(crate) fn default_max_retries() -> u32 {
+    5
+}
+
+pub(crate) fn default_initial_backoff() -> Duration {
+    Duration::from_secs(1)
+}
+
+pub(crate) fn default_max_backoff() -> Duration {
+    Duration::from_secs(30)
+}
+
+pub(crate) fn default_backoff_multiplier() -> f64 {
+    2.0
+}
+
+pub(crate) fn default_retry_on_rate_limit() -> bool {
+    true
+}
+
+/// Retry policy for clients calling the Fireblocks API, read from the
+/// optional `[retry]` section
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RetryConfig {
+    /// Number of retries after the first attempt
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry
+    #[serde(
+        default = "default_initial_backoff",
+        deserialize_with = "deserialize_initial_backoff",
+        serialize_with = "serialize_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::duration_schema")
+    )]
+    pub initial_backoff: Duration,
+    /// Upper bound for any single delay
+    #[serde(
+        default = "default_max_backoff",
+        deserialize_with = "deserialize_max_backoff",
+        serialize_with = "serialize_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::duration_schema")
+    )]
+    pub max_backoff: Duration,
+    /// Factor applied to the delay after each retry
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    /// Retry requests rejected with HTTP 429
+    #[serde(default = "default_retry_on_rate_limit")]
+    pub retry_on_rate_limit: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            initial_backoff: default_initial_backoff(),
+            max_backoff: default_max_backoff(),
+            backoff_multiplier: default_backoff_multiplier(),
+            retry_on_rate_limit: default_retry_on_rate_limit(),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// The delay before each retry, `max_retries` items long
+    ///
+    /// Delays grow by `backoff_multiplier` from `initial_backoff` and are
+    /// capped at `max_backoff`. A non-positive multiplier is treated as
+    /// `1.0`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use {fireblocks_config::RetryConfig, std::time::Duration};
+    ///
+    /// let delays: Vec<Duration> = RetryConfig::default().delays().take(3).collect();
+    /// assert_eq!(delays, [1, 2, 4].map(Duration::from_secs));
+    /// ```
+    pub fn delays(&self) -> impl Iterator<Item = Duration> {
+        let multiplier = Some(self.backoff_multiplier)
+            .filter(|m| m.is_finite() && *m > 0.0)
+            .unwrap_or(1.0);
+        let initial = self.initial_backoff.as_secs_f64();
+        let max = self.max_backoff;
+        (0..self.max_retries).map(move |retry| {
+            let secs = initial * multiplier.powf(f64::from(retry));
+            Duration::try_from_secs_f64(secs)
+                .unwrap_or(Duration::MAX)
+                .min(max)
+        })
+    }
+}
+
+pub(crate) fn default_connect_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+pub(crate) fn default_request_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+pub(crate) fn default_max_concurrency() -> usize {
+    8
+}
+
+/// HTTP client settings, read from the optional `[http]` section
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HttpConfig {
+    /// Timeout for establishing a connection
+    #[serde(
+        default = "default_connect_timeout",
+        deserialize_with = "deserialize_connect_timeout",
+        serialize_with = "serialize_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::duration_schema")
+    )]
+    pub connect_timeout: Duration,
+    /// Timeout for a whole request, from connecting to reading the body
+    #[serde(
+        default = "default_request_timeout",
+        deserialize_with = "deserialize_request_timeout",
+        serialize_with = "serialize_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::duration_schema")
+    )]
+    pub request_timeout: Duration,
+    /// Upper bound for requests in flight at once, must be greater than zero
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Proxy for all requests, e.g. `http://proxy.internal:3128`
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Hosts reached without `proxy`, in `NO_PROXY` syntax, e.g.
+    /// `["localhost", ".internal", "10.0.0.0/8"]`
+    #[serde(default)]
+    pub no_proxy: Option<Vec<String>>,
+    /// `User-Agent` header sent with every request
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Skip TLS certificate validation, only meant for test environments
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: default_connect_timeout(),
+            request_timeout: default_request_timeout(),
+            max_concurrency: default_max_concurrency(),
+            proxy: None,
+            no_proxy: None,
+            user_agent: None,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+pub(crate) fn default_page_size() -> u32 {
+    200
+}
+
+/// Page size and result caps for API listing operations, read from the
+/// optional `[paging]` section
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PagingConfig {
+    /// Items requested per page, must be greater than zero
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+    /// Stop listing after this many items, at least `page_size` when set
+    #[serde(default)]
+    pub max_items: Option<u32>,
+    /// Order of listed items
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+impl Default for PagingConfig {
+    fn default() -> Self {
+        Self {
+            page_size: default_page_size(),
+            max_items: None,
+            order: SortOrder::default(),
+        }
+    }
+}
+
+impl PagingConfig {
+    /// Check that `page_size` is not zero and `max_items` is at least
+    /// `page_size`
+    pub fn validate(&self) -> Result<()> {
+        if self.page_size == 0 {
+            return Err(Error::InvalidValue {
+                key: "paging.page_size".to_string(),
+                value: "0".to_string(),
+                reason: "must be greater than zero".to_string(),
+            });
+        }
+        match self.max_items {
+            Some(max_items) if max_items < self.page_size => Err(Error::InvalidValue {
+                key: "paging.max_items".to_string(),
+                value: max_items.to_string(),
+                reason: format!(
+                    "must be at least paging.page_size ({}), lower page_size to fetch fewer items",
+                    self.page_size
+                ),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check that `value` is an absolute URL with one of `schemes` and a host
+fn validate_url(key: &str, value: &str, schemes: &[&str]) -> Result<()> {
+    let invalid = |reason: String| Error::InvalidUrl {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason,
+    };
+    let url = url::Url::parse(value).map_err(|e| invalid(e.to_string()))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(invalid(format!("unsupported scheme {}", url.scheme())));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(invalid("missing host".to_string()));
+    }
+    Ok(())
+}
+
+impl HttpConfig {
+    /// Check that `max_concurrency` is not zero and that `proxy` is an
+    /// absolute `http`, `https` or `socks5` URL
+    pub fn validate(&self) -> Result<()> {
+        if self.max_concurrency == 0 {
+            return Err(Error::InvalidValue {
+                key: "http.max_concurrency".to_string(),
+                value: "0".to_string(),
+                reason: "must be greater than zero".to_string(),
+            });
+        }
+        match self.proxy {
+            Some(ref proxy) => {
+                validate_url("http.proxy", proxy, &["http", "https", "socks5", "socks5h"])
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Blockchain network settings, read from the optional `[network]` section
+///
+/// The legacy `rpc_url`, `ws_url`, `commitment`, `chain_id` and
+/// `explorer_url` keys under `[extra]` still fill in fields the section
+/// leaves unset, with a deprecation warning.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NetworkConfig {
+    /// JSON-RPC endpoint, e.g. `https://api.mainnet-beta.solana.com`
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    /// Websocket endpoint for subscriptions
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// Solana commitment level, e.g. `confirmed`
+    #[serde(default)]
+    pub commitment: Option<String>,
+    /// EVM chain id
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    /// Block explorer base URL
+    #[serde(default)]
+    pub explorer_url: Option<String>,
+}
+
+impl NetworkConfig {
+    /// Check that `rpc_url` and `explorer_url` are `http(s)` URLs and
+    /// `ws_url` a `ws(s)` URL
+    pub fn validate(&self) -> Result<()> {
+        self.checks().collect()
+    }
+
+    /// One result per URL, for [`FireblocksConfig::validate_all`]
+    fn checks(&self) -> impl Iterator<Item = Result<()>> {
+        let urls = [
+            ("network.rpc_url", &self.rpc_url, &["http", "https"]),
+            ("network.ws_url", &self.ws_url, &["ws", "wss"]),
+            ("network.explorer_url", &self.explorer_url, &[
+                "http", "https",
+            ]),
+        ];
+        urls.into_iter().filter_map(|(key, value, schemes)| {
+            value
+                .as_ref()
+                .map(|value| validate_url(key, value, schemes))
+        })
+    }
+}
+
+/// Settings of the co-signer callback handler, read from the optional
+/// `[callback]` section
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CallbackConfig {
+    /// Public key verifying callback requests, `~` is expanded
+    #[serde(default)]
+    pub public_key_path: Option<PathBuf>,
+    /// Address the handler listens on as `host:port`, e.g. `0.0.0.0:8443`
+    #[serde(default)]
+    pub listen: Option<String>,
+    /// Addresses or CIDR ranges allowed to call the handler, e.g.
+    /// `10.0.0.0/8`
+    #[serde(default)]
+    pub allowed_ips: Vec<String>,
+}
+
+/// Whether `value` is `host:port`, with IPv6 hosts in brackets
+fn is_host_port(value: &str) -> bool {
+    if value.parse::<std::net::SocketAddr>().is_ok() {
+        return true;
+    }
+    let Some((host, port)) = value.rsplit_once(':') else {
+        return false;
+    };
+    !host.is_empty() && !host.contains([':', '[', ']']) && port.parse::<u16>().is_ok()
+}
+
+/// Whether `value` is an IP address or a CIDR range
+fn is_ip_or_cidr(value: &str) -> bool {
+    let (ip, prefix) = match value.split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix)),
+        None => (value, None),
+    };
+    let Ok(ip) = ip.parse::<std::net::IpAddr>() else {
+        return false;
+    };
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    prefix.is_none_or(|prefix| prefix.parse::<u8>().is_ok_and(|len| len <= max))
+}
+
+impl CallbackConfig {
+    /// Check that `listen` is `host:port` and every `allowed_ips` entry an IP
+    /// address or CIDR range
+    pub fn validate(&self) -> Result<()> {
+        self.checks().collect()
+    }
+
+    /// One result per checked value, for
+    /// [`FireblocksConfig::validate_all`]
+    fn checks(&self) -> impl Iterator<Item = Result<()>> {
+        let invalid = |key: &str, value: &str, reason: &str| {
+            Err(Error::InvalidValue {
+                key: key.to_string(),
+                value: value.to_string(),
+                reason: reason.to_string(),
+            })
+        };
+        let listen = self
+            .listen
+            .as_deref()
+            .filter(|listen| !is_host_port(listen))
+            .map(|listen| invalid("callback.listen", listen, "expected host:port"));
+        let allowed_ips = self
+            .allowed_ips
+            .iter()
+            .filter(|ip| !is_ip_or_cidr(ip))
+            .map(move |ip| {
+                invalid(
+                    "callback.allowed_ips",
+                    ip,
+                    "expected an IP address or CIDR range",
+                )
+            });
+        listen.into_iter().chain(allowed_ips)
+    }
+
+    /// Read the file at `public_key_path`
+    ///
+    /// Fails with [`Error::NotPresent`] when no path is set.
+    pub fn public_key(&self) -> Result<Vec<u8>> {
+        let path = self
+            .public_key_path
+            .as_ref()
+            .ok_or_else(|| Error::NotPresent {
+                key: "callback.public_key_path".to_string(),
+            })?;
+        let path = expand_path(path)?;
+        fs::read(&path).map_err(|e| Error::IOError {
+            source: e,
+            path: path.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        })
+    }
+}
+
+/// Names `log_level` accepts, from least to most verbose
+const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// A `log_level` name, case-insensitive. Unlike `tracing::Level::from_str`
+/// this refuses numeric levels.
+fn parse_log_level(level: &str) -> Option<tracing::Level> {
+    LOG_LEVELS
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(level))
+        .then(|| level.parse().ok())
+        .flatten()
+}
+
+/// Dependencies [`FireblocksConfig::env_filter`] keeps at `warn` at most
+const NOISY_TARGETS: &[&str] = &[
+    "aws_config",
+    "aws_smithy_runtime",
+    "h2",
+    "hyper",
+    "hyper_util",
+    "notify",
+    "reqwest",
+    "rustls",
+];
+
+pub(crate) fn default_asset_test_suffix() -> String {
+    "_TEST".to_string()
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+pub(crate) fn default_require_https() -> bool {
+    true
+}
+
+/// Whether `url` points at this machine, where plain `http` is acceptable
+fn is_loopback(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FireblocksConfig {
+    /// Layout version of the config, see
+    /// [`FireblocksConfig::CURRENT_VERSION`]. Files without one are version
+    /// 1, and loading migrates them so this is always current afterwards.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// Fireblocks API key. Left out it is empty, which
+    /// [`FireblocksConfig::validate`] rejects.
+    #[serde(default, alias = "apiKey")]
+    pub api_key: ApiKey,
+    /// Accept API keys that are not UUIDs, for on-prem setups
+    #[serde(default)]
+    pub allow_nonstandard_api_key: bool,
+    /// API base URL, may be omitted or empty to use the default for
+    /// `mainnet`. See [`FireblocksConfig::resolved_url`].
+    #[serde(default, alias = "base_url", alias = "baseUrl")]
+    pub url: String,
+    /// Reject a plain `http://` `url` unless it points at localhost
+    #[serde(default = "default_require_https")]
+    pub require_https: bool,
+    #[serde(alias = "secretPath", alias = "secret_key_path")]
+    pub secret_path: Option<PathBuf>,
+    /// Environment variable holding a bearer token for an `https://`
+    /// `secret_path`
+    pub secret_url_auth_env: Option<String>,
+    /// Directory `secret_path` must resolve into after following symlinks,
+    /// for configs written by less trusted tooling. Resolved like
+    /// `secret_path`, unset allows any path.
+    pub secret_path_root: Option<PathBuf>,
+    /// The key itself, never serialized. Takes precedence over
+    /// `secret_path` when both are set.
+    #[serde(default, skip_serializing, alias = "secretKey", alias = "secret_key")]
+    pub secret: Option<String>,
+    /// Shell command printing the key on stdout, e.g. `pass show fireblocks`.
+    /// Run by [`FireblocksConfig::get_key`] after `secret` and before
+    /// `secret_path`.
+    pub secret_command: Option<String>,
+    /// Hex encoded Shamir secret shares, combined into the key when the `sss`
+    /// feature is enabled. Set `secret_shares_threshold` under `[extra]` when
+    /// the threshold differs from the number of shares. Never serialized.
+    #[serde(default, skip_serializing)]
+    pub secret_shares: Option<Vec<String>>,
+    /// ARN of an AWS Secrets Manager secret holding the key, read with the
+    /// ambient credential chain when the `aws` feature is enabled
+    pub secret_aws_arn: Option<String>,
+    /// Base64 key encrypted under a KMS key, decrypted by
+    /// [`FireblocksConfig::get_key`] when a KMS provider feature such as
+    /// `aws-kms` is enabled
+    pub secret_kms_ciphertext: Option<String>,
+    /// KMS key the ciphertext was encrypted under, required for asymmetric
+    /// keys and checked against the ciphertext otherwise
+    pub secret_kms_key_id: Option<String>,
+    /// HashiCorp Vault server address, e.g. `https://vault.example.com:8200`
+    pub vault_addr: Option<String>,
+    /// Environment variable holding the Vault token, defaults to
+    /// `VAULT_TOKEN`
+    pub vault_token_env: Option<String>,
+    /// KV v2 secret holding the key as `<mount>/<path>[#field]`, read when the
+    /// `vault` feature is enabled. The field defaults to `key`.
+    pub vault_secret_path: Option<String>,
+    /// PEM public key verifying Fireblocks webhooks, or
+    /// [`FireblocksConfig::WEBHOOK_BUILTIN`]. See
+    /// [`FireblocksConfig::get_webhook_key`].
+    pub webhook_public_key: Option<String>,
+    /// File holding the webhook public key, resolved like `secret_path`
+    pub webhook_public_key_path: Option<PathBuf>,
+    /// Fail loading on keys no field recognizes instead of only warning. See
+    /// [`FireblocksConfig::unknown_keys`].
+    #[serde(default)]
+    pub treat_unknown_as_error: bool,
+    /// Lifetime of request tokens signed with the `jwt` feature, defaults to
+    /// 30 seconds
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_duration",
+        serialize_with = "serialize_optional_duration"
+    )]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::optional_duration_schema")
+    )]
+    pub jwt_ttl: Option<Duration>,
+    #[serde(rename = "display", default)]
+    pub display_config: DisplayConfig,
+    /// Signing settings, defaults apply when `[signer]` is missing
+    #[serde(default)]
+    pub signer: Signer,
+    /// Retry policy for API clients, defaults apply when `[retry]` is missing
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// HTTP client settings, defaults apply when `[http]` is missing
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Listing defaults for API clients, defaults apply when `[paging]` is
+    /// missing
+    #[serde(default)]
+    pub paging: PagingConfig,
+    /// Blockchain network settings, see [`FireblocksConfig::network`]
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Co-signer callback handler settings, `None` without a `[callback]`
+    /// section
+    #[serde(default)]
+    pub callback: Option<CallbackConfig>,
+    /// Arbitrary extra configuration values
+    #[serde(default)]
+    pub extra: HashMap<String, serde_json::Value>,
+    /// Enable debug mode
+    #[serde(default)]
+    pub debug: bool,
+    /// Log verbosity, one of `error`, `warn`, `info`, `debug` or `trace`. See
+    /// [`FireblocksConfig::log_level`].
+    #[serde(default)]
+    pub log_level: Option<String>,
+
+    #[serde(default)]
+    pub mainnet: bool,
+
+    /// Suffix [`FireblocksConfig::asset_id`] appends to asset ids when
+    /// `mainnet` is false
+    #[serde(default = "default_asset_test_suffix")]
+    pub asset_test_suffix: String,
+
+    /// Testnet asset ids that do not follow `asset_test_suffix`, by base
+    /// asset, e.g. `ETH = "ETH_TEST5"`
+    #[serde(default)]
+    pub asset_overrides: HashMap<String, String>,
+
+    /// Base assets [`FireblocksConfig::require_asset`] accepts, empty allows
+    /// any
+    #[serde(default)]
+    pub assets_allowed: Vec<String>,
+
+    /// Base and override files this config was loaded from in load order,
+    /// followed by the environment when it contributed. Used by
+    /// [`FireblocksConfig::reload`].
+    #[serde(skip)]
+    sources: Vec<ConfigSource>,
+
+    /// [`FireblocksConfig::sources`] with their role in the layering
+    #[serde(skip)]
+    applied_sources: Vec<SourceInfo>,
+
+    /// How the files were layered, replayed by [`FireblocksConfig::reload`]
+    #[serde(skip)]
+    options: LoadOptions,
+
+    /// Keys the loaded sources set that no field recognizes
+    #[serde(skip)]
+    unknown_keys: Vec<String>,
+
+    /// Alternative names of extra keys, see
+    /// [`FireblocksConfig::with_extra_aliases`]
+    #[serde(skip)]
+    extra_aliases: HashMap<String, Vec<String>>,
+
+    /// Modification time of the base file when it was loaded, see
+    /// [`FireblocksConfig::loaded_mtime`]
+    #[serde(skip)]
+    loaded_mtime: Option<SystemTime>,
+
+    /// Whether `secret` came from the `FIREBLOCKS_SECRET` variable of the
+    /// environment overlay, see [`SecretSource::Env`]
+    #[serde(skip)]
+    secret_from_env: bool,
+}
+
+impl Default for FireblocksConfig {
+    fn default() -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            api_key: ApiKey::default(),
+            allow_nonstandard_api_key: false,
+            url: String::new(),
+            require_https: default_require_https(),
+            secret_path: None,
+            secret_url_auth_env: None,
+            secret_path_root: None,
+            secret: None,
+            secret_command: None,
+            secret_shares: None,
+            secret_aws_arn: None,
+            secret_kms_ciphertext: None,
+            secret_kms_key_id: None,
+            vault_addr: None,
+            vault_token_env: None,
+            vault_secret_path: None,
+            webhook_public_key: None,
+            webhook_public_key_path: None,
+            treat_unknown_as_error: false,
+            jwt_ttl: None,
+            display_config: DisplayConfig::default(),
+            signer: Signer::default(),
+            retry: RetryConfig::default(),
+            paging: PagingConfig::default(),
+            http: HttpConfig::default(),
+            network: NetworkConfig::default(),
+            callback: None,
+            extra: HashMap::new(),
+            debug: false,
+            log_level: None,
+            mainnet: false,
+            asset_test_suffix: default_asset_test_suffix(),
+            asset_overrides: HashMap::new(),
+            assets_allowed: Vec::new(),
+            sources: Vec::new(),
+            applied_sources: Vec::new(),
+            options: LoadOptions::default(),
+            unknown_keys: Vec::new(),
+            extra_aliases: HashMap::new(),
+            loaded_mtime: None,
+            secret_from_env: false,
+        }
+    }
+}
+
+impl FireblocksConfig {
+    /// Fireblocks production API base URL, used when `url` is not set and
+    /// `mainnet` is true
+    pub const PRODUCTION_URL: &str = "https://api.fireblocks.io/v1";
+    /// Fireblocks sandbox API base URL, used when `url` is not set and
+    /// `mainnet` is false
+    pub const SANDBOX_URL: &str = "https://sandbox-api.fireblocks.io/v1";
+    /// `webhook_public_key` value selecting the key Fireblocks publishes for
+    /// the API `mainnet` points at
+    pub const WEBHOOK_BUILTIN: &str = "builtin";
+
+    /// The Fireblocks asset id of `base` for the environment `mainnet`
+    /// selects
+    ///
+    /// On mainnet `base` is returned unchanged. Otherwise an
+    /// `[asset_overrides]` entry wins, and any other asset gets
+    /// `asset_test_suffix` appended.
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::default();
+    /// assert_eq!(config.asset_id("SOL"), "SOL_TEST");
+    /// ```
+    pub fn asset_id(&self, base: &str) -> String {
+        if self.mainnet {
+            return base.to_string();
+        }
+        match self.asset_overrides.get(base) {
+            Some(id) => id.clone(),
+            None => format!("{base}{}", self.asset_test_suffix),
+        }
+    }
+
+    /// The base asset of the asset id `id`, the inverse of
+    /// [`FireblocksConfig::asset_id`]
+    ///
+    /// Ids that are neither an `[asset_overrides]` value nor end with
+    /// `asset_test_suffix` are returned unchanged.
+    pub fn base_asset(&self, id: &str) -> String {
+        if self.mainnet {
+            return id.to_string();
+        }
+        if let Some((base, _)) = self.asset_overrides.iter().find(|(_, v)| *v == id) {
+            return base.clone();
+        }
+        id.strip_suffix(self.asset_test_suffix.as_str())
+            .filter(|base| !base.is_empty() && !self.asset_test_suffix.is_empty())
+            .unwrap_or(id)
+            .to_string()
+    }
+
+    /// [`FireblocksConfig::asset_id`], failing with [`Error::AssetNotFound`]
+    /// when `assets_allowed` is set and does not list `base`
+    pub fn require_asset(&self, base: &str) -> Result<String> {
+        if !self.assets_allowed.is_empty() && !self.assets_allowed.iter().any(|a| a == base) {
+            return Err(Error::AssetNotFound {
+                asset: base.to_string(),
+            });
+        }
+        Ok(self.asset_id(base))
+    }
+
+    /// The configured log verbosity
+    ///
+    /// `log_level` wins when set. Otherwise `debug = true` means
+    /// [`tracing::Level::DEBUG`] and anything else
+    /// [`tracing::Level::INFO`].
+    pub fn log_level(&self) -> tracing::Level {
+        if let Some(level) = self.log_level.as_deref().and_then(parse_log_level) {
+            return level;
+        }
+        if self.debug {
+            tracing::Level::DEBUG
+        } else {
+            tracing::Level::INFO
+        }
+    }
+
+    /// A `tracing_subscriber::EnvFilter` directive string for
+    /// [`FireblocksConfig::log_level`]
+    ///
+    /// HTTP, TLS and AWS dependencies are capped at `warn` so `debug` and
+    /// `trace` stay readable.
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::default();
+    /// assert!(config.env_filter().starts_with("info,"));
+    /// assert!(config.env_filter().contains(",hyper=warn"));
+    /// ```
+    pub fn env_filter(&self) -> String {
+        let level = self.log_level();
+        let noisy = level
+            .min(tracing::Level::WARN)
+            .as_str()
+            .to_ascii_lowercase();
+        std::iter::once(level.as_str().to_ascii_lowercase())
+            .chain(
+                NOISY_TARGETS
+                    .iter()
+                    .map(|target| format!("{target}={noisy}")),
+            )
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn check_log_level(&self) -> Result<()> {
+        match self.log_level.as_deref() {
+            Some(level) if parse_log_level(level).is_none() => Err(Error::InvalidValue {
+                key: "log_level".to_string(),
+                value: level.to_string(),
+                reason: format!("expected one of {}", LOG_LEVELS.join(", ")),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// `api_key` masked for log lines, see [`ApiKey::masked`]
+    pub fn masked_api_key(&self) -> String {
+        self.api_key.masked()
+    }
+
+    /// The API base URL, falling back to [`FireblocksConfig::PRODUCTION_URL`]
+    /// or [`FireblocksConfig::SANDBOX_URL`] based on `mainnet` when `url` is
+    /// missing or empty
+    pub fn resolved_url(&self) -> &str {
+        match self.url.trim() {
+            "" if self.mainnet => Self::PRODUCTION_URL,
+            "" => Self::SANDBOX_URL,
+            url => url,
+        }
+    }
+
+    /// Compare two configs, ignoring inline secret material (`secret` and
+    /// `secret_shares`)
+    pub fn eq_ignoring_secrets(&self, other: &Self) -> bool {
+        let without_secrets = |cfg: &Self| Self {
+            secret: None,
+            secret_shares: None,
+            ..cfg.clone()
+        };
+        without_secrets(self) == without_secrets(other)
+    }
+
+    /// Register alternative spellings of extra keys, so
+    /// [`FireblocksConfig::get_extra`] and the other extra getters find a
+    /// value under any of them
+    ///
+    /// The key itself wins over its aliases. Aliases holding different values
+    /// are an `Error::AmbiguousKey`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?
+    ///     .with_extra_aliases(&[("rpc_url", &["rpcUrl", "rpc-endpoint"])]);
+    /// let rpc_url: String = config.get_extra("rpc_url")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_extra_aliases(mut self, aliases: &[(&str, &[&str])]) -> Self {
+        for (key, names) in aliases {
+            let entry = self.extra_aliases.entry(key.to_string()).or_default();
+            entry.extend(names.iter().map(|name| name.to_string()));
+        }
+        self
+    }
+
+    /// The extra value for `key`, falling back to its aliases, or matching
+    /// keys by [`normalize_extra_key`] when `normalize` is set
+    fn find_extra(&self, key: &str, normalize: bool) -> Result<&serde_json::Value> {
+        if !normalize && let Some(value) = self.extra.get(key) {
+            return Ok(value);
+        }
+        let norm = |k: &str| {
+            if normalize {
+                normalize_extra_key(k)
+            } else {
+                k.to_string()
+            }
+        };
+        let wanted: Vec<String> = std::iter::once(key)
+            .chain(
+                self.extra_aliases
+                    .get(key)
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str),
+            )
+            .map(norm)
+            .collect();
+        let mut candidates: Vec<(&String, &serde_json::Value)> = self
+            .extra
+            .iter()
+            .filter(|(k, _)| wanted.contains(&norm(k)))
+            .collect();
+        candidates.sort_by_key(|(k, _)| *k);
+        match candidates.split_first() {
+            None => Err(Error::NotPresent {
+                key: key.to_string(),
+            }),
+            Some(((_, value), rest)) if rest.iter().all(|(_, v)| v == value) => Ok(value),
+            Some(_) => Err(Error::AmbiguousKey {
+                key: key.to_string(),
+                candidates: candidates.into_iter().map(|(k, _)| k.clone()).collect(),
+            }),
+        }
+    }
+
+    fn deserialize_extra<T>(key: &str, value: &serde_json::Value) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        serde_json::from_value(value.clone()).map_err(|source| Error::ExtraDeserialize {
+            key: key.to_string(),
+            source,
+            backtrace: capture_backtrace(),
+        })
+    }
+
+    /// Get an extra configuration value as any deserializable type
+    pub fn get_extra<T, K>(&self, key: K) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        K: AsRef<str>,
+    {
+        let key_str = key.as_ref();
+        Self::deserialize_extra(key_str, self.find_extra(key_str, false)?)
+    }
+
+    /// Get an extra configuration value like [`FireblocksConfig::get_extra`],
+    /// ignoring case, `_` and `-` in key names
+    ///
+    /// `rpc_url`, `rpcUrl` and `RPC-URL` all match each other. Matching keys
+    /// with different values are an `Error::AmbiguousKey`.
+    pub fn get_extra_ci<T, K>(&self, key: K) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        K: AsRef<str>,
+    {
+        let key_str = key.as_ref();
+        Self::deserialize_extra(key_str, self.find_extra(key_str, true)?)
+    }
+
+    /// Get an extra array, e.g. repeated `[[extra.endpoints]]` tables, as a
+    /// typed `Vec`
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotPresent` - If the key doesn't exist in the configuration
+    /// * `Error::InvalidValue` - If the value is not an array
+    /// * `Error::ExtraDeserialize` - If an element cannot be deserialized
+    pub fn get_extra_vec<T, K>(&self, key: K) -> Result<Vec<T>>
+    where
+        T: serde::de::DeserializeOwned,
+        K: AsRef<str>,
+    {
+        let key = key.as_ref();
+        let value = self.find_extra(key, false)?;
+        if !value.is_array() {
+            return Err(Error::InvalidValue {
+                key: key.to_string(),
+                value: value.to_string(),
+                reason: "expected an array".to_string(),
+            });
+        }
+        Self::deserialize_extra(key, value)
+    }
+
+    /// Get an extra configuration value as a Duration from seconds
+    ///
+    /// This function retrieves a numeric value from the extra configuration
+    /// and converts it to a `std::time::Duration` using
+    /// `Duration::from_secs()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The configuration key to look up (can be `&str`, `String`,
+    ///   etc.)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Duration)` - The duration value if the key exists and can be
+    ///   parsed as u64
+    /// * `Err(Error::NotPresent)` - If the key doesn't exist in the
+    ///   configuration
+    /// * `Err(Error::ExtraDeserialize)` - If the value cannot be deserialized
+    ///   as u64
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use {fireblocks_config::FireblocksConfig, std::time::Duration};
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    ///
+    /// // Get timeout as Duration (assuming config has: timeout = 30)
+    /// let timeout: Duration = config.get_extra_duration("timeout")?;
+    /// assert_eq!(timeout, Duration::from_secs(30));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn get_extra_duration<K>(&self, key: K) -> Result<Duration>
+    where
+        K: AsRef<str>,
+    {
+        let seconds: u64 = self.get_extra(key)?;
+        Ok(Duration::from_secs(seconds))
+    }
+
+    /// Get an extra configuration value as a number of bytes
+    ///
+    /// Accepts a non-negative integer or a string with a size suffix, e.g.
+    /// `max_upload = "25MB"` or `"1.5 GiB"`. `KB`, `MB`, `GB` and `TB` are
+    /// powers of 1000, `KiB`, `MiB`, `GiB` and `TiB` powers of 1024.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotPresent` - If the key doesn't exist in the configuration
+    /// * `Error::InvalidValue` - If the value is not a valid size
+    pub fn get_extra_bytes<K>(&self, key: K) -> Result<u64>
+    where
+        K: AsRef<str>,
+    {
+        let key = key.as_ref();
+        let value = self.find_extra(key, false)?;
+        let invalid = |reason: String| Error::InvalidValue {
+            key: key.to_string(),
+            value: value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string),
+            reason,
+        };
+        match value {
+            serde_json::Value::Number(n) => n
+                .as_u64()
+                .ok_or_else(|| invalid("expected a non-negative integer".to_string())),
+            serde_json::Value::String(s) => parse_byte_size(s).map_err(invalid),
+            _ => Err(invalid("expected an integer or a string".to_string())),
+        }
+    }
+
+    /// Get an extra configuration value as a path, expanded like
+    /// `secret_path`
+    ///
+    /// `$VAR` and `${VAR}` are replaced from the environment (`$$` is a
+    /// literal `$`), then `~` is expanded and a relative path is resolved
+    /// against the base config directory when it exists there.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotPresent` - If the key doesn't exist in the configuration
+    /// * `Error::InvalidValue` - If the value is not a string or names an unset
+    ///   environment variable
+    /// * `Error::HomeDirUnavailable` - If `~` is used without a home directory
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// // [extra]
+    /// // keyfile = "~/keys/${TENANT}.pem"
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let keyfile = config.get_extra_path("keyfile")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn get_extra_path<K>(&self, key: K) -> Result<PathBuf>
+    where
+        K: AsRef<str>,
+    {
+        let key = key.as_ref();
+        let value = self.find_extra(key, false)?;
+        let invalid = |reason: String| Error::InvalidValue {
+            key: key.to_string(),
+            value: value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string),
+            reason,
+        };
+        let path = value
+            .as_str()
+            .ok_or_else(|| invalid("expected a string".to_string()))?;
+        let path = expand_env_with(path, |name| std::env::var(name).ok()).map_err(invalid)?;
+        self.resolve_path(Path::new(&path))
+    }
+
+    /// Get an extra configuration value as a UTC timestamp
+    ///
+    /// Accepts RFC 3339 strings and TOML datetimes. Values without an offset,
+    /// e.g. `2025-06-01T00:00:00` or `2025-06-01`, are taken as UTC.
+    ///
+    /// # Errors
+    ///
+    /// * `Error::NotPresent` - If the key doesn't exist in the configuration
+    /// * `Error::InvalidValue` - If the value is not a valid datetime
+    #[cfg(feature = "chrono")]
+    pub fn get_extra_datetime<K>(&self, key: K) -> Result<chrono::DateTime<chrono::Utc>>
+    where
+        K: AsRef<str>,
+    {
+        use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+        let key = key.as_ref();
+        let value = self.find_extra(key, false)?;
+        let invalid = |reason: &str| Error::InvalidValue {
+            key: key.to_string(),
+            value: value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_string),
+            reason: reason.to_string(),
+        };
+        let s = value
+            .as_str()
+            .ok_or_else(|| invalid("expected a datetime"))?
+            .trim();
+        // TOML allows a space instead of `T`
+        let s = s.replacen(' ', "T", 1);
+        if let Ok(dt) = DateTime::parse_from_rfc3339(&s) {
+            return Ok(dt.with_timezone(&Utc));
+        }
+        if let Ok(dt) = NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f") {
+            return Ok(dt.and_utc());
+        }
+        NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+            .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+            .map_err(|_| invalid("expected an RFC 3339 or TOML datetime"))
+    }
+
+    /// Check if an extra configuration key exists
+    pub fn has_extra<K>(&self, key: K) -> bool
+    where
+        K: AsRef<str>,
+    {
+        !matches!(
+            self.find_extra(key.as_ref(), false),
+            Err(Error::NotPresent { .. })
+        )
+    }
+
+    pub fn get_key(&self) -> Result<Vec<u8>> {
+        self.resolve_key().map(|(key, _)| key)
+    }
+
+    /// Resolve the key like [`FireblocksConfig::get_key`], also returning the
+    /// source it came from, e.g. to record it in an audit log
+    ///
+    /// A `secret` set through the `FIREBLOCKS_SECRET` overlay is reported as
+    /// [`SecretSource::Env`], otherwise as [`SecretSource::Embedded`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let (key, source) = config.resolve_key()?;
+    /// tracing::info!(?source, "loaded {} byte signing key", key.len());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn resolve_key(&self) -> Result<(Vec<u8>, SecretSource)> {
+        let source = self.key_source()?;
+        tracing::debug!(?source, "resolving key");
+        let key = match source {
+            // the overlay's value, which need not be in the process environment
+            SecretSource::Env => self.get_key_from(SecretSource::Embedded)?,
+            source => self.get_key_from(source)?,
+        };
+        Ok((key, source))
+    }
+
+    /// The source [`FireblocksConfig::get_key`] reads, by precedence
+    ///
+    /// Falls back to [`SecretSource::Path`], which reports
+    /// [`Error::MissingSecret`] when `secret_path` is unset too.
+    fn key_source(&self) -> Result<SecretSource> {
+        // A remote secret manager takes priority over local sources
+        #[cfg(feature = "aws")]
+        if self.secret_aws_arn.is_some() {
+            return Ok(SecretSource::AwsSecretsManager);
+        }
+        #[cfg(not(feature = "aws"))]
+        if let Some(ref arn) = self.secret_aws_arn {
+            tracing::warn!("ignoring secret_aws_arn {arn}, enable the aws feature to use it");
+        }
+
+        #[cfg(feature = "kms")]
+        if self.secret_kms_ciphertext.is_some() {
+            return Ok(SecretSource::Kms);
+        }
+        #[cfg(not(feature = "kms"))]
+        if let Some(ref ciphertext) = self.secret_kms_ciphertext {
+            tracing::warn!(
+                "ignoring {} byte secret_kms_ciphertext, enable the kms feature to use it",
+                ciphertext.len()
+            );
+        }
+
+        #[cfg(feature = "vault")]
+        if self.vault_secret_path.is_some() {
+            return Ok(SecretSource::Vault);
+        }
+        #[cfg(not(feature = "vault"))]
+        if let Some(ref secret_path) = self.vault_secret_path {
+            tracing::warn!(
+                "ignoring vault_secret_path {secret_path}, enable the vault feature to use it"
+            );
+        }
+
+        // Secret shares take priority over any single-source key
+        #[cfg(feature = "sss")]
+        if self.secret_shares.is_some() {
+            return Ok(SecretSource::Shares);
+        }
+        #[cfg(not(feature = "sss"))]
+        if let Some(ref shares) = self.secret_shares {
+            tracing::warn!(
+                "ignoring {} secret_shares, enable the sss feature to use them",
+                shares.len()
+            );
+        }
+
+        // Try secret_key first (simpler case)
+        if self.secret.is_some() {
+            return Ok(if self.secret_from_env {
+                SecretSource::Env
+            } else {
+                SecretSource::Embedded
+            });
+        }
+
+        if self.secret_command.is_some() {
+            return Ok(SecretSource::Command);
+        }
+
+        // Then try secret_path
+        if let Some(url) = self.secret_url()?
+            && url.scheme() != "file"
+        {
+            return Ok(SecretSource::Url);
+        }
+        #[cfg(feature = "gpg")]
+        if self.secret_path.as_deref().is_some_and(is_gpg_file) {
+            return Ok(SecretSource::Gpg);
+        }
+        Ok(SecretSource::Path)
+    }
+
+    /// Resolve the key from exactly one source, ignoring the precedence
+    /// [`FireblocksConfig::get_key`] applies
+    ///
+    /// Returns [`Error::MissingSecret`] when that source is not configured.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::{FireblocksConfig, SecretSource};
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let key = config.get_key_from(SecretSource::Path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn get_key_from(&self, source: SecretSource) -> Result<Vec<u8>> {
+        match source {
+            SecretSource::Embedded => self
+                .secret
+                .as_ref()
+                .map(|key| key.clone().into_bytes())
+                .ok_or(Error::MissingSecret),
+            SecretSource::Path => self.read_secret_path(),
+            SecretSource::Env => self
+                .options
+                .env
+                .vars()
+                .and_then(|mut vars| vars.remove(SecretSource::ENV_VAR))
+                .map(String::into_bytes)
+                .ok_or(Error::MissingSecret),
+            SecretSource::Command => self
+                .secret_command
+                .as_deref()
+                .ok_or(Error::MissingSecret)
+                .and_then(run_secret_command),
+            #[cfg(feature = "gpg")]
+            SecretSource::Gpg => self.decrypt_gpg_file(&self.checked_secret_path()?),
+            SecretSource::Url => match self.secret_url()? {
+                Some(url) if url.scheme() != "file" => self.fetch_secret_url(&url),
+                _ => Err(Error::MissingSecret),
+            },
+            #[cfg(feature = "sss")]
+            SecretSource::Shares => self
+                .secret_shares
+                .as_deref()
+                .ok_or(Error::MissingSecret)
+                .and_then(|shares| self.combine_secret_shares(shares)),
+            #[cfg(feature = "aws")]
+            SecretSource::AwsSecretsManager => self
+                .secret_aws_arn
+                .as_deref()
+                .ok_or(Error::MissingSecret)
+                .and_then(crate::aws::fetch_secret),
+            #[cfg(feature = "kms")]
+            SecretSource::Kms => self
+                .secret_kms_ciphertext
+                .as_deref()
+                .ok_or(Error::MissingSecret)
+                .and_then(|ciphertext| {
+                    crate::kms::decrypt(ciphertext, self.secret_kms_key_id.as_deref())
+                }),
+            #[cfg(feature = "vault")]
+            SecretSource::Vault => self
+                .vault_secret_path
+                .as_deref()
+                .ok_or(Error::MissingSecret)
+                .and_then(|secret_path| {
+                    crate::vault::fetch_secret(
+                        self.vault_addr.as_deref().unwrap_or_default(),
+                        self.vault_token_env
+                            .as_deref()
+                            .unwrap_or(crate::vault::DEFAULT_TOKEN_ENV),
+                        secret_path,
+                    )
+                }),
+        }
+    }
+
+    /// Resolve the key like [`FireblocksConfig::get_key`], prompting for it
+    /// on the terminal when no secret source is configured
+    ///
+    /// The key is read as a single line without echo. Without a terminal on
+    /// stdin nothing is prompted and [`Error::MissingSecret`] is returned.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let key = config.get_key_interactive()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "interactive")]
+    pub fn get_key_interactive(&self) -> Result<Vec<u8>> {
+        use std::io::IsTerminal;
+        self.prompt_key(std::io::stdin().is_terminal())
+    }
+
+    #[cfg(feature = "interactive")]
+    pub(crate) fn prompt_key(&self, terminal: bool) -> Result<Vec<u8>> {
+        if self.has_secret_source() {
+            return self.get_key();
+        }
+        if !terminal {
+            return Err(Error::MissingSecret);
+        }
+        let key = rpassword::prompt_password("Fireblocks private key: ")?;
+        Ok(key.into_bytes())
+    }
+
+    /// Whether any source [`FireblocksConfig::get_key`] reads is configured
+    fn has_secret_source(&self) -> bool {
+        self.secret.is_some()
+            || self.secret_command.is_some()
+            || self.secret_path.is_some()
+            || self.secret_shares.is_some()
+            || self.secret_aws_arn.is_some()
+            || self.secret_kms_ciphertext.is_some()
+            || self.vault_secret_path.is_some()
+    }
+
+    /// Read the key from `secret_path`, decrypting `.gpg` files when the
+    /// `gpg` feature is enabled
+    fn read_secret_path(&self) -> Result<Vec<u8>> {
+        if let Some(url) = self.secret_url()?
+            && url.scheme() != "file"
+        {
+            return self.fetch_secret_url(&url);
+        }
+        let expanded_path = self.checked_secret_path()?;
+
+        #[cfg(feature = "gpg")]
+        if is_gpg_file(&expanded_path) {
+            return self.decrypt_gpg_file(&expanded_path);
+        }
+
+        // Regular file read
+        fs::read(&expanded_path).map_err(|e| Error::IOError {
+            source: e,
+            path: expanded_path.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        })
+    }
+
+    /// Resolve the public key verifying Fireblocks webhooks
+    ///
+    /// An inline `webhook_public_key` wins, then the file at
+    /// `webhook_public_key_path`, then the published key when
+    /// `webhook_public_key` is [`FireblocksConfig::WEBHOOK_BUILTIN`]. Without
+    /// any of them [`Error::NotPresent`] is returned.
+    ///
+    /// No published key is bundled yet, so the builtin selection fails with
+    /// [`Error::BuiltinWebhookKeyUnavailable`] naming the environment.
+    pub fn get_webhook_key(&self) -> Result<Vec<u8>> {
+        let builtin = match self.webhook_public_key.as_deref() {
+            Some(Self::WEBHOOK_BUILTIN) => true,
+            Some(key) => return Ok(key.as_bytes().to_vec()),
+            None => false,
+        };
+        if let Some(ref path) = self.webhook_public_key_path {
+            let path = self.resolve_path(path)?;
+            return fs::read(&path).map_err(|e| Error::IOError {
+                source: e,
+                path: path.to_string_lossy().to_string(),
+                backtrace: capture_backtrace(),
+            });
+        }
+        if builtin {
+            return Err(Error::BuiltinWebhookKeyUnavailable {
+                mainnet: self.mainnet,
+            });
+        }
+        Err(Error::NotPresent {
+            key: "webhook_public_key".to_string(),
+        })
+    }
+
+    /// Take the signer settings, dropping the rest of the config
+    pub fn into_signer(self) -> Signer {
+        self.signer
+    }
+
+    /// The signer settings paired with the key from
+    /// [`FireblocksConfig::get_key`], everything a signing path needs
+    pub fn signer_with_key(&self) -> Result<(Signer, Vec<u8>)> {
+        Ok((self.signer.clone(), self.get_key()?))
+    }
+
+    /// Check that [`FireblocksConfig::get_key`] resolves to a PEM encoded
+    /// private key
+    ///
+    /// `get_key` itself accepts any bytes, call this at startup to catch a
+    /// `secret_path` pointing at the wrong file.
+    #[cfg(feature = "pem")]
+    pub fn validate_key_pem(&self) -> Result<()> {
+        let key = self.get_key()?;
+        let pem = pem::parse(&key).map_err(|e| Error::InvalidKey(e.to_string()))?;
+        if pem.tag().ends_with("PRIVATE KEY") {
+            Ok(())
+        } else {
+            Err(Error::InvalidKey(format!(
+                "expected a private key, found {}",
+                pem.tag()
+            )))
+        }
+    }
+
+    /// `secret_path` with `~` expanded and a relative path resolved against
+    /// the directory of the base config file
+    ///
+    /// A relative path that does not exist there is taken relative to the
+    /// working directory instead, so configs written against the working
+    /// directory keep loading.
+    pub(crate) fn expanded_secret_path(&self) -> Result<PathBuf> {
+        let path = self.secret_path.as_ref().ok_or(Error::MissingSecret)?;
+        match self.secret_url()? {
+            Some(url) if url.scheme() == "file" => url
+                .to_file_path()
+                .map_err(|()| Error::InvalidPath(url.to_string())),
+            Some(_) => Ok(path.clone()),
+            None => self.resolve_path(path),
+        }
+    }
+
+    /// `secret_path` as a URL when it starts with a scheme, e.g. `file://` or
+    /// `https://`
+    fn secret_url(&self) -> Result<Option<url::Url>> {
+        let Some(path) = self.secret_path.as_ref().and_then(|p| p.to_str()) else {
+            return Ok(None);
+        };
+        if !has_url_scheme(path) {
+            return Ok(None);
+        }
+        url::Url::parse(path)
+            .map(Some)
+            .map_err(|e| Error::InvalidUrl {
+                key: "secret_path".to_string(),
+                value: path.to_string(),
+                reason: e.to_string(),
+            })
+    }
+
+    /// Fetch the key from a `secret_path` URL other than `file://`
+    fn fetch_secret_url(&self, url: &url::Url) -> Result<Vec<u8>> {
+        match url.scheme() {
+            #[cfg(feature = "reqwest")]
+            "https" => {
+                let token = match self.secret_url_auth_env {
+                    Some(ref var) => Some(std::env::var(var).map_err(|_| Error::SecretFetch {
+                        url: url.to_string(),
+                        reason: format!("token variable {var} is not set"),
+                    })?),
+                    None => None,
+                };
+                crate::http::fetch_secret(&self.http, url, token.as_deref())
+            }
+            #[cfg(not(feature = "reqwest"))]
+            "https" => {
+                tracing::warn!("enable the reqwest feature to fetch secret_path {url}");
+                Err(Error::UnsupportedSecretScheme("https".to_string()))
+            }
+            scheme => Err(Error::UnsupportedSecretScheme(scheme.to_string())),
+        }
+    }
+
+    /// Expand `~` in `path` and resolve it against the base config directory
+    /// like [`FireblocksConfig::expanded_secret_path`]
+    fn resolve_path(&self, path: &Path) -> Result<PathBuf> {
+        let path = expand_path(path)?;
+        if path.is_relative()
+            && let Some(dir) = self.config_dir()
+        {
+            let resolved = dir.join(&path);
+            if resolved.exists() {
+                return Ok(resolved);
+            }
+        }
+        Ok(path)
+    }
+
+    /// [`FireblocksConfig::expanded_secret_path`], canonicalized and checked
+    /// against `secret_path_root` when that is set
+    fn checked_secret_path(&self) -> Result<PathBuf> {
+        let path = self.expanded_secret_path()?;
+        let Some(root) = &self.secret_path_root else {
+            return Ok(path);
+        };
+        let canonicalize = |path: &Path| {
+            path.canonicalize().map_err(|e| Error::IOError {
+                source: e,
+                path: path.to_string_lossy().to_string(),
+                backtrace: capture_backtrace(),
+            })
+        };
+        let root = canonicalize(&self.resolve_path(root)?)?;
+        let resolved = canonicalize(&path)?;
+        if !resolved.starts_with(&root) {
+            return Err(Error::SecretPathOutsideRoot {
+                path: path.to_string_lossy().to_string(),
+                root: root.to_string_lossy().to_string(),
+            });
+        }
+        Ok(resolved)
+    }
+
+    /// Directory of the base config file, when loaded from a file
+    fn config_dir(&self) -> Option<&Path> {
+        match self.sources.first()? {
+            ConfigSource::File(path) => path.parent(),
+            ConfigSource::Environment => None,
+        }
+    }
+
+    /// The local file `secret_path` names, encrypted or not, `None` when it
+    /// is unset or a remote URL
+    pub(crate) fn local_secret_file(&self) -> Option<PathBuf> {
+        if self
+            .secret_url()
+            .ok()?
+            .is_some_and(|url| url.scheme() != "file")
+        {
+            return None;
+        }
+        self.checked_secret_path().ok()
+    }
+
+    /// The plain (unencrypted) file `get_key` reads, when no other secret
+    /// source takes precedence over `secret_path`
+    #[cfg(feature = "async")]
+    fn plain_secret_file(&self) -> Option<PathBuf> {
+        if self.secret_aws_arn.is_some()
+            || self.secret_kms_ciphertext.is_some()
+            || self.vault_secret_path.is_some()
+            || self.secret_shares.is_some()
+            || self.secret.is_some()
+            || self.secret_command.is_some()
+        {
+            return None;
+        }
+        let path = self.local_secret_file()?;
+        #[cfg(feature = "gpg")]
+        if is_gpg_file(&path) {
+            return None;
+        }
+        Some(path)
+    }
+
+    #[cfg(feature = "sss")]
+    fn combine_secret_shares(&self, shares: &[String]) -> Result<Vec<u8>> {
+        let shares = shares
+            .iter()
+            .map(|share| {
+                let bytes = hex::decode(share.trim()).map_err(|e| {
+                    Error::SecretReconstruction(format!("invalid share encoding: {e}"))
+                })?;
+                sharks::Share::try_from(bytes.as_slice())
+                    .map_err(|e| Error::SecretReconstruction(e.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let threshold = if self.has_extra("secret_shares_threshold") {
+            self.get_extra::<u8, _>("secret_shares_threshold")?
+        } else {
+            u8::try_from(shares.len()).map_err(|_| {
+                Error::SecretReconstruction(format!("too many shares: {}", shares.len()))
+            })?
+        };
+
+        sharks::Sharks(threshold)
+            .recover(&shares)
+            .map_err(|e| Error::SecretReconstruction(e.to_string()))
+    }
+
+    #[cfg(feature = "gpg")]
+    fn decrypt_gpg_file(&self, path: &Path) -> Result<Vec<u8>> {
+        let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+
+        let mut input = fs::File::open(path).map_err(|e| Error::IOError {
+            source: e,
+            path: path.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        })?;
+
+        let mut output = Vec::new();
+        ctx.decrypt(&mut input, &mut output)?;
+
+        Ok(output)
+    }
+}
+impl FireblocksConfig {
+    /// Environment variable naming the base config [`FireblocksConfig::init`]
+    /// falls back to when there is no XDG config
+    pub const CONFIG_ENV_VAR: &str = "FIREBLOCKS_CONFIG";
+    /// Environment variable listing the profiles, comma separated,
+    /// [`FireblocksConfig::init_from_config_env`] layers over the base config
+    pub const PROFILE_ENV_VAR: &str = "FIREBLOCKS_PROFILE";
+    /// System-wide base config, the last place [`FireblocksConfig::init`]
+    /// looks
+    pub const SYSTEM_CONFIG: &str = "/etc/fireblocks/default.toml";
+
+    /// Load `cfg`, then each of `cfg_overrides` in order, then the
+    /// `FIREBLOCKS_*` environment variables
+    ///
+    /// The overrides share the base path's type so that `&[]` needs no
+    /// annotation. Use [`FireblocksConfig::with_overrides`] to pass, e.g., a
+    /// `&str` base with `PathBuf` overrides.
+    pub fn new<P: AsRef<Path>>(cfg: P, cfg_overrides: &[P]) -> Result<Self> {
+        Self::new_with_kv::<P, &str>(cfg, cfg_overrides, &[])
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`], warning about every
+    /// scalar key more than one of the files sets
+    ///
+    /// Each warning names the key and the file whose value wins, the last
+    /// one to set it, to surface accidental double definitions. Parents
+    /// pulled in through `extends` and inline profiles are not compared.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new_warn_conflicts("config.toml", &["a.toml", "b.toml"])?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new_warn_conflicts<P: AsRef<Path>>(cfg: P, cfg_overrides: &[P]) -> Result<Self> {
+        let mut set_by: BTreeMap<String, Vec<&Path>> = BTreeMap::new();
+        for path in std::iter::once(cfg.as_ref()).chain(cfg_overrides.iter().map(AsRef::as_ref)) {
+            let mut table = read_layer(path)?;
+            take_profiles(&mut table);
+            table.remove("extends");
+            let mut keys = Vec::new();
+            scalar_keys("", &table, &mut keys);
+            for key in keys {
+                set_by.entry(key).or_default().push(path);
+            }
+        }
+        for (key, paths) in set_by.iter().filter(|(_, paths)| paths.len() > 1) {
+            if let Some(winner) = paths.last() {
+                tracing::warn!(
+                    key = key.as_str(),
+                    winner = %winner.display(),
+                    files = paths.len(),
+                    "config key set by more than one file"
+                );
+            }
+        }
+        Self::new(cfg, cfg_overrides)
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`] without the
+    /// environment overlay, so stray `FIREBLOCKS_*` variables cannot change
+    /// the result
+    ///
+    /// Meant for tests and sandboxed loaders that need reproducible configs.
+    pub fn new_no_env<P: AsRef<Path>>(cfg: P, cfg_overrides: &[P]) -> Result<Self> {
+        Self::new_layered(cfg, cfg_overrides, LoadOptions {
+            env: EnvSource::Disabled,
+            ..LoadOptions::default()
+        })
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`], taking the
+    /// environment overlay from `env` instead of the process environment
+    ///
+    /// Precedence is unchanged, `env` holds `FIREBLOCKS_*` names as they
+    /// would be exported. This makes loads hermetic, e.g. tests running in
+    /// parallel with different variables. [`FireblocksConfig::reload`]
+    /// reuses the same variables.
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let env = [("FIREBLOCKS_SIGNER__VAULT".to_string(), "7".to_string())];
+    /// let config = FireblocksConfig::new_with_env("examples/default.toml", &[], env)?;
+    /// assert_eq!(config.signer.vault, "7");
+    /// # Ok::<(), fireblocks_config::Error>(())
+    /// ```
+    pub fn new_with_env<P: AsRef<Path>>(
+        cfg: P,
+        cfg_overrides: &[P],
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self> {
+        Self::new_layered(cfg, cfg_overrides, LoadOptions {
+            env: EnvSource::Vars(env.into_iter().collect()),
+            ..LoadOptions::default()
+        })
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`] with different
+    /// baseline defaults
+    ///
+    /// `defaults` only apply to keys that no file or environment variable
+    /// sets.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use {
+    ///     fireblocks_config::{Defaults, FireblocksConfig, OutputFormat},
+    ///     std::time::Duration,
+    /// };
+    ///
+    /// let defaults = Defaults {
+    ///     poll_timeout: Some(Duration::from_secs(600)),
+    ///     output: Some(OutputFormat::Json),
+    ///     ..Defaults::default()
+    /// };
+    /// let config = FireblocksConfig::new_with_defaults("config.toml", &[], defaults)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new_with_defaults<P: AsRef<Path>>(
+        cfg: P,
+        cfg_overrides: &[P],
+        defaults: Defaults,
+    ) -> Result<Self> {
+        Self::new_layered(cfg, cfg_overrides, LoadOptions {
+            defaults: Some(defaults),
+            ..LoadOptions::default()
+        })
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`], then apply
+    /// `key=value` overrides above the files but below environment variables
+    ///
+    /// Keys are dotted paths into the config. `true` and `false` are read as
+    /// booleans, numeric values as numbers and anything else as a string. A
+    /// pair without `=` returns [`Error::InvalidOverride`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new_with_kv("config.toml", &[], &[
+    ///     "signer.vault=9",
+    ///     "display.output=json",
+    ///     "extra.rpc_url=https://rpc.example.com",
+    /// ])?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new_with_kv<P: AsRef<Path>, S: AsRef<str>>(
+        cfg: P,
+        cfg_overrides: &[P],
+        kv: &[S],
+    ) -> Result<Self> {
+        Self::new_layered(cfg, cfg_overrides, LoadOptions {
+            kv: kv.iter().map(|pair| pair.as_ref().to_string()).collect(),
+            ..LoadOptions::default()
+        })
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`] with values taken
+    /// from command-line flags, as `(key, value)` pairs with dotted keys
+    ///
+    /// Sources are applied in this order, later ones winning:
+    ///
+    /// 1. the base file and its `extends` chain
+    /// 2. override files
+    /// 3. the CLI pairs
+    /// 4. `FIREBLOCKS_*` environment variables
+    ///
+    /// Use [`FireblocksConfig::new_with_cli_precedence`] to apply the CLI
+    /// pairs above the environment instead. Values are coerced like
+    /// [`FireblocksConfig::new_with_kv`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let flags = [(
+    ///     "url".to_string(),
+    ///     "https://api.fireblocks.io/v1".to_string(),
+    /// )];
+    /// let config = FireblocksConfig::new_with_cli("config.toml", &[], &flags)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new_with_cli<P: AsRef<Path>>(
+        cfg: P,
+        cfg_overrides: &[P],
+        cli: &[(String, String)],
+    ) -> Result<Self> {
+        Self::new_with_cli_precedence(cfg, cfg_overrides, cli, OverridePrecedence::BelowEnv)
+    }
+
+    /// Load configuration like [`FireblocksConfig::new_with_cli`], choosing
+    /// whether the CLI pairs are applied below or above the environment
+    pub fn new_with_cli_precedence<P: AsRef<Path>>(
+        cfg: P,
+        cfg_overrides: &[P],
+        cli: &[(String, String)],
+        precedence: OverridePrecedence,
+    ) -> Result<Self> {
+        Self::new_layered(cfg, cfg_overrides, LoadOptions {
+            kv: cli.iter().map(|(k, v)| format!("{k}={v}")).collect(),
+            precedence,
+            ..LoadOptions::default()
+        })
+    }
+
+    fn new_layered<P: AsRef<Path>>(
+        cfg: P,
+        cfg_overrides: &[P],
+        options: LoadOptions,
+    ) -> Result<Self> {
+        let cfg_path = cfg.as_ref();
+        let _span = load_span(cfg_path, cfg_overrides.len()).entered();
+        log_source(SourceKind::Base, cfg_path);
+
+        let layers = resolve_extends(cfg_path)?;
+        let mut overrides = Vec::with_capacity(cfg_overrides.len());
+        for override_path in cfg_overrides {
+            let path = override_path.as_ref();
+            log_source(SourceKind::Override, path);
+            overrides.push(read_layer(path)?);
+        }
+        let files = source_paths(cfg_path, cfg_overrides);
+        Self::build_layered(layers, overrides, files, options)
+    }
+
+    /// Layer the tables read by [`FireblocksConfig::new_layered`] or
+    /// [`FireblocksConfig::new_async`] and load them according to `options`
+    ///
+    /// `layers` is the `extends` chain of the base file, `overrides` the
+    /// override files in order and `files` the paths they were read from.
+    fn build_layered(
+        mut layers: Vec<toml::Table>,
+        overrides: Vec<toml::Table>,
+        files: Vec<ConfigSource>,
+        options: LoadOptions,
+    ) -> Result<Self> {
+        // the base file is the last layer of its `extends` chain
+        if let (Some(inline), Some(base)) = (&options.inline, layers.last_mut()) {
+            let overlay = take_inline(base, inline)?;
+            layers.push(overlay);
+        }
+        let mut config_builder = layered_builder(layers)?;
+        if let Some(ref defaults) = options.defaults {
+            config_builder = defaults.seed(config_builder)?;
+        }
+
+        // Add all override files in order
+        for table in &overrides {
+            config_builder = config_builder.add_source(table_source(table)?);
+        }
+        let kv_layer = if options.kv.is_empty() {
+            None
+        } else {
+            Some(kv_layer(&options.kv)?)
+        };
+        let env = &options.env;
+        let mut conf = match options.precedence {
+            OverridePrecedence::BelowEnv => {
+                if let Some(layer) = kv_layer {
+                    config_builder = config_builder.add_source(layer);
+                }
+                Self::load_layers(config_builder, files, None, env)?
+            }
+            OverridePrecedence::AboveEnv => {
+                Self::load_layers(config_builder, files, kv_layer, env)?
+            }
+        };
+        conf.options = options;
+        Ok(conf)
+    }
+
+    /// Load a fresh config from the same files (and the current environment)
+    /// this one was built from
+    ///
+    /// Only configs loaded from files through [`FireblocksConfig::new`] and
+    /// the constructors built on it can be reloaded, anything else returns
+    /// [`Error::NotReloadable`].
+    pub fn reload(&self) -> Result<Self> {
+        self.reload_options(self.options.clone())
+    }
+
+    fn reload_options(&self, options: LoadOptions) -> Result<Self> {
+        let files: Vec<&Path> = self
+            .sources
+            .iter()
+            .filter_map(|source| match source {
+                ConfigSource::File(path) => Some(path.as_path()),
+                ConfigSource::Environment => None,
+            })
+            .collect();
+        let (base, overrides) = files.split_first().ok_or(Error::NotReloadable)?;
+        tracing::debug!("reloading config {}", base.display());
+        let mut conf = Self::new_layered(*base, overrides, options)?;
+        conf.extra_aliases = self.extra_aliases.clone();
+        Ok(conf)
+    }
+
+    /// Load a fresh config like [`FireblocksConfig::reload`], with `env` as
+    /// the environment overlay
+    ///
+    /// Every other option of the original load, such as `key=value` pairs
+    /// and their precedence, is kept. This gives the constructors without an
+    /// `env` parameter a hermetic form, see
+    /// [`FireblocksConfig::new_with_env`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new_with_kv("examples/default.toml", &[], &["mainnet=true"])?;
+    /// let env = [("FIREBLOCKS_SIGNER__VAULT".to_string(), "7".to_string())];
+    /// let config = config.reload_with_env(env)?;
+    /// assert!(config.mainnet);
+    /// assert_eq!(config.signer.vault, "7");
+    /// # Ok::<(), fireblocks_config::Error>(())
+    /// ```
+    pub fn reload_with_env(&self, env: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        let mut options = self.options.clone();
+        options.env = EnvSource::Vars(env.into_iter().collect());
+        self.reload_options(options)
+    }
+
+    /// Layer one more override file on top of this config, e.g. one picked
+    /// from the detected cluster, without reading the other files again
+    ///
+    /// The result matches loading the files with the override appended,
+    /// except that `FIREBLOCKS_*` variables are not applied again: values
+    /// this config took from the environment stay below the new file. Use
+    /// [`FireblocksConfig::reload`] on the result to put the environment
+    /// back on top.
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("examples/default.toml", &[])?;
+    /// let config = config.apply_override("examples/override.toml")?;
+    /// assert!(config.mainnet);
+    /// # Ok::<(), fireblocks_config::Error>(())
+    /// ```
+    pub fn apply_override<P: AsRef<Path>>(&self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        tracing::debug!("applying config override: {}", path.display());
+        let table = read_layer(path)?;
+        let mut files = self.sources.clone();
+        files.push(ConfigSource::File(path.to_path_buf()));
+        self.layer(table_source(&table)?, files)
+    }
+
+    /// Layer TOML from memory on top of this config, like
+    /// [`FireblocksConfig::apply_override`]
+    ///
+    /// The string is not recorded in [`FireblocksConfig::sources`], so
+    /// [`FireblocksConfig::reload`] drops it.
+    pub fn apply_override_str(&self, toml: &str) -> Result<Self> {
+        tracing::debug!("applying config override from memory");
+        let mut table = parse_toml(Path::new("memory"), toml)?;
+        normalize_table(&mut table)?;
+        self.layer(table_source(&table)?, self.sources.clone())
+    }
+
+    /// Deserialize `source` layered on top of this config, with tables such as
+    /// `[extra]` merged key by key
+    fn layer<S>(&self, source: S, files: Vec<ConfigSource>) -> Result<Self>
+    where
+        S: config::Source + Send + Sync + 'static,
+    {
+        let load_error = |source| load_error(&self.sources, false, source);
+        let sets_secret = source.collect().map_err(load_error)?.contains_key("secret");
+        let mut builder = Config::builder().add_source(Config::try_from(self).map_err(load_error)?);
+        // Secrets are never serialized, carry them over below the new layer
+        if let Some(secret) = &self.secret {
+            builder = builder
+                .set_default("secret", secret.as_str())
+                .map_err(load_error)?;
+        }
+        if let Some(shares) = &self.secret_shares {
+            builder = builder
+                .set_default("secret_shares", shares.clone())
+                .map_err(load_error)?;
+        }
+        let mut conf = Self::load_layers(
+            builder.add_source(source),
+            files,
+            None,
+            &EnvSource::Disabled,
+        )?;
+        conf.unknown_keys.extend(self.unknown_keys.iter().cloned());
+        conf.unknown_keys.sort();
+        conf.unknown_keys.dedup();
+        conf.options = self.options.clone();
+        conf.extra_aliases = self.extra_aliases.clone();
+        conf.secret_from_env = self.secret_from_env && !sets_secret;
+        Ok(conf)
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`], reading files with
+    /// `tokio::fs` so the async runtime is not blocked
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// # async fn run() -> fireblocks_config::Result<()> {
+    /// let config = FireblocksConfig::new_async("config.toml", &["prod.toml"]).await?;
+    /// let key = config.get_key_async().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn new_async<P: AsRef<Path>>(cfg: P, cfg_overrides: &[P]) -> Result<Self> {
+        use tracing::Instrument;
+
+        let cfg_path = cfg.as_ref();
+        let load = async {
+            log_source(SourceKind::Base, cfg_path);
+            let layers = resolve_extends_async(cfg_path).await?;
+            let mut overrides = Vec::with_capacity(cfg_overrides.len());
+            for override_path in cfg_overrides {
+                let path = override_path.as_ref();
+                log_source(SourceKind::Override, path);
+                let contents = read_config_async(path).await?;
+                let mut table = parse_toml(path, &contents)?;
+                normalize_table(&mut table)?;
+                overrides.push(table);
+            }
+            let files = source_paths(cfg_path, cfg_overrides);
+            Self::build_layered(layers, overrides, files, LoadOptions::default())
+        };
+        load.instrument(load_span(cfg_path, cfg_overrides.len()))
+            .await
+    }
+
+    /// Resolve the key like [`FireblocksConfig::get_key`] without blocking the
+    /// async runtime
+    ///
+    /// Plain key files are read with `tokio::fs`, every other source (e.g.
+    /// GPG decryption) runs on the blocking thread pool.
+    #[cfg(feature = "async")]
+    pub async fn get_key_async(&self) -> Result<Vec<u8>> {
+        if let Some(path) = self.plain_secret_file() {
+            return tokio::fs::read(&path).await.map_err(|e| Error::IOError {
+                source: e,
+                path: path.to_string_lossy().to_string(),
+                backtrace: capture_backtrace(),
+            });
+        }
+        let cfg = self.clone();
+        tokio::task::spawn_blocking(move || cfg.get_key())
+            .await
+            .map_err(std::io::Error::from)?
+    }
+
+    /// Apply the environment overlay and deserialize the layered sources
+    pub(crate) fn load(config_builder: ConfigBuilder<DefaultState>) -> Result<Self> {
+        Self::load_layers(config_builder, Vec::new(), None, &EnvSource::Process)
+    }
+
+    /// Apply the environment overlay from `env`, then `above_env` when
+    /// given, and deserialize the layered sources
+    ///
+    /// `files` are the files already added to `config_builder`, in order.
+    fn load_layers(
+        mut config_builder: ConfigBuilder<DefaultState>,
+        files: Vec<ConfigSource>,
+        above_env: Option<Config>,
+        env: &EnvSource,
+    ) -> Result<Self> {
+        // Environment variables take precedence over everything but
+        // `above_env`
+        let mut env_used = false;
+        let mut secret_from_env = false;
+        // the variables that pick files are left out
+        if let Some(vars) = env.vars() {
+            let environment = config::Environment::with_prefix("FIREBLOCKS")
+                .prefix_separator("_")
+                .separator("__")
+                .try_parsing(true)
+                .source(Some(vars));
+            let collected = environment
+                .collect()
+                .map_err(|e| load_error(&files, true, e))?;
+            env_used = !collected.is_empty();
+            secret_from_env = collected.contains_key("secret");
+            config_builder = config_builder.add_source(environment);
+        }
+        tracing::Span::current().record("env", env_used);
+        if let Some(layer) = above_env {
+            secret_from_env &= layer.get_string("secret").is_err();
+            config_builder = config_builder.add_source(layer);
+        }
+
+        let load_error = |source| load_error(&files, env_used, source);
+        let mut table = config_builder
+            .build()
+            .and_then(|config| config.collect())
+            .map_err(load_error)?;
+        migrate_table(&mut table)?;
+        resolve_aliases(&mut table)?;
+        let unknown = unknown_keys(&table);
+        let keys = count_keys(&table);
+        let mut conf: Self = config::Value::new(None, ValueKind::Table(table))
+            .try_deserialize()
+            .map_err(load_error)?;
+        for (key, origin) in &unknown {
+            match origin {
+                Some(origin) => tracing::warn!("unknown config key {key} in {origin}"),
+                None => tracing::warn!("unknown config key {key}"),
+            }
+        }
+        conf.unknown_keys = unknown.into_iter().map(|(key, _)| key).collect();
+        conf.secret_from_env = secret_from_env;
+        conf.migrate_legacy_network()?;
+        conf.loaded_mtime = match files.first() {
+            Some(ConfigSource::File(path)) => fs::metadata(path).and_then(|m| m.modified()).ok(),
+            _ => None,
+        };
+        conf.sources = files;
+        if env_used {
+            conf.sources.push(ConfigSource::Environment);
+        }
+        conf.applied_sources = SourceInfo::describe(&conf.sources);
+        tracing::debug!(
+            keys,
+            unknown = conf.unknown_keys.len(),
+            sources = conf.sources.len(),
+            "config loaded"
+        );
+        if conf.treat_unknown_as_error && !conf.unknown_keys.is_empty() {
+            return Err(Error::UnknownKeys {
+                keys: conf.unknown_keys,
+            });
+        }
+        conf.validate()?;
+        Ok(conf)
+    }
+
+    /// Check the loaded values, this runs automatically on every load
+    pub fn validate(&self) -> Result<()> {
+        if self.secret.is_some() && self.secret_path.is_some() {
+            tracing::warn!(
+                "both secret and secret_path are set, get_key uses secret and ignores {}",
+                self.secret_path
+                    .as_deref()
+                    .unwrap_or(Path::new(""))
+                    .display()
+            );
+        }
+        self.api_key.validate(self.allow_nonstandard_api_key)?;
+        self.check_https()?;
+        self.check_log_level()?;
+        self.signer.validate()?;
+        self.http.validate()?;
+        self.paging.validate()?;
+        self.network.validate()?;
+        self.callback
+            .as_ref()
+            .map_or(Ok(()), CallbackConfig::validate)
+    }
+
+    /// Collect every problem with the loaded values instead of stopping at
+    /// the first, so a `config check` style command can report them in one
+    /// pass
+    ///
+    /// Besides what [`FireblocksConfig::validate`] checks, this reports
+    /// problems loading tolerates until the value is used: an invalid `url`,
+    /// a non-numeric vault id, no secret source or a missing key file, and a
+    /// `url` pointing at the sandbox API with `mainnet` set or the other way
+    /// around.
+    pub fn validate_all(&self) -> Vec<Error> {
+        let mut checks = vec![
+            self.api_key.validate(self.allow_nonstandard_api_key),
+            self.check_url(),
+            self.check_https(),
+            self.check_log_level(),
+            self.check_environment(),
+            self.signer.validate(),
+            self.signer.vault_id().map(|_| ()),
+            self.check_secret(),
+            self.http.validate(),
+            self.paging.validate(),
+        ];
+        checks.extend(self.network.checks());
+        if let Some(ref callback) = self.callback {
+            checks.extend(callback.checks());
+        }
+        checks.into_iter().filter_map(Result::err).collect()
+    }
+
+    fn check_url(&self) -> Result<()> {
+        match self.url.trim() {
+            "" => Ok(()),
+            url => validate_url("url", url, &["http", "https"]),
+        }
+    }
+
+    /// Check that `url` uses `https` when `require_https` is set, loopback
+    /// hosts excepted
+    ///
+    /// A `url` that does not parse is left to [`FireblocksConfig::check_url`].
+    fn check_https(&self) -> Result<()> {
+        if !self.require_https {
+            return Ok(());
+        }
+        match url::Url::parse(self.url.trim()) {
+            Ok(url) if url.scheme() == "http" && !is_loopback(&url) => Err(Error::InsecureUrl {
+                key: "url".to_string(),
+                url: self.url.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that a Fireblocks API `url` agrees with `mainnet`
+    fn check_environment(&self) -> Result<()> {
+        let host = |url: &str| url::Url::parse(url).ok()?.host_str().map(str::to_string);
+        let Some(url_host) = host(self.resolved_url()) else {
+            return Ok(());
+        };
+        let mainnet = if Some(&url_host) == host(Self::PRODUCTION_URL).as_ref() {
+            true
+        } else if Some(&url_host) == host(Self::SANDBOX_URL).as_ref() {
+            false
+        } else {
+            return Ok(());
+        };
+        if mainnet == self.mainnet {
+            Ok(())
+        } else {
+            Err(Error::EnvironmentMismatch {
+                url: self.resolved_url().to_string(),
+                mainnet: self.mainnet,
+            })
+        }
+    }
+
+    /// Check that a secret source is set, and that the key file exists when
+    /// `secret_path` is the source [`FireblocksConfig::get_key`] uses
+    fn check_secret(&self) -> Result<()> {
+        if !self.has_secret_source() {
+            return Err(Error::MissingSecret);
+        }
+        let path_is_source = self.secret.is_none()
+            && self.secret_command.is_none()
+            && self.secret_shares.is_none()
+            && self.secret_aws_arn.is_none()
+            && self.secret_kms_ciphertext.is_none()
+            && self.vault_secret_path.is_none();
+        if !path_is_source || self.secret_url()?.is_some_and(|url| url.scheme() != "file") {
+            return Ok(());
+        }
+        let path = self.checked_secret_path()?;
+        fs::metadata(&path).map(|_| ()).map_err(|e| Error::IOError {
+            source: e,
+            path: path.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        })
+    }
+
+    /// Fill unset `[network]` fields from the legacy `[extra]` keys
+    fn migrate_legacy_network(&mut self) -> Result<()> {
+        fn migrate<T: serde::de::DeserializeOwned>(
+            cfg: &FireblocksConfig,
+            key: &str,
+            field: &mut Option<T>,
+        ) -> Result<()> {
+            if !cfg.extra.contains_key(key) {
+                return Ok(());
+            }
+            if field.is_some() {
+                tracing::warn!("ignoring extra.{key}, network.{key} is set");
+            } else {
+                tracing::warn!("extra.{key} is deprecated, use network.{key}");
+                *field = Some(cfg.get_extra(key)?);
+            }
+            Ok(())
+        }
+
+        let mut network = std::mem::take(&mut self.network);
+        migrate(self, "rpc_url", &mut network.rpc_url)?;
+        migrate(self, "ws_url", &mut network.ws_url)?;
+        migrate(self, "commitment", &mut network.commitment)?;
+        migrate(self, "chain_id", &mut network.chain_id)?;
+        migrate(self, "explorer_url", &mut network.explorer_url)?;
+        self.network = network;
+        Ok(())
+    }
 
-#[derive(Clone, Debug, Deserialize)]
-pub struct Signer {
-    #[serde(
-        default = "default_poll_timeout",
-        deserialize_with = "deserialize_duration"
-    )]
-    pub poll_timeout: Duration,
-    #[serde(
-        default = "default_poll_interval",
-        deserialize_with = "deserialize_duration"
-    )]
-    pub poll_interval: Duration,
-    /// The vault id
-    pub vault: String,
-    /// If true, Fireblocks will broadcast the transaction
-    #[serde(default = "default_broadcast")]
-    pub broadcast: bool,
-}
+    /// The full API key as a string
+    #[deprecated(note = "use `api_key.expose()`")]
+    pub fn api_key_str(&self) -> &str {
+        self.api_key.expose()
+    }
 
-impl Default for Signer {
-    fn default() -> Self {
-        Self {
-            poll_timeout: default_poll_timeout(),
-            poll_interval: default_poll_interval(),
-            vault: String::new(),
-            broadcast: default_broadcast(),
-        }
+    /// HTTP client settings from the `[http]` section
+    pub fn http(&self) -> &HttpConfig {
+        &self.http
     }
-}
 
-#[derive(Clone, Debug, Default, Deserialize)]
-pub struct FireblocksConfig {
-    pub api_key: String,
-    pub url: String,
-    pub secret_path: Option<PathBuf>,
-    pub secret: Option<String>,
-    #[serde(rename = "display", default)]
-    pub display_config: DisplayConfig,
-    pub signer: Signer,
-    /// Arbitrary extra configuration values
-    #[serde(default)]
-    pub extra: HashMap<String, serde_json::Value>,
-    /// Enable debug mode
-    #[serde(default)]
-    pub debug: bool,
+    /// Listing defaults from the `[paging]` section
+    pub fn paging(&self) -> &PagingConfig {
+        &self.paging
+    }
 
-    #[serde(default)]
-    pub mainnet: bool,
-}
+    /// Blockchain network settings from the `[network]` section, filled in
+    /// from legacy `[extra]` keys
+    pub fn network(&self) -> &NetworkConfig {
+        &self.network
+    }
 
-impl FireblocksConfig {
-    /// Get an extra configuration value as any deserializable type
-    pub fn get_extra<T, K>(&self, key: K) -> Result<T>
-    where
-        T: serde::de::DeserializeOwned,
-        K: AsRef<str>,
-    {
-        let key_str = key.as_ref();
-        let value = self.extra.get(key_str).ok_or_else(|| Error::NotPresent {
-            key: key_str.to_string(),
-        })?;
+    /// Co-signer callback handler settings from the `[callback]` section
+    pub fn callback(&self) -> Option<&CallbackConfig> {
+        self.callback.as_ref()
+    }
 
-        serde_json::from_value(value.clone()).map_err(|e| {
-            Error::ConfigParseError(config::ConfigError::Message(format!(
-                "Failed to deserialize key '{key_str}': {e}"
-            )))
-        })
+    /// The files this config was loaded from in load order, followed by
+    /// [`ConfigSource::Environment`] when `FIREBLOCKS_*` variables were set
+    ///
+    /// Configs loaded from memory list no files.
+    pub fn sources(&self) -> &[ConfigSource] {
+        &self.sources
     }
 
-    /// Get an extra configuration value as a Duration from seconds
+    /// Modification time of the base file as it was when this config was
+    /// loaded, for "reload if changed" checks without statting again at load
     ///
-    /// This function retrieves a numeric value from the extra configuration
-    /// and converts it to a `std::time::Duration` using
-    /// `Duration::from_secs()`.
+    /// `None` for configs loaded from memory or the environment alone, and
+    /// when the platform does not report it.
     ///
-    /// # Arguments
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
     ///
-    /// * `key` - The configuration key to look up (can be `&str`, `String`,
-    ///   etc.)
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let changed = std::fs::metadata("config.toml")?.modified()?;
+    /// if config.loaded_mtime() != Some(changed) {
+    ///     let config = config.reload()?;
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn loaded_mtime(&self) -> Option<SystemTime> {
+        self.loaded_mtime
+    }
+
+    /// [`FireblocksConfig::sources`] with their index and role, for tools
+    /// that render the layering
+    pub fn applied_sources(&self) -> &[SourceInfo] {
+        &self.applied_sources
+    }
+
+    /// Keys the loaded sources set that no field recognizes, e.g. a
+    /// misspelled `signer.poll_timout`. Keys under `[extra]` are never
+    /// reported.
+    pub fn unknown_keys(&self) -> &[String] {
+        &self.unknown_keys
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`], taking the
+    /// overrides from any iterator
     ///
-    /// # Returns
+    /// The overrides need not share the base path's type. To mix owned and
+    /// borrowed paths in one list, pass them as `&dyn AsRef<Path>`. An empty
+    /// list is logged at debug level, since it loads the base alone.
     ///
-    /// * `Ok(Duration)` - The duration value if the key exists and can be
-    ///   parsed as u64
-    /// * `Err(Error::NotPresent)` - If the key doesn't exist in the
-    ///   configuration
-    /// * `Err(Error::ConfigParseError)` - If the value cannot be deserialized
-    ///   as u64
+    /// # Example
+    /// ```rust,no_run
+    /// use {fireblocks_config::FireblocksConfig, std::path::Path};
     ///
-    /// # Examples
+    /// let profile = format!("{}.toml", "prod");
+    /// let overrides: [&dyn AsRef<Path>; 2] = [&profile, &"local.toml"];
+    /// let config = FireblocksConfig::with_overrides("config.toml", overrides)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_overrides<P: AsRef<Path>, O: AsRef<Path>>(
+        cfg: P,
+        overrides: impl IntoIterator<Item = O>,
+    ) -> Result<Self> {
+        let cfg = cfg.as_ref();
+        let overrides: Vec<PathBuf> = overrides
+            .into_iter()
+            .map(|o| o.as_ref().to_path_buf())
+            .collect();
+        if overrides.is_empty() {
+            tracing::debug!("no overrides given, loading {} alone", cfg.display());
+        }
+        Self::new(cfg.to_path_buf(), &overrides)
+    }
+
+    /// Load configuration like [`FireblocksConfig::new`], expanding each
+    /// override pattern with [`glob`]
     ///
+    /// Matches of a pattern are applied in sorted order, and patterns are
+    /// applied in the order given. A pattern that matches no file is an
+    /// error, blank patterns are skipped.
+    ///
+    /// # Example
     /// ```rust,no_run
-    /// use {fireblocks_config::FireblocksConfig, std::time::Duration};
+    /// use fireblocks_config::FireblocksConfig;
     ///
-    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let config = FireblocksConfig::new_with_globs("config.toml", &["overrides/*.toml"])?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new_with_globs<P: AsRef<Path>, S: AsRef<str>>(cfg: P, patterns: &[S]) -> Result<Self> {
+        let mut overrides = Vec::new();
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            if pattern.trim().is_empty() {
+                continue;
+            }
+            let paths = glob::glob(pattern).map_err(|source| Error::InvalidGlob {
+                pattern: pattern.to_string(),
+                source,
+            })?;
+            let mut matches = Vec::new();
+            for entry in paths {
+                let path = entry.map_err(|e| Error::IOError {
+                    path: e.path().to_string_lossy().to_string(),
+                    source: e.into(),
+                    backtrace: capture_backtrace(),
+                })?;
+                if path.is_file() {
+                    matches.push(path);
+                }
+            }
+            if matches.is_empty() {
+                return Err(Error::ConfigNotFound(format!(
+                    "no files match override pattern {pattern}"
+                )));
+            }
+            matches.sort();
+            tracing::debug!("override pattern {pattern} matched {matches:?}");
+            overrides.extend(matches);
+        }
+        Self::new(cfg.as_ref().to_path_buf(), &overrides)
+    }
+
+    /// Load configuration from a reader, such as an embedded asset or a
+    /// network stream, then apply the environment overlay
     ///
-    /// // Get timeout as Duration (assuming config has: timeout = 30)
-    /// let timeout: Duration = config.get_extra_duration("timeout")?;
-    /// assert_eq!(timeout, Duration::from_secs(30));
+    /// # Example
+    /// ```rust
+    /// use {
+    ///     fireblocks_config::{FileFormat, FireblocksConfig},
+    ///     std::io::Cursor,
+    /// };
+    ///
+    /// let toml = b"api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n[signer]\nvault = \"0\"\n";
+    /// let config = FireblocksConfig::from_reader(Cursor::new(toml), FileFormat::Toml)?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    pub fn get_extra_duration<K>(&self, key: K) -> Result<Duration>
-    where
-        K: AsRef<str>,
-    {
-        let seconds: u64 = self.get_extra(key)?;
-        Ok(Duration::from_secs(seconds))
+    pub fn from_reader<R: Read>(mut reader: R, format: FileFormat) -> Result<Self> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        Self::from_contents(&contents, format)
     }
 
-    /// Check if an extra configuration key exists
-    pub fn has_extra<K>(&self, key: K) -> bool
-    where
-        K: AsRef<str>,
-    {
-        self.extra.contains_key(key.as_ref())
+    /// Load configuration from an in-memory string, then apply the
+    /// environment overlay
+    pub fn from_contents(contents: &str, format: FileFormat) -> Result<Self> {
+        tracing::debug!("using {format:?} config from memory");
+        Self::load(Config::builder().add_source(File::from_str(contents, format)))
     }
 
-    pub fn get_key(&self) -> Result<Vec<u8>> {
-        // Try secret_key first (simpler case)
-        if let Some(ref key) = self.secret {
-            return Ok(key.clone().into_bytes());
-        }
+    /// Deserialize and validate a [`Config`] built by the caller, e.g. with
+    /// sources of its own such as a remote config server
+    ///
+    /// The environment overlay is not applied, add a
+    /// `config::Environment` source to `config` when it should be.
+    ///
+    /// # Example
+    /// ```rust
+    /// use fireblocks_config::{Config, FileFormat, FireblocksConfig};
+    ///
+    /// let toml = "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n[signer]\nvault = \"0\"\n";
+    /// let config = Config::builder()
+    ///     .add_source(config::File::from_str(toml, FileFormat::Toml))
+    ///     .build()?;
+    /// let config = FireblocksConfig::from_config(config)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_config(config: Config) -> Result<Self> {
+        tracing::debug!("using prebuilt config");
+        Self::load_layers(
+            Config::builder().add_source(config),
+            Vec::new(),
+            None,
+            &EnvSource::Disabled,
+        )
+    }
 
-        // Then try secret_path
-        let path = self.secret_path.as_ref().ok_or(Error::MissingSecret)?;
-        let expanded_path = if path.starts_with("~") {
-            expand_tilde(&path.to_string_lossy())
-        } else {
-            path.clone()
-        };
+    /// Load configuration from `FIREBLOCKS_*` environment variables alone,
+    /// for file-less deployments
+    ///
+    /// Nested keys use `__`, e.g. `FIREBLOCKS_SIGNER__VAULT`. Field defaults
+    /// apply and a missing `api_key` is an error. A missing `signer.vault`
+    /// loads empty and is reported by [`FireblocksConfig::validate_all`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// // FIREBLOCKS_API_KEY=... FIREBLOCKS_SECRET=... FIREBLOCKS_SIGNER__VAULT=0
+    /// let config = FireblocksConfig::from_env()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_env() -> Result<Self> {
+        tracing::debug!("using config from the environment");
+        Self::load(Config::builder())
+    }
 
-        #[cfg(feature = "gpg")]
-        if expanded_path
-            .extension()
-            .is_some_and(|ext| ext.eq_ignore_ascii_case("gpg"))
-        {
-            return self.decrypt_gpg_file(&expanded_path);
-        }
+    /// Load configuration like [`FireblocksConfig::from_env`] from the
+    /// variables in `env` instead of the process environment
+    pub fn from_env_vars(env: impl IntoIterator<Item = (String, String)>) -> Result<Self> {
+        tracing::debug!("using config from given environment variables");
+        Self::load_layers(
+            Config::builder(),
+            Vec::new(),
+            None,
+            &EnvSource::Vars(env.into_iter().collect()),
+        )
+    }
 
-        // Regular file read
-        fs::read(&expanded_path).map_err(|e| Error::IOError {
-            source: e,
-            path: expanded_path.to_string_lossy().to_string(),
-        })
+    /// Load configuration from a table of a larger TOML file, e.g. the
+    /// `[fireblocks]` table of an application config, then apply the
+    /// environment overlay
+    ///
+    /// Nested tables are addressed with dots, e.g. `services.fireblocks`.
+    /// Everything outside the table is ignored.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::from_file_section("app.toml", "fireblocks")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_file_section<P: AsRef<Path>>(path: P, section: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let (_, mut table) = read_toml(path)?;
+        for name in section.split('.') {
+            table = match table.remove(name) {
+                Some(toml::Value::Table(inner)) => inner,
+                _ => {
+                    return Err(Error::ConfigNotFound(format!(
+                        "section [{section}] in {}",
+                        path.display()
+                    )));
+                }
+            };
+        }
+        tracing::debug!("using config {} section [{section}]", path.display());
+        Self::from_contents(&to_toml_string(&table)?, FileFormat::Toml)
     }
 
-    #[cfg(feature = "gpg")]
-    fn decrypt_gpg_file(&self, path: &Path) -> Result<Vec<u8>> {
-        let mut ctx = Context::from_protocol(Protocol::OpenPgp)?;
+    /// Load every `*.toml` fragment in a `conf.d`-style directory
+    ///
+    /// `default.toml` is loaded first when present, then the remaining files
+    /// are applied as overrides in lexical order, followed by the
+    /// environment.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// // conf.d/default.toml, conf.d/10-prod.toml, conf.d/20-signer.toml
+    /// let config = FireblocksConfig::from_dir("conf.d")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let io_error = |source| Error::IOError {
+            source,
+            path: dir.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        };
 
-        let mut input = fs::File::open(path).map_err(|e| Error::IOError {
-            source: e,
-            path: path.to_string_lossy().to_string(),
-        })?;
+        let mut fragments = Vec::new();
+        for entry in fs::read_dir(dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "toml") {
+                fragments.push(path);
+            }
+        }
+        fragments.sort();
 
-        let mut output = Vec::new();
-        ctx.decrypt(&mut input, &mut output)?;
+        let default_config = dir.join("default.toml");
+        if let Some(pos) = fragments.iter().position(|p| *p == default_config) {
+            let default_config = fragments.remove(pos);
+            fragments.insert(0, default_config);
+        }
 
-        Ok(output)
+        let Some((base, overrides)) = fragments.split_first() else {
+            return Err(Error::ConfigNotFound(format!(
+                "no .toml files in {}",
+                dir.display()
+            )));
+        };
+        tracing::debug!(
+            "loading {} config fragments from {}",
+            fragments.len(),
+            dir.display()
+        );
+        Self::new(base.clone(), overrides)
     }
-}
-impl FireblocksConfig {
-    pub fn new<P: AsRef<Path>>(cfg: P, cfg_overrides: &[P]) -> Result<Self> {
-        let cfg_path = cfg.as_ref();
-        tracing::debug!("using config {}", cfg_path.display());
 
-        let mut config_builder =
-            Config::builder().add_source(File::new(&cfg_path.to_string_lossy(), FileFormat::Toml));
-
-        // Add all override files in order
-        for override_path in cfg_overrides {
-            let path = override_path.as_ref();
-            tracing::debug!("adding config override: {}", path.display());
-            config_builder = config_builder
-                .add_source(File::new(&path.to_string_lossy(), FileFormat::Toml).required(true));
-        }
+    /// Load a config file and overlay one of its inline `[profiles.<name>]`
+    /// tables
+    ///
+    /// Profile tables may contain a full or partial config and are deep
+    /// merged over the top-level values, so a profile only needs to set the
+    /// keys it changes (including keys under `[signer]` and `[extra]`).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// // default.toml contains [profiles.sandbox] and [profiles.production]
+    /// let config = FireblocksConfig::with_profile("default.toml", "production")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// The file loads like [`FireblocksConfig::new`] with no overrides,
+    /// following `extends` and applying the environment, and the profile is
+    /// kept for [`FireblocksConfig::reload`].
+    pub fn with_profile<P: AsRef<Path>>(cfg: P, profile: &str) -> Result<Self> {
+        let cfg = cfg.as_ref();
+        tracing::debug!("using config {} profile {profile}", cfg.display());
+        Self::new_layered(cfg, &[], LoadOptions {
+            inline: Some(InlineTable::Profile(profile.to_string())),
+            ..LoadOptions::default()
+        })
+    }
 
-        // Environment variables still take highest precedence
-        config_builder = config_builder
-            .add_source(config::Environment::with_prefix("FIREBLOCKS").try_parsing(true));
+    /// Load the `[workspaces.<name>]` table of `path` over its top-level
+    /// keys, without the environment overlay
+    pub(crate) fn load_workspace(path: &Path, name: &str) -> Result<Self> {
+        tracing::debug!("loading workspace {name} from {}", path.display());
+        Self::new_layered(path, &[], LoadOptions {
+            env: EnvSource::Disabled,
+            inline: Some(InlineTable::Workspace(name.to_string())),
+            ..LoadOptions::default()
+        })
+    }
 
-        let conf: Self = config_builder.build()?.try_deserialize()?;
-        tracing::trace!("loaded config {conf:#?}");
-        Ok(conf)
+    /// List the inline `[profiles.<name>]` tables defined in a config file,
+    /// sorted by name
+    pub fn list_inline_profiles<P: AsRef<Path>>(cfg: P) -> Result<Vec<String>> {
+        let (_, mut table) = read_toml(cfg.as_ref())?;
+        let mut names: Vec<String> = take_profiles(&mut table).keys().cloned().collect();
+        names.sort();
+        Ok(names)
     }
 
-    pub fn with_overrides<P: AsRef<Path>>(
-        cfg: P,
-        overrides: impl IntoIterator<Item = P>,
-    ) -> Result<Self> {
-        let override_vec: Vec<P> = overrides.into_iter().collect();
-        Self::new(cfg, &override_vec)
+    /// The first existing base config out of the XDG `default.toml`, the
+    /// file named by `env_config` and `system`, or every place tried
+    pub(crate) fn find_init_config(
+        config_dir: Option<PathBuf>,
+        env_config: Option<PathBuf>,
+        system: &Path,
+    ) -> Result<PathBuf> {
+        let candidates = [
+            (
+                "XDG config directory",
+                config_dir.map(|dir| dir.join("fireblocks").join("default.toml")),
+            ),
+            (Self::CONFIG_ENV_VAR, env_config),
+            ("system config", Some(system.to_path_buf())),
+        ];
+        let mut tried = Vec::with_capacity(candidates.len());
+        for (name, path) in candidates {
+            match path {
+                Some(path) if path.exists() => return Ok(path),
+                Some(path) => tried.push(path.display().to_string()),
+                None => tried.push(format!("{name} (unavailable)")),
+            }
+        }
+        Err(Error::NoConfigFound { tried })
     }
 
     /// Load configuration from XDG config directory
@@ -261,6 +3810,12 @@ impl FireblocksConfig {
     /// Loads ~/.config/fireblocks/default.toml as base config, then applies
     /// each profile from ~/.config/fireblocks/{profile}.toml in order.
     ///
+    /// Without a home directory, or when that file is missing, the file named
+    /// by [`FireblocksConfig::CONFIG_ENV_VAR`] and then
+    /// [`FireblocksConfig::SYSTEM_CONFIG`] are used instead, with profiles
+    /// read from the same directory. [`Error::NoConfigFound`] lists every
+    /// place tried.
+    ///
     /// # Example
     /// ```rust,no_run
     /// use fireblocks_config::FireblocksConfig;
@@ -276,23 +3831,92 @@ impl FireblocksConfig {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn init_with_profiles<S: AsRef<str>>(profiles: &[S]) -> Result<Self> {
-        let config_dir = dirs::config_dir().ok_or(Error::XdgConfigNotFound)?;
-        let fireblocks_dir = config_dir.join("fireblocks");
-        let default_config = fireblocks_dir.join("default.toml");
+        let default_config = Self::find_init_config(
+            dirs::config_dir(),
+            std::env::var_os(Self::CONFIG_ENV_VAR).map(PathBuf::from),
+            Path::new(Self::SYSTEM_CONFIG),
+        )?;
+        Self::init_from(default_config, profiles)
+    }
+
+    /// Load configuration from the file named by
+    /// [`FireblocksConfig::CONFIG_ENV_VAR`], with the profiles listed in
+    /// [`FireblocksConfig::PROFILE_ENV_VAR`]
+    ///
+    /// This lets a CLI pick its config without a flag. When
+    /// `FIREBLOCKS_CONFIG` is unset or empty this is
+    /// [`FireblocksConfig::init_with_profiles`], starting from the XDG
+    /// `default.toml`. Either way `FIREBLOCKS_PROFILE`, e.g.
+    /// `staging,production`, names profile files in the base config's
+    /// directory, applied in order over the base. `FIREBLOCKS_*` variables
+    /// for config keys still override every file.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// // FIREBLOCKS_CONFIG=/etc/app/fireblocks.toml FIREBLOCKS_PROFILE=production
+    /// let config = FireblocksConfig::init_from_config_env()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn init_from_config_env() -> Result<Self> {
+        let profiles: Vec<String> = std::env::var(Self::PROFILE_ENV_VAR)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+        match std::env::var_os(Self::CONFIG_ENV_VAR) {
+            Some(path) if !path.is_empty() => Self::init_from(PathBuf::from(path), &profiles),
+            _ => Self::init_with_profiles(&profiles),
+        }
+    }
 
+    /// Load `default.toml` and `profiles` from `dir` instead of the XDG
+    /// config directory
+    ///
+    /// This is [`FireblocksConfig::init_with_profiles`] rooted at `dir`, for
+    /// tests and tools serving several tenants from one process. There is no
+    /// fallback: a missing `default.toml` fails with
+    /// [`Error::NoConfigFound`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// // /srv/tenants/acme/default.toml + /srv/tenants/acme/production.toml
+    /// let config = FireblocksConfig::init_with_dir("/srv/tenants/acme", &["production"])?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn init_with_dir<S: AsRef<str>>(dir: impl AsRef<Path>, profiles: &[S]) -> Result<Self> {
+        let default_config = dir.as_ref().join("default.toml");
         if !default_config.exists() {
-            return Err(Error::ConfigNotFound(
-                default_config.to_string_lossy().to_string(),
-            ));
+            return Err(Error::NoConfigFound {
+                tried: vec![default_config.display().to_string()],
+            });
         }
+        Self::init_from(default_config, profiles)
+    }
+
+    /// Load `default_config` with `profiles` read from its directory
+    fn init_from<S: AsRef<str>>(default_config: PathBuf, profiles: &[S]) -> Result<Self> {
+        let fireblocks_dir = default_config
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
 
         tracing::debug!("loading default config: {}", default_config.display());
 
         let mut profile_configs = Vec::new();
         for profile in profiles {
             let profile_file = format!("{}.toml", profile.as_ref());
-            let profile_config = fireblocks_dir.join(&profile_file);
-            if profile_config.exists() {
+            // An encrypted profile may keep the `.gpg` extension
+            let candidates = [
+                fireblocks_dir.join(&profile_file),
+                fireblocks_dir.join(format!("{profile_file}.gpg")),
+            ];
+            if let Some(profile_config) = candidates.into_iter().find(|p| p.exists()) {
                 tracing::debug!("adding profile config: {}", profile_config.display());
                 profile_configs.push(profile_config);
             } else {
@@ -300,6 +3924,74 @@ impl FireblocksConfig {
             }
         }
 
-        Self::new(default_config, &profile_configs)
+        let conf = Self::new(default_config, &profile_configs)?;
+        let profiles: Vec<&str> = profiles.iter().map(AsRef::as_ref).collect();
+        tracing::debug!(?profiles, "loaded XDG config");
+        Ok(conf)
+    }
+}
+
+/// Load a config file without overrides, like `FireblocksConfig::new(path,
+/// &[])`
+///
+/// ```rust
+/// use {fireblocks_config::FireblocksConfig, std::path::Path};
+///
+/// let cfg = FireblocksConfig::try_from(Path::new("examples/default.toml"))?;
+/// # Ok::<(), fireblocks_config::Error>(())
+/// ```
+impl TryFrom<&Path> for FireblocksConfig {
+    type Error = Error;
+
+    fn try_from(path: &Path) -> Result<Self> {
+        Self::new(path, &[])
+    }
+}
+
+/// Load a config file without overrides, like `FireblocksConfig::new(path,
+/// &[])`
+///
+/// ```rust
+/// use {fireblocks_config::FireblocksConfig, std::path::PathBuf};
+///
+/// let cfg = FireblocksConfig::try_from(PathBuf::from("examples/default.toml"))?;
+/// # Ok::<(), fireblocks_config::Error>(())
+/// ```
+impl TryFrom<PathBuf> for FireblocksConfig {
+    type Error = Error;
+
+    fn try_from(path: PathBuf) -> Result<Self> {
+        Self::new(path, &[])
+    }
+}
+
+/// Parse the string as the path of a config file without overrides
+///
+/// ```rust
+/// use fireblocks_config::FireblocksConfig;
+///
+/// let cfg: FireblocksConfig = "examples/default.toml".parse()?;
+/// # Ok::<(), fireblocks_config::Error>(())
+/// ```
+impl std::str::FromStr for FireblocksConfig {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        Self::new(path, &[])
+    }
+}
+
+/// One-line summary without secret material, for startup logs
+impl std::fmt::Display for FireblocksConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FireblocksConfig {{ url={}, vault={}, mainnet={}, output={:?}, extra_keys={} }}",
+            self.resolved_url(),
+            self.signer.vault,
+            self.mainnet,
+            self.display_config.output,
+            self.extra.len()
+        )
     }
 }
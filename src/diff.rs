@@ -0,0 +1,111 @@
+use {
+    crate::{ApiKey, FireblocksConfig, Result},
+    serde_json::Value,
+    std::collections::BTreeMap,
+};
+
+/// Keys whose values are replaced with [`REDACTED`] in change records
+pub(crate) const SECRET_KEYS: &[&str] = &["secret", "secret_shares"];
+
+pub(crate) const REDACTED: &str = "<redacted>";
+
+/// A single changed value between two configs, keyed by its dotted path
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigChange {
+    /// Dotted key, e.g. `signer.vault` or `extra.rpc_url`
+    pub key: String,
+    /// Value in the original config, `None` when the key was added
+    pub old: Option<Value>,
+    /// Value in the other config, `None` when the key was removed
+    pub new: Option<Value>,
+}
+
+pub(crate) fn flatten(prefix: &str, value: Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() {
+                    k
+                } else {
+                    format!("{prefix}.{k}")
+                };
+                flatten(&key, v, out);
+            }
+        }
+        // absent optional values are treated like missing keys
+        Value::Null => {}
+        value => {
+            out.insert(prefix.to_string(), value);
+        }
+    }
+}
+
+fn redact(key: &str, value: Option<Value>) -> Option<Value> {
+    let root = key.split('.').next().unwrap_or(key);
+    if SECRET_KEYS.contains(&root) {
+        value.map(|_| Value::String(REDACTED.to_string()))
+    } else if key == "api_key" {
+        // masked like the rendered config
+        value.map(|v| match v {
+            Value::String(s) => Value::String(ApiKey::new(s).to_string()),
+            v => v,
+        })
+    } else {
+        value
+    }
+}
+
+impl FireblocksConfig {
+    /// Dotted key/value view of the config
+    ///
+    /// Secrets are never serialized, so they are added back here to let
+    /// [`FireblocksConfig::diff`] report (redacted) secret changes.
+    pub(crate) fn flattened(&self) -> Result<BTreeMap<String, Value>> {
+        let mut out = BTreeMap::new();
+        flatten("", serde_json::to_value(self)?, &mut out);
+        if let Some(ref secret) = self.secret {
+            out.insert("secret".to_string(), Value::from(secret.as_str()));
+        }
+        if let Some(ref shares) = self.secret_shares {
+            out.insert("secret_shares".to_string(), serde_json::to_value(shares)?);
+        }
+        Ok(out)
+    }
+
+    /// List the keys whose values differ between `self` and `other`
+    ///
+    /// Changes are sorted by key. Secret values are reported as
+    /// `"<redacted>"` and the API key is masked.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let fresh = config.reload()?;
+    /// for change in config.diff(&fresh)? {
+    ///     println!("{} changed", change.key);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn diff(&self, other: &Self) -> Result<Vec<ConfigChange>> {
+        let mut old = self.flattened()?;
+        let mut new = other.flattened()?;
+        let mut keys: Vec<String> = old.keys().chain(new.keys()).cloned().collect();
+        keys.sort();
+        keys.dedup();
+
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| {
+                let old = old.remove(&key);
+                let new = new.remove(&key);
+                (old != new).then(|| ConfigChange {
+                    old: redact(&key, old),
+                    new: redact(&key, new),
+                    key,
+                })
+            })
+            .collect())
+    }
+}
@@ -1,16 +1,53 @@
-use thiserror::Error;
+use {std::backtrace::Backtrace, thiserror::Error};
+
+/// Backtrace for an error created here, captured as `RUST_BACKTRACE` asks
+/// when the `backtrace` feature is enabled
+pub(crate) fn capture_backtrace() -> Option<Box<Backtrace>> {
+    #[cfg(feature = "backtrace")]
+    return Some(Box::new(Backtrace::capture()));
+    #[cfg(not(feature = "backtrace"))]
+    None
+}
+
+/// The sources of an [`Error::Load`], or `memory` when none were files
+fn load_sources(sources: &[String]) -> String {
+    if sources.is_empty() {
+        "memory".to_string()
+    } else {
+        sources.join(", ")
+    }
+}
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("config not found {0}")]
     ConfigNotFound(String),
 
+    /// Kept for compatibility, loading reports [`Error::Load`] and
+    /// [`Error::ExtraDeserialize`] instead
     #[error(transparent)]
-    ConfigParseError(#[from] config::ConfigError),
+    ConfigParseError(config::ConfigError),
+
+    #[error("failed to load config from {}: {source}", load_sources(sources))]
+    Load {
+        /// The files layered, in order, then `environment` when
+        /// `FIREBLOCKS_*` variables were applied
+        sources: Vec<String>,
+        source: Box<config::ConfigError>,
+        backtrace: Option<Box<Backtrace>>,
+    },
 
     #[error(transparent)]
     IO(#[from] std::io::Error),
 
+    #[error("failed to parse {path}: {detail}")]
+    ConfigParseAt {
+        path: String,
+        detail: String,
+        source: Box<toml::de::Error>,
+        backtrace: Option<Box<Backtrace>>,
+    },
+
     #[error("missing secret key. Check your configuration file or set env FIREBLOCKS_SECRET")]
     MissingSecret,
 
@@ -21,21 +58,528 @@ pub enum Error {
     IOError {
         source: std::io::Error,
         path: String,
+        backtrace: Option<Box<Backtrace>>,
     },
 
     #[error("Invalid Duration {0}")]
     InvalidDuration(String),
 
+    #[error("Invalid vault id '{value}', expected a non-negative integer")]
+    InvalidVault { value: String },
+
+    #[error("Invalid glob pattern '{pattern}': {source}")]
+    InvalidGlob {
+        pattern: String,
+        source: glob::PatternError,
+    },
+
+    #[error("Invalid URL '{value}' for {key}: {reason}")]
+    InvalidUrl {
+        key: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("Invalid value '{value}' for {key}: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("Invalid override '{0}', expected KEY=VALUE")]
+    InvalidOverride(String),
+
+    #[error("Invalid api_key '{key}': {reason}")]
+    InvalidApiKey { key: String, reason: String },
+
+    #[error("Unknown config keys: {}", keys.join(", "))]
+    UnknownKeys { keys: Vec<String> },
+
+    #[cfg(feature = "pem")]
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    #[error("Key '{key}' is ambiguous, candidates: {candidates:?}")]
+    AmbiguousKey {
+        key: String,
+        candidates: Vec<String>,
+    },
+
     #[error("Key '{key}' not present in configuration")]
     NotPresent { key: String },
 
+    #[error("Failed to deserialize extra key '{key}': {source}")]
+    ExtraDeserialize {
+        key: String,
+        source: serde_json::Error,
+        backtrace: Option<Box<Backtrace>>,
+    },
+
     #[cfg(feature = "gpg")]
     #[error(transparent)]
     GpgError(#[from] gpgme::Error),
 
+    #[cfg(feature = "sss")]
+    #[error("Failed to reconstruct secret from shares: {0}")]
+    SecretReconstruction(String),
+
+    #[cfg(feature = "watch")]
+    #[error(transparent)]
+    Watch(#[from] notify::Error),
+
+    #[error("config was not loaded from files and cannot be reloaded")]
+    NotReloadable,
+
+    #[cfg(feature = "aws")]
+    #[error("AWS Secrets Manager error: {0}")]
+    AwsSecretsManager(String),
+
+    #[cfg(feature = "kms")]
+    #[error("KMS error: {0}")]
+    Kms(String),
+
+    #[cfg(feature = "vault")]
+    #[error("Vault error: {0}")]
+    Vault(String),
+
+    #[cfg(feature = "reqwest")]
+    #[error("failed to fetch secret from {url}: {reason}")]
+    SecretFetch { url: String, reason: String },
+
+    #[cfg(feature = "remote")]
+    #[error("failed to fetch config from {url}: {reason}")]
+    RemoteFetch { url: String, reason: String },
+
+    #[cfg(feature = "jwt")]
+    #[error("JWT error: {0}")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+
     #[error("XDG config directory not found")]
     XdgConfigNotFound,
 
     #[error("Profile config not found: {0}")]
     ProfileConfigNotFound(String),
+
+    #[error("Profile '{profile}' not found. Available profiles: {available:?}")]
+    ProfileNotFound {
+        profile: String,
+        available: Vec<String>,
+    },
+
+    #[error("Workspace '{workspace}': {source}")]
+    Workspace {
+        workspace: String,
+        source: Box<Error>,
+    },
+
+    #[error("Workspace '{workspace}' not found. Available workspaces: {available:?}")]
+    WorkspaceNotFound {
+        workspace: String,
+        available: Vec<String>,
+    },
+
+    #[error("No default_workspace set. Available workspaces: {available:?}")]
+    NoDefaultWorkspace { available: Vec<String> },
+
+    #[error(transparent)]
+    TomlError(#[from] toml::de::Error),
+
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+
+    #[error(
+        "config extends chain is cyclic or too deep: {}",
+        chain.join(" -> ")
+    )]
+    ExtendsCycle { chain: Vec<String> },
+
+    #[error("secret_path {path} resolves outside of secret_path_root {root}")]
+    SecretPathOutsideRoot { path: String, root: String },
+
+    #[error("path {0} cannot be represented in a config")]
+    InvalidPath(String),
+
+    #[error("unsupported secret_path scheme {0}")]
+    UnsupportedSecretScheme(String),
+
+    #[error("cannot expand {path}, the home directory is unavailable")]
+    HomeDirUnavailable { path: String },
+
+    #[error("no config found, tried {}", tried.join(", "))]
+    NoConfigFound { tried: Vec<String> },
+
+    #[error("{key} {url} is not https, set require_https = false to allow it")]
+    InsecureUrl { key: String, url: String },
+
+    #[error("invalid placeholder '{placeholder}' in template '{template}'")]
+    InvalidTemplate {
+        template: String,
+        placeholder: String,
+    },
+
+    #[error(
+        "no builtin {} webhook key is bundled, set webhook_public_key or \
+         webhook_public_key_path",
+        if *mainnet { "production" } else { "sandbox" }
+    )]
+    BuiltinWebhookKeyUnavailable { mainnet: bool },
+
+    #[error("secret_command failed with {status}")]
+    SecretCommand { status: std::process::ExitStatus },
+
+    #[error("url {url} does not match mainnet = {mainnet}")]
+    EnvironmentMismatch { url: String, mainnet: bool },
+
+    #[error("failed to decrypt config {path}: {reason}")]
+    ConfigDecryption { path: String, reason: String },
+
+    #[error("config version {found} is newer than the supported version {supported}")]
+    UnsupportedConfigVersion { found: u32, supported: u32 },
+
+    #[cfg(feature = "global")]
+    #[error("the global config is not initialized, call global::init first")]
+    GlobalNotInitialized,
+
+    #[cfg(feature = "global")]
+    #[error("the global config is already initialized")]
+    GlobalAlreadyInitialized,
+
+    #[error("both {alias} and {key} are set, with different values")]
+    ConflictingKeys { alias: String, key: String },
+}
+
+impl Error {
+    /// Stable identifier for the error, e.g. `MISSING_SECRET`, for scripts
+    /// and for looking up documentation
+    ///
+    /// Codes never change once released, unlike the `Display` text.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ConfigNotFound(_) => "CONFIG_NOT_FOUND",
+            Self::ConfigParseError(_) => "CONFIG_PARSE",
+            Self::Load { .. } => "CONFIG_LOAD",
+            Self::IO(_) => "IO",
+            Self::ConfigParseAt { .. } => "CONFIG_SYNTAX",
+            Self::MissingSecret => "MISSING_SECRET",
+            Self::AssetNotFound { .. } => "ASSET_NOT_FOUND",
+            Self::IOError { .. } => "FILE_IO",
+            Self::InvalidDuration(_) => "INVALID_DURATION",
+            Self::InvalidVault { .. } => "INVALID_VAULT",
+            Self::InvalidGlob { .. } => "INVALID_GLOB",
+            Self::InvalidUrl { .. } => "INVALID_URL",
+            Self::InvalidValue { .. } => "INVALID_VALUE",
+            Self::InvalidOverride(_) => "INVALID_OVERRIDE",
+            Self::InvalidApiKey { .. } => "INVALID_API_KEY",
+            Self::UnknownKeys { .. } => "UNKNOWN_KEYS",
+            #[cfg(feature = "pem")]
+            Self::InvalidKey(_) => "INVALID_KEY",
+            Self::AmbiguousKey { .. } => "AMBIGUOUS_KEY",
+            Self::NotPresent { .. } => "NOT_PRESENT",
+            Self::ExtraDeserialize { .. } => "EXTRA_DESERIALIZE",
+            #[cfg(feature = "gpg")]
+            Self::GpgError(_) => "GPG",
+            #[cfg(feature = "sss")]
+            Self::SecretReconstruction(_) => "SECRET_RECONSTRUCTION",
+            #[cfg(feature = "watch")]
+            Self::Watch(_) => "WATCH",
+            Self::NotReloadable => "NOT_RELOADABLE",
+            #[cfg(feature = "aws")]
+            Self::AwsSecretsManager(_) => "AWS_SECRETS_MANAGER",
+            #[cfg(feature = "kms")]
+            Self::Kms(_) => "KMS",
+            #[cfg(feature = "vault")]
+            Self::Vault(_) => "VAULT",
+            #[cfg(feature = "reqwest")]
+            Self::SecretFetch { .. } => "SECRET_FETCH",
+            #[cfg(feature = "remote")]
+            Self::RemoteFetch { .. } => "REMOTE_FETCH",
+            #[cfg(feature = "jwt")]
+            Self::Jwt(_) => "JWT",
+            Self::XdgConfigNotFound => "XDG_CONFIG_NOT_FOUND",
+            Self::ProfileConfigNotFound(_) => "PROFILE_CONFIG_NOT_FOUND",
+            Self::ProfileNotFound { .. } => "PROFILE_NOT_FOUND",
+            Self::Workspace { .. } => "WORKSPACE",
+            Self::WorkspaceNotFound { .. } => "WORKSPACE_NOT_FOUND",
+            Self::NoDefaultWorkspace { .. } => "NO_DEFAULT_WORKSPACE",
+            Self::TomlError(_) => "TOML",
+            Self::JsonError(_) => "JSON",
+            Self::ExtendsCycle { .. } => "EXTENDS_CYCLE",
+            Self::SecretPathOutsideRoot { .. } => "SECRET_PATH_OUTSIDE_ROOT",
+            Self::InvalidPath(_) => "INVALID_PATH",
+            Self::UnsupportedSecretScheme(_) => "UNSUPPORTED_SECRET_SCHEME",
+            Self::HomeDirUnavailable { .. } => "HOME_DIR_UNAVAILABLE",
+            Self::NoConfigFound { .. } => "NO_CONFIG_FOUND",
+            Self::InsecureUrl { .. } => "INSECURE_URL",
+            Self::InvalidTemplate { .. } => "INVALID_TEMPLATE",
+            Self::BuiltinWebhookKeyUnavailable { .. } => "BUILTIN_WEBHOOK_KEY_UNAVAILABLE",
+            Self::SecretCommand { .. } => "SECRET_COMMAND",
+            Self::EnvironmentMismatch { .. } => "ENVIRONMENT_MISMATCH",
+            Self::ConfigDecryption { .. } => "CONFIG_DECRYPTION",
+            Self::UnsupportedConfigVersion { .. } => "UNSUPPORTED_CONFIG_VERSION",
+            #[cfg(feature = "global")]
+            Self::GlobalNotInitialized => "GLOBAL_NOT_INITIALIZED",
+            #[cfg(feature = "global")]
+            Self::GlobalAlreadyInitialized => "GLOBAL_ALREADY_INITIALIZED",
+            Self::ConflictingKeys { .. } => "CONFLICTING_KEYS",
+        }
+    }
+
+    /// What the user can do about the error, for a CLI to print below the
+    /// message
+    ///
+    /// `None` for errors wrapping another library's error, where the message
+    /// says all there is to say.
+    pub fn hint(&self) -> Option<String> {
+        let hint = match self {
+            Self::ConfigNotFound(_) | Self::NoConfigFound { .. } | Self::XdgConfigNotFound => {
+                "create ~/.config/fireblocks/default.toml, or point FIREBLOCKS_CONFIG at a config \
+                 file"
+                    .to_string()
+            }
+            Self::ConfigParseError(_) | Self::Load { .. } => {
+                "check the types of the config values, e.g. a quoted signer.vault".to_string()
+            }
+            Self::ConfigParseAt { path, .. } => format!("fix the TOML syntax in {path}"),
+            Self::TomlError(_) => "fix the TOML syntax".to_string(),
+            Self::MissingSecret => "set secret_path or secret in the config, or the \
+                                    FIREBLOCKS_SECRET environment variable"
+                .to_string(),
+            Self::AssetNotFound { asset } => format!("add {asset} to assets_allowed"),
+            Self::IOError { path, .. } => format!("check that {path} exists and is readable"),
+            Self::InvalidDuration(_) => "give durations in seconds, e.g. 30, 1.5 or \"30\"; \
+                                         poll_interval must not exceed poll_timeout"
+                .to_string(),
+            Self::InvalidVault { .. } => {
+                "set signer.vault to a numeric vault id, e.g. \"0\"".to_string()
+            }
+            Self::InvalidGlob { .. } => "fix the glob pattern, e.g. overrides/*.toml".to_string(),
+            Self::InvalidUrl { key, .. } => {
+                format!("set {key} to an absolute URL, e.g. https://api.fireblocks.io/v1")
+            }
+            Self::InvalidValue { key, .. } => format!(
+                "fix {key} in the config or its FIREBLOCKS_{} variable",
+                key.to_uppercase().replace('.', "__")
+            ),
+            Self::InvalidOverride(_) => {
+                "write overrides as key=value, e.g. signer.vault=1".to_string()
+            }
+            Self::InvalidApiKey { .. } => "copy the API key from the Fireblocks console, or set \
+                                           allow_nonstandard_api_key = true"
+                .to_string(),
+            Self::UnknownKeys { .. } => {
+                "remove or correct the keys, or set treat_unknown_as_error = false".to_string()
+            }
+            #[cfg(feature = "pem")]
+            Self::InvalidKey(_) => {
+                "point secret_path at the PEM private key of the API user".to_string()
+            }
+            Self::AmbiguousKey { candidates, .. } => {
+                format!("use the exact key, one of {}", candidates.join(", "))
+            }
+            Self::NotPresent { key } => format!("add {key} to the [extra] section"),
+            Self::ExtraDeserialize { key, .. } => {
+                format!("check the type of {key} in the [extra] section")
+            }
+            #[cfg(feature = "gpg")]
+            Self::GpgError(_) => {
+                "check that gpg-agent is running and holds the decryption key".to_string()
+            }
+            #[cfg(feature = "sss")]
+            Self::SecretReconstruction(_) => {
+                "check that secret_shares holds enough valid hex encoded shares".to_string()
+            }
+            Self::NotReloadable => "load the config from files, e.g. with FireblocksConfig::new, \
+                                    to reload it"
+                .to_string(),
+            #[cfg(feature = "aws")]
+            Self::AwsSecretsManager(_) => {
+                "check the AWS credentials and region, and that secret_aws_arn exists".to_string()
+            }
+            #[cfg(feature = "kms")]
+            Self::Kms(_) => "check the AWS credentials and region, and that secret_kms_ciphertext \
+                             was encrypted under secret_kms_key_id"
+                .to_string(),
+            #[cfg(feature = "vault")]
+            Self::Vault(_) => {
+                "check vault_addr, vault_secret_path and the token in vault_token_env".to_string()
+            }
+            #[cfg(feature = "reqwest")]
+            Self::SecretFetch { .. } => "check that the secret URL is reachable and \
+                                         secret_url_auth_env holds a valid token"
+                .to_string(),
+            #[cfg(feature = "remote")]
+            Self::RemoteFetch { .. } => "check that the config URL is reachable".to_string(),
+            Self::ProfileConfigNotFound(file) => {
+                format!("create {file} next to the base config, or drop the profile")
+            }
+            Self::ProfileNotFound { available, .. } => {
+                format!("use one of the profiles {}", available.join(", "))
+            }
+            Self::Workspace { source, .. } => return source.hint(),
+            Self::WorkspaceNotFound { available, .. } => {
+                format!("use one of the workspaces {}", available.join(", "))
+            }
+            Self::NoDefaultWorkspace { .. } => {
+                "set default_workspace, or pick a workspace explicitly".to_string()
+            }
+            Self::ExtendsCycle { .. } => "remove the cycle from the extends chain".to_string(),
+            Self::SecretPathOutsideRoot { root, .. } => {
+                format!("move the key under {root}, or change secret_path_root")
+            }
+            Self::InvalidPath(_) => "use a UTF-8 path".to_string(),
+            Self::UnsupportedSecretScheme(_) => {
+                "use a plain path, or a file:// or https:// secret_path".to_string()
+            }
+            Self::HomeDirUnavailable { .. } => {
+                "use an absolute path instead of ~, or set HOME".to_string()
+            }
+            Self::InsecureUrl { .. } => {
+                "use an https URL, or set require_https = false for local testing".to_string()
+            }
+            Self::InvalidTemplate { .. } => {
+                "use only the {uuid}, {timestamp}, {vault} and {nonce} placeholders".to_string()
+            }
+            Self::BuiltinWebhookKeyUnavailable { .. } => {
+                "set webhook_public_key or webhook_public_key_path".to_string()
+            }
+            Self::SecretCommand { .. } => {
+                "run secret_command in a shell to see why it fails".to_string()
+            }
+            Self::EnvironmentMismatch { mainnet, .. } => format!(
+                "point url at the {} API, or set mainnet = {}",
+                if *mainnet { "production" } else { "sandbox" },
+                !mainnet
+            ),
+            Self::ConfigDecryption { .. } => {
+                "check that the key the config was encrypted for is available".to_string()
+            }
+            Self::UnsupportedConfigVersion { .. } => {
+                "upgrade to a fireblocks-config release that supports this version".to_string()
+            }
+            #[cfg(feature = "global")]
+            Self::GlobalNotInitialized => "call global::init once at startup".to_string(),
+            #[cfg(feature = "global")]
+            Self::GlobalAlreadyInitialized => {
+                "call global::init only once, use global::get afterwards".to_string()
+            }
+            Self::ConflictingKeys { alias, .. } => format!("remove the legacy {alias}"),
+            Self::IO(_) | Self::JsonError(_) => return None,
+            #[cfg(feature = "watch")]
+            Self::Watch(_) => return None,
+            #[cfg(feature = "jwt")]
+            Self::Jwt(_) => return None,
+        };
+        Some(hint)
+    }
+
+    /// Whether the error comes from the configuration or how it was given,
+    /// as opposed to a failing system, network or service
+    ///
+    /// A CLI can use this to pick its exit code, e.g. `78` (`EX_CONFIG`) for
+    /// user errors and `1` otherwise. A missing or unreadable file counts as a
+    /// user error.
+    pub fn is_user_error(&self) -> bool {
+        match self {
+            Self::IO(_) | Self::JsonError(_) | Self::NotReloadable => false,
+            Self::IOError { source, .. } => matches!(
+                source.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied
+            ),
+            Self::SecretCommand { .. } | Self::ConfigDecryption { .. } => false,
+            #[cfg(feature = "gpg")]
+            Self::GpgError(_) => false,
+            #[cfg(feature = "watch")]
+            Self::Watch(_) => false,
+            #[cfg(feature = "aws")]
+            Self::AwsSecretsManager(_) => false,
+            #[cfg(feature = "kms")]
+            Self::Kms(_) => false,
+            #[cfg(feature = "vault")]
+            Self::Vault(_) => false,
+            #[cfg(feature = "reqwest")]
+            Self::SecretFetch { .. } => false,
+            #[cfg(feature = "remote")]
+            Self::RemoteFetch { .. } => false,
+            #[cfg(feature = "jwt")]
+            Self::Jwt(_) => false,
+            #[cfg(feature = "global")]
+            Self::GlobalNotInitialized | Self::GlobalAlreadyInitialized => false,
+            Self::Workspace { source, .. } => source.is_user_error(),
+            Self::ConfigNotFound(_)
+            | Self::ConfigParseError(_)
+            | Self::Load { .. }
+            | Self::ConfigParseAt { .. }
+            | Self::MissingSecret
+            | Self::AssetNotFound { .. }
+            | Self::InvalidDuration(_)
+            | Self::InvalidVault { .. }
+            | Self::InvalidGlob { .. }
+            | Self::InvalidUrl { .. }
+            | Self::InvalidValue { .. }
+            | Self::InvalidOverride(_)
+            | Self::InvalidApiKey { .. }
+            | Self::UnknownKeys { .. }
+            | Self::AmbiguousKey { .. }
+            | Self::NotPresent { .. }
+            | Self::ExtraDeserialize { .. }
+            | Self::XdgConfigNotFound
+            | Self::ProfileConfigNotFound(_)
+            | Self::ProfileNotFound { .. }
+            | Self::WorkspaceNotFound { .. }
+            | Self::NoDefaultWorkspace { .. }
+            | Self::TomlError(_)
+            | Self::ExtendsCycle { .. }
+            | Self::SecretPathOutsideRoot { .. }
+            | Self::InvalidPath(_)
+            | Self::UnsupportedSecretScheme(_)
+            | Self::HomeDirUnavailable { .. }
+            | Self::NoConfigFound { .. }
+            | Self::InsecureUrl { .. }
+            | Self::InvalidTemplate { .. }
+            | Self::BuiltinWebhookKeyUnavailable { .. }
+            | Self::EnvironmentMismatch { .. }
+            | Self::UnsupportedConfigVersion { .. }
+            | Self::ConflictingKeys { .. } => true,
+            #[cfg(feature = "pem")]
+            Self::InvalidKey(_) => true,
+            #[cfg(feature = "sss")]
+            Self::SecretReconstruction(_) => true,
+        }
+    }
+
+    /// The [`std::io::ErrorKind`] behind the error, if an IO failure caused
+    /// it anywhere along the source chain
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            Self::IO(e) | Self::IOError { source: e, .. } => return Some(e.kind()),
+            Self::Workspace { source, .. } => return source.io_kind(),
+            _ => {}
+        }
+        let mut next = std::error::Error::source(self);
+        while let Some(err) = next {
+            if let Some(io) = err.downcast_ref::<std::io::Error>() {
+                return Some(io.kind());
+            }
+            next = err.source();
+        }
+        None
+    }
+
+    /// Where the error was created, when the `backtrace` feature is enabled
+    /// and `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` asked for one
+    ///
+    /// Only errors wrapping a lower-level failure (IO, load and parse
+    /// errors) carry a backtrace.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        let backtrace = match self {
+            Self::IOError { backtrace, .. }
+            | Self::Load { backtrace, .. }
+            | Self::ConfigParseAt { backtrace, .. }
+            | Self::ExtraDeserialize { backtrace, .. } => backtrace.as_deref(),
+            Self::Workspace { source, .. } => source.backtrace(),
+            _ => None,
+        }?;
+        (backtrace.status() == std::backtrace::BacktraceStatus::Captured).then_some(backtrace)
+    }
 }
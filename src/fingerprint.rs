@@ -0,0 +1,93 @@
+use {
+    crate::{FireblocksConfig, diff::SECRET_KEYS},
+    sha2::{Digest, Sha256},
+    std::fmt::Write,
+};
+
+impl FireblocksConfig {
+    /// Stable hex encoded SHA-256 of the effective config and its key, for
+    /// caching artifacts derived from it
+    ///
+    /// Like [`FireblocksConfig::fingerprint_redacted`], plus a SHA-256 of the
+    /// local key material: inline `secret` and `secret_shares`, and the bytes
+    /// of a local `secret_path` file as stored, encrypted or not, read on
+    /// every call. Only that digest enters the fingerprint, never the key
+    /// itself, and rotating the key changes it. Keys from commands, URLs or
+    /// secret managers are not fetched, their settings are hashed instead.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let cache_key = format!("jwt-template-{}", config.fingerprint());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn fingerprint(&self) -> String {
+        let mut key = Sha256::new();
+        if let Some(ref secret) = self.secret {
+            key.update(b"secret=");
+            key.update(secret.as_bytes());
+        }
+        for share in self.secret_shares.iter().flatten() {
+            key.update(b"share=");
+            key.update(share.as_bytes());
+        }
+        if let Some(bytes) = self
+            .local_secret_file()
+            .and_then(|path| std::fs::read(path).ok())
+        {
+            key.update(b"secret_path=");
+            key.update(&bytes);
+        }
+        self.digest(Some(&key.finalize()))
+    }
+
+    /// Stable hex encoded SHA-256 of the effective config, for recording
+    /// which configuration a run used
+    ///
+    /// The hash covers every field except inline secret material (`secret`
+    /// and `secret_shares`). Keys are hashed in sorted order, so the same
+    /// logical config always has the same fingerprint.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// tracing::info!(fingerprint = %config.fingerprint_redacted(), "loaded config");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn fingerprint_redacted(&self) -> String {
+        self.digest(None)
+    }
+
+    fn digest(&self, key: Option<&[u8]>) -> String {
+        let mut hasher = Sha256::new();
+        let flattened = self.flattened().expect("config always serializes to JSON");
+        for (key, value) in flattened {
+            let root = key.split('.').next().unwrap_or(&key);
+            if SECRET_KEYS.contains(&root) {
+                continue;
+            }
+            hasher.update(key.as_bytes());
+            hasher.update(b"=");
+            hasher.update(value.to_string().as_bytes());
+            hasher.update(b"\n");
+        }
+        if let Some(key) = key {
+            hasher.update(b"\0key=");
+            hasher.update(key);
+        }
+        hex(&hasher.finalize())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
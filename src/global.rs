@@ -0,0 +1,63 @@
+//! A process-wide config, initialized once, enabled with the `global` feature
+//!
+//! ```rust,no_run
+//! use fireblocks_config::global;
+//!
+//! fn main() -> fireblocks_config::Result<()> {
+//!     global::init_from_xdg(&["production"])?;
+//!     run()
+//! }
+//!
+//! fn run() -> fireblocks_config::Result<()> {
+//!     let vault = global::get()?.signer.vault_id()?;
+//!     let url = global::with_global(|config| config.url.clone())?;
+//!     Ok(())
+//! }
+//! ```
+use {
+    crate::{Error, FireblocksConfig, Result},
+    std::sync::OnceLock,
+};
+
+static CONFIG: OnceLock<FireblocksConfig> = OnceLock::new();
+
+/// Install `config` as the global config
+///
+/// Only the first call succeeds, later ones return
+/// [`Error::GlobalAlreadyInitialized`] and leave the installed config in
+/// place, so two parts of a program cannot silently disagree about it.
+pub fn init(config: FireblocksConfig) -> Result<&'static FireblocksConfig> {
+    let mut config = Some(config);
+    let installed = CONFIG.get_or_init(|| config.take().expect("runs at most once"));
+    match config {
+        None => Ok(installed),
+        Some(_) => Err(Error::GlobalAlreadyInitialized),
+    }
+}
+
+/// Load the XDG config with `profiles` like
+/// [`FireblocksConfig::init_with_profiles`] and install it with [`init`]
+///
+/// Nothing is loaded when a config is already installed.
+pub fn init_from_xdg<S: AsRef<str>>(profiles: &[S]) -> Result<&'static FireblocksConfig> {
+    if CONFIG.get().is_some() {
+        return Err(Error::GlobalAlreadyInitialized);
+    }
+    init(FireblocksConfig::init_with_profiles(profiles)?)
+}
+
+/// The global config, [`Error::GlobalNotInitialized`] before [`init`]
+pub fn get() -> Result<&'static FireblocksConfig> {
+    try_get().ok_or(Error::GlobalNotInitialized)
+}
+
+/// The global config, `None` before [`init`]
+pub fn try_get() -> Option<&'static FireblocksConfig> {
+    CONFIG.get()
+}
+
+/// Run `f` with the global config, [`Error::GlobalNotInitialized`] before
+/// [`init`]
+pub fn with_global<T>(f: impl FnOnce(&FireblocksConfig) -> T) -> Result<T> {
+    get().map(f)
+}
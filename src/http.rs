@@ -0,0 +1,90 @@
+//! `reqwest` client wiring for [`HttpConfig`], enabled with the `reqwest`
+//! feature
+use crate::{Error, HttpConfig, Result};
+
+impl HttpConfig {
+    /// A `reqwest::ClientBuilder` with the timeouts, proxy, user agent and
+    /// certificate settings of this section applied
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// let client = config.http().client_builder()?.build()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn client_builder(&self) -> Result<reqwest::ClientBuilder> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(proxy) = self.reqwest_proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(ref user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder)
+    }
+
+    /// [`HttpConfig::client_builder`] for `reqwest::blocking`
+    pub(crate) fn blocking_client_builder(&self) -> Result<reqwest::blocking::ClientBuilder> {
+        let mut builder = reqwest::blocking::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(proxy) = self.reqwest_proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        if let Some(ref user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder)
+    }
+
+    fn reqwest_proxy(&self) -> Result<Option<reqwest::Proxy>> {
+        let Some(ref proxy) = self.proxy else {
+            return Ok(None);
+        };
+        let no_proxy = self
+            .no_proxy
+            .as_ref()
+            .and_then(|hosts| reqwest::NoProxy::from_string(&hosts.join(",")));
+        reqwest::Proxy::all(proxy)
+            .map(|proxy| Some(proxy.no_proxy(no_proxy)))
+            .map_err(|e| Error::InvalidUrl {
+                key: "http.proxy".to_string(),
+                value: proxy.clone(),
+                reason: e.to_string(),
+            })
+    }
+}
+
+/// Read the key from an `https://` `secret_path`, sending `token` as a bearer
+/// token when given
+pub(crate) fn fetch_secret(
+    http: &HttpConfig,
+    url: &url::Url,
+    token: Option<&str>,
+) -> Result<Vec<u8>> {
+    let fetch_error = |reason: String| Error::SecretFetch {
+        url: url.to_string(),
+        reason,
+    };
+    tracing::debug!("fetching secret {url}");
+    let mut request = http
+        .blocking_client_builder()?
+        .build()
+        .map_err(|e| fetch_error(e.to_string()))?
+        .get(url.clone());
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| fetch_error(e.to_string()))
+}
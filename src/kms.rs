@@ -0,0 +1,84 @@
+//! KMS-wrapped key source, enabled with the `kms` feature
+//!
+//! The key is stored encrypted under a KMS key as `secret_kms_ciphertext` and
+//! decrypted by the provider sub-feature, e.g. `aws-kms`.
+use {
+    crate::{Error, Result},
+    base64::{Engine, engine::general_purpose::STANDARD},
+};
+
+/// A KMS `Decrypt` call assembled from the config fields
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct DecryptRequest {
+    pub(crate) ciphertext_blob: Vec<u8>,
+    pub(crate) key_id: Option<String>,
+}
+
+/// Decode the base64 ciphertext, ignoring whitespace so wrapped values from
+/// multi-line TOML strings work, and pair it with the optional key id
+pub(crate) fn decrypt_request(ciphertext: &str, key_id: Option<&str>) -> Result<DecryptRequest> {
+    let compact: String = ciphertext.split_whitespace().collect();
+    if compact.is_empty() {
+        return Err(Error::Kms("secret_kms_ciphertext is empty".to_string()));
+    }
+    let ciphertext_blob = STANDARD
+        .decode(compact)
+        .map_err(|e| Error::Kms(format!("invalid secret_kms_ciphertext: {e}")))?;
+    let key_id = key_id
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+    Ok(DecryptRequest {
+        ciphertext_blob,
+        key_id,
+    })
+}
+
+/// Decrypt the ciphertext with the enabled provider
+pub(crate) fn decrypt(ciphertext: &str, key_id: Option<&str>) -> Result<Vec<u8>> {
+    let request = decrypt_request(ciphertext, key_id)?;
+    #[cfg(feature = "aws-kms")]
+    return aws::decrypt(request);
+    #[cfg(not(feature = "aws-kms"))]
+    Err(Error::Kms(format!(
+        "no KMS provider enabled to decrypt {} bytes with key {}, enable the aws-kms feature",
+        request.ciphertext_blob.len(),
+        request.key_id.as_deref().unwrap_or("<embedded>")
+    )))
+}
+
+#[cfg(feature = "aws-kms")]
+mod aws {
+    use {
+        super::DecryptRequest,
+        crate::{Error, Result},
+        aws_config::BehaviorVersion,
+        aws_sdk_kms::{error::DisplayErrorContext, primitives::Blob},
+    };
+
+    /// Call AWS KMS `Decrypt` using the ambient AWS credential chain
+    ///
+    /// Like the Secrets Manager source this drives the request on its own
+    /// runtime, so it must not be called from within an async context.
+    pub(super) fn decrypt(request: DecryptRequest) -> Result<Vec<u8>> {
+        tracing::debug!("decrypting secret with AWS KMS");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        runtime.block_on(async {
+            let sdk_config = aws_config::load_defaults(BehaviorVersion::latest()).await;
+            let client = aws_sdk_kms::Client::new(&sdk_config);
+            let output = client
+                .decrypt()
+                .ciphertext_blob(Blob::new(request.ciphertext_blob))
+                .set_key_id(request.key_id)
+                .send()
+                .await
+                .map_err(|e| Error::Kms(DisplayErrorContext(&e).to_string()))?;
+            output
+                .plaintext()
+                .map(|plaintext| plaintext.as_ref().to_vec())
+                .ok_or_else(|| Error::Kms("decrypt returned no plaintext".to_string()))
+        })
+    }
+}
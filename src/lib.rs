@@ -1,104 +1,3635 @@
 #![doc = include_str!("../README.md")]
+mod api_key;
+#[cfg(feature = "aws")]
+mod aws;
+#[cfg(feature = "cli")]
+mod cli;
 mod config;
+mod diff;
 mod error;
-pub use error::Error;
-use serde::Deserialize;
+mod fingerprint;
+#[cfg(feature = "global")]
+pub mod global;
+#[cfg(feature = "reqwest")]
+mod http;
+#[cfg(feature = "jwt")]
+mod jwt;
+#[cfg(feature = "kms")]
+mod kms;
+mod loader;
+mod migrate;
+#[cfg(feature = "remote")]
+mod remote;
+mod render;
+mod schema;
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "vault")]
+mod vault;
+#[cfg(feature = "watch")]
+mod watch;
+mod workspaces;
+use serde::{Deserialize, Serialize};
+pub use {api_key::ApiKey, error::Error};
 pub type Result<T> = std::result::Result<T, error::Error>;
-pub use config::*;
+#[cfg(feature = "cli")]
+pub use cli::FireblocksCliArgs;
+#[cfg(feature = "jwt")]
+pub use jwt::RequestClaims;
+#[cfg(feature = "watch")]
+pub use watch::WatchHandle;
+pub use {
+    ::config::{Config, FileFormat},
+    config::*,
+    diff::ConfigChange,
+    schema::FieldDescriptor,
+    workspaces::FireblocksWorkspaces,
+};
 
-#[derive(Copy, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[derive(
+    Copy, Deserialize, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum OutputFormat {
     #[default]
     /// Ascii Table
+    #[serde(alias = "table")]
     Table,
     /// Tab separated
+    #[serde(alias = "tsv")]
     Tsv,
+    #[serde(alias = "json")]
     Json,
 }
 
+impl OutputFormat {
+    /// The format for an output file extension, e.g. `json` for
+    /// `report.json`, ignoring case and a leading dot
+    ///
+    /// `txt` maps to [`OutputFormat::Table`]. `csv` and `yaml` have no
+    /// matching format and return `None`, like unknown extensions.
+    ///
+    /// # Example
+    /// ```rust
+    /// use {fireblocks_config::OutputFormat, std::path::Path};
+    ///
+    /// let ext = Path::new("report.json")
+    ///     .extension()
+    ///     .and_then(|e| e.to_str());
+    /// assert_eq!(
+    ///     ext.and_then(OutputFormat::from_extension),
+    ///     Some(OutputFormat::Json)
+    /// );
+    /// ```
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.trim_start_matches('.').to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "tsv" => Some(Self::Tsv),
+            "txt" => Some(Self::Table),
+            _ => None,
+        }
+    }
+
+    /// The MIME type of output in this format, e.g. for a `Content-Type`
+    /// header
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Table => "text/plain",
+            Self::Tsv => "text/tab-separated-values",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Fireblocks fee level for transactions created by a signer
+#[derive(
+    Copy, Deserialize, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum FeeLevel {
+    #[serde(alias = "low", alias = "LOW")]
+    Low,
+    #[default]
+    #[serde(alias = "medium", alias = "MEDIUM")]
+    Medium,
+    #[serde(alias = "high", alias = "HIGH")]
+    High,
+}
+
+/// Sort order for API listing operations, see [`PagingConfig`]
+#[derive(
+    Copy, Deserialize, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum SortOrder {
+    /// Oldest first
+    #[serde(alias = "ASC", alias = "Asc")]
+    Asc,
+    /// Newest first, the Fireblocks API default
+    #[default]
+    #[serde(alias = "DESC", alias = "Desc")]
+    Desc,
+}
+
+impl SortOrder {
+    /// The value Fireblocks expects in an `order` query parameter
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
-        std::{path::PathBuf, time::Duration},
+        std::{
+            path::{Path, PathBuf},
+            sync::{Mutex, MutexGuard},
+            time::Duration,
+        },
     };
 
+    /// Serializes the few tests that set process variables, e.g. for
+    /// `init_from_config_env`, with tests loading through the process
+    /// environment. Other tests pass variables with `new_with_env`.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn env_lock() -> MutexGuard<'static, ()> {
+        ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A synthetic environment for `new_with_env` and friends
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test_log::test]
+    fn test_signer_config_default() -> anyhow::Result<()> {
+        let cfg = super::Signer::default();
+        assert!(!cfg.broadcast);
+        assert!(!cfg.sign_only);
+        assert_eq!(cfg.vault.len(), 0);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_api_key() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let uuid = "6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50";
+        let key = ApiKey::new(uuid);
+        assert_eq!(key.to_string(), "6f0c2a44****************************");
+        assert_eq!(
+            format!("{key:?}"),
+            "ApiKey(6f0c2a44****************************)"
+        );
+        assert_eq!(key.expose(), uuid);
+        key.validate(false)?;
+        assert_eq!(ApiKey::new("short").to_string(), "short");
+
+        match ApiKey::new("blah-blah-blah").validate(false) {
+            Err(e @ Error::InvalidApiKey { .. }) => {
+                let msg = e.to_string();
+                assert!(msg.contains("allow_nonstandard_api_key"), "{msg}");
+                assert!(msg.contains("'**************'"), "{msg}");
+            }
+            other => panic!("Expected InvalidApiKey error, got {other:?}"),
+        }
+        ApiKey::new("blah-blah-blah").validate(true)?;
+        assert!(ApiKey::new(" ").validate(true).is_err());
+
+        // fixtures opt out of the UUID check, without it loading fails
+        let b = "examples/default.toml";
+        assert!(matches!(
+            FireblocksConfig::new_with_kv(b, &[], &["allow_nonstandard_api_key=false"]),
+            Err(Error::InvalidApiKey { .. })
+        ));
+
+        let cfg = FireblocksConfig::new_with_env(
+            b,
+            &[],
+            vars(&[
+                ("FIREBLOCKS_API_KEY", uuid),
+                ("FIREBLOCKS_ALLOW_NONSTANDARD_API_KEY", "false"),
+            ]),
+        )?;
+        assert_eq!(cfg.api_key, uuid);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_sign_only() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert!(!cfg.signer.sign_only);
+
+        let cfg = FireblocksConfig::new(b, &["examples/sign_only.toml"])?;
+        assert!(cfg.signer.sign_only);
+        assert!(!cfg.signer.broadcast);
+        assert_eq!(cfg.signer.vault, "0");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_fee_policy() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!(cfg.signer.max_fee(), None);
+        assert_eq!(cfg.signer.fee_level(), None);
+        assert_eq!(cfg.signer.confirmations(), None);
+        assert!(cfg.signer.fail_on_fee_exceeded());
+
+        let cfg = FireblocksConfig::new(b, &["examples/fee_policy.toml"])?;
+        assert_eq!(cfg.signer.max_fee(), Some("0.0025"));
+        assert_eq!(cfg.signer.fee_level(), Some(FeeLevel::Low));
+        assert_eq!(cfg.signer.confirmations(), Some(12));
+        assert!(!cfg.signer.fail_on_fee_exceeded());
+
+        for valid in ["0", "12", "0.005", ".5", "5."] {
+            let signer = Signer {
+                max_fee: Some(valid.to_string()),
+                ..Default::default()
+            };
+            signer.validate()?;
+        }
+        for invalid in ["", ".", "-1", "1e-3", "0.1.2", " 1", "abc"] {
+            let signer = Signer {
+                max_fee: Some(invalid.to_string()),
+                ..Default::default()
+            };
+            assert!(signer.validate().is_err(), "{invalid:?}");
+        }
+        match FireblocksConfig::new_with_kv(b, &[], &["signer.max_fee=-0.5"]) {
+            Err(Error::InvalidValue { key, value, .. }) => {
+                assert_eq!(key, "signer.max_fee");
+                assert_eq!(value, "-0.5");
+            }
+            other => panic!("Expected InvalidValue error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_external_tx_id() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert!(cfg.signer.external_tx_id_template.is_none());
+        assert_eq!(cfg.signer.external_tx_id(1), None);
+
+        let cfg = FireblocksConfig::new(b, &["examples/external_tx_id.toml"])?;
+        assert_eq!(cfg.signer.external_tx_id(9).as_deref(), Some("payout-7-9"));
+        // deterministic for a given nonce
+        assert_eq!(cfg.signer.external_tx_id(9), cfg.signer.external_tx_id(9));
+
+        let signer = |template: &str| Signer {
+            vault: "7".to_string(),
+            external_tx_id_template: Some(template.to_string()),
+            ..Default::default()
+        };
+        let id = signer("tx-{uuid}")
+            .external_tx_id(0)
+            .expect("template is valid");
+        let uuid = id.strip_prefix("tx-").expect("prefix is kept");
+        assert!(ApiKey::new(uuid).validate(false).is_ok(), "{uuid}");
+        assert_ne!(
+            signer("{uuid}").external_tx_id(0),
+            signer("{uuid}").external_tx_id(0)
+        );
+
+        let before = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        let timestamp: u64 = signer("{timestamp}")
+            .external_tx_id(0)
+            .expect("template is valid")
+            .parse()?;
+        assert!(timestamp >= before);
+        assert_eq!(
+            signer("static").external_tx_id(5).as_deref(),
+            Some("static")
+        );
+
+        for (template, placeholder) in [("{vault}-{hash}", "hash"), ("tx-{nonce", "{nonce")] {
+            match signer(template).validate() {
+                Err(Error::InvalidTemplate {
+                    template: t,
+                    placeholder: p,
+                }) => {
+                    assert_eq!(t, template);
+                    assert_eq!(p, placeholder);
+                }
+                other => panic!("Expected InvalidTemplate error, got {other:?}"),
+            }
+            assert_eq!(signer(template).external_tx_id(0), None);
+        }
+        assert!(matches!(
+            FireblocksConfig::new_with_kv(b, &[], &["signer.external_tx_id_template={id}"]),
+            Err(Error::InvalidTemplate { .. })
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_vault_id() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert_eq!(cfg.signer.vault_id()?, 0);
+
+        let signer = Signer {
+            vault: "12".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(signer.vault_id()?, 12);
+
+        let signer = Signer {
+            vault: "abc".to_string(),
+            ..Default::default()
+        };
+        match signer.vault_id() {
+            Err(Error::InvalidVault { value }) => assert_eq!(value, "abc"),
+            other => panic!("Expected InvalidVault error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_poll_interval_validation() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        match FireblocksConfig::new(b, &["examples/zero_interval.toml"]) {
+            Err(Error::InvalidDuration(msg)) => assert!(msg.contains("poll_interval")),
+            other => panic!("Expected InvalidDuration error, got {other:?}"),
+        }
+
+        let signer = Signer {
+            poll_interval: Duration::from_secs(1),
+            ..Default::default()
+        };
+        signer.validate()?;
+
+        // a zero timeout disables polling, so any interval is allowed
+        let signer = Signer {
+            poll_timeout: Duration::ZERO,
+            poll_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        signer.validate()?;
+
+        match FireblocksConfig::new(b, &["examples/interval_exceeds_timeout.toml"]) {
+            Err(Error::InvalidDuration(msg)) => {
+                assert!(msg.contains("poll_interval (30s)"), "{msg}");
+                assert!(msg.contains("poll_timeout (10s)"), "{msg}");
+            }
+            other => panic!("Expected InvalidDuration error, got {other:?}"),
+        }
+        let signer = Signer {
+            poll_timeout: Duration::from_secs(5),
+            poll_interval: Duration::from_secs(5),
+            ..Default::default()
+        };
+        signer.validate()?;
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_polling_disabled() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert!(cfg.signer.polling_enabled());
+        assert_eq!(
+            cfg.signer.effective_timeout(),
+            Some(Duration::from_secs(120))
+        );
+
+        let cfg = FireblocksConfig::new("examples/default.toml", &["examples/no_polling.toml"])?;
+        assert!(!cfg.signer.polling_enabled());
+        assert_eq!(cfg.signer.effective_timeout(), None);
+        assert_eq!(cfg.signer.poll_timeout, Duration::ZERO);
+        assert_eq!(cfg.signer.attempts(), 0);
+
+        for value in ["none", "0"] {
+            let cfg = FireblocksConfig::new_with_env(
+                "examples/default.toml",
+                &[],
+                vars(&[("FIREBLOCKS_SIGNER__POLL_TIMEOUT", value)]),
+            )?;
+            assert!(!cfg.signer.polling_enabled(), "{value}");
+        }
+        for value in ["0", "0.0", "\"0\"", "\"NONE\"", "\" none \""] {
+            let contents = format!(
+                "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n[signer]\nvault = \
+                 \"0\"\npoll_timeout = {value}\n"
+            );
+            let cfg = FireblocksConfig::from_contents(&contents, FileFormat::Toml)?;
+            assert_eq!(cfg.signer.effective_timeout(), None, "{value}");
+        }
+
+        assert!(
+            FireblocksConfig::from_contents(
+                "[signer]\nvault = \"0\"\npoll_timeout = \"never\"\n",
+                FileFormat::Toml
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_signer_polling_helpers() -> anyhow::Result<()> {
+        let signer = Signer::default();
+        assert_eq!(signer.attempts(), 36);
+
+        let before = std::time::Instant::now();
+        let deadline = signer.poll_deadline();
+        assert!(deadline >= before + signer.poll_timeout);
+        assert!(deadline <= std::time::Instant::now() + signer.poll_timeout);
+
+        // sub-second intervals count as one second
+        let signer = Signer {
+            poll_timeout: Duration::from_secs(10),
+            poll_interval: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(signer.attempts(), 10);
+
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert_eq!(cfg.signer.attempts(), 24);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_signer_backoff() -> anyhow::Result<()> {
+        // fixed interval by default
+        let signer = Signer::default();
+        assert_eq!(signer.poll_backoff, Some(1.0));
+        for attempt in 0..5 {
+            assert_eq!(signer.next_interval(attempt), Duration::from_secs(5));
+        }
+
+        let signer = Signer {
+            poll_backoff: Some(2.0),
+            ..Default::default()
+        };
+        let intervals: Vec<u64> = (0..4)
+            .map(|attempt| signer.next_interval(attempt).as_secs())
+            .collect();
+        assert_eq!(intervals, vec![5, 10, 20, 40]);
+
+        let signer = Signer {
+            poll_backoff: Some(2.0),
+            poll_max_interval: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+        assert_eq!(signer.next_interval(2), Duration::from_secs(20));
+        assert_eq!(signer.next_interval(3), Duration::from_secs(30));
+        assert_eq!(signer.next_interval(100), Duration::from_secs(30));
+
+        let cfg = FireblocksConfig::new("examples/backoff.toml", &[])?;
+        assert_eq!(cfg.signer.poll_backoff, Some(1.5));
+        assert_eq!(cfg.signer.poll_max_interval, Some(Duration::from_secs(60)));
+        assert_eq!(cfg.signer.next_interval(1), Duration::from_secs(3));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_retry_config() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!(cfg.retry, RetryConfig::default());
+        assert_eq!(cfg.retry.max_retries, 5);
+        assert!(cfg.retry.retry_on_rate_limit);
+        let delays: Vec<u64> = cfg.retry.delays().map(|d| d.as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16]);
+
+        let cfg = FireblocksConfig::new(b, &["examples/retry.toml"])?;
+        assert_eq!(cfg.retry.max_retries, 3);
+        assert_eq!(cfg.retry.initial_backoff, Duration::from_secs(2));
+        assert_eq!(cfg.retry.max_backoff, Duration::from_secs(10));
+        assert_eq!(cfg.retry.backoff_multiplier, 3.0);
+        assert!(!cfg.retry.retry_on_rate_limit);
+        let delays: Vec<u64> = cfg.retry.delays().map(|d| d.as_secs()).collect();
+        assert_eq!(delays, vec![2, 6, 10]);
+
+        let retry = RetryConfig {
+            max_retries: 0,
+            ..Default::default()
+        };
+        assert_eq!(retry.delays().count(), 0);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_paging_config() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!(cfg.paging(), &PagingConfig::default());
+        assert_eq!(cfg.paging().page_size, 200);
+        assert_eq!(cfg.paging().max_items, None);
+        assert_eq!(cfg.paging().order, SortOrder::Desc);
+
+        let cfg = FireblocksConfig::new(b, &["examples/paging.toml"])?;
+        assert_eq!(cfg.paging().page_size, 100);
+        assert_eq!(cfg.paging().max_items, Some(1000));
+        assert_eq!(cfg.paging().order.as_api_str(), "ASC");
+
+        let cfg = FireblocksConfig::new_with_env(
+            b,
+            &["examples/paging.toml"],
+            vars(&[
+                ("FIREBLOCKS_PAGING__PAGE_SIZE", "25"),
+                ("FIREBLOCKS_PAGING__ORDER", "desc"),
+            ]),
+        )?;
+        assert_eq!(cfg.paging().page_size, 25);
+        assert_eq!(cfg.paging().order, SortOrder::Desc);
+
+        let err =
+            FireblocksConfig::new_with_env(b, &[], vars(&[("FIREBLOCKS_PAGING__PAGE_SIZE", "0")]))
+                .unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidValue { ref key, ref reason, .. }
+                if key == "paging.page_size" && reason == "must be greater than zero"),
+            "{err:?}"
+        );
+
+        let err = FireblocksConfig::new_with_env(
+            b,
+            &["examples/paging.toml"],
+            vars(&[("FIREBLOCKS_PAGING__MAX_ITEMS", "50")]),
+        )
+        .unwrap_err();
+        match err {
+            Error::InvalidValue { key, value, reason } => {
+                assert_eq!(key, "paging.max_items");
+                assert_eq!(value, "50");
+                assert!(
+                    reason.contains("at least paging.page_size (100)"),
+                    "{reason}"
+                );
+            }
+            other => panic!("Expected InvalidValue error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_http_config() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!(*cfg.http(), HttpConfig::default());
+        assert_eq!(cfg.http().connect_timeout, Duration::from_secs(10));
+        assert_eq!(cfg.http().request_timeout, Duration::from_secs(30));
+        assert_eq!(cfg.http().max_concurrency, 8);
+        assert!(cfg.http().proxy.is_none());
+        assert!(cfg.http().no_proxy.is_none());
+        assert!(!cfg.http().accept_invalid_certs);
+
+        let cfg = FireblocksConfig::new(b, &["examples/http.toml"])?;
+        let http = cfg.http();
+        assert_eq!(http.connect_timeout, Duration::from_secs(5));
+        assert_eq!(http.request_timeout, Duration::from_secs(60));
+        assert_eq!(http.max_concurrency, 4);
+        assert_eq!(http.proxy.as_deref(), Some("http://proxy.internal:3128"));
+        assert_eq!(
+            http.no_proxy.as_deref(),
+            Some(&["localhost".to_string(), ".internal".to_string()][..])
+        );
+        assert_eq!(http.user_agent.as_deref(), Some("fireblocks-config-tests"));
+
+        match FireblocksConfig::new(b, &["examples/invalid_proxy.toml"]) {
+            Err(Error::InvalidUrl { key, value, .. }) => {
+                assert_eq!(key, "http.proxy");
+                assert_eq!(value, "ftp://proxy.internal");
+            }
+            other => panic!("Expected InvalidUrl error, got {other:?}"),
+        }
+        let http = HttpConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(http.validate(), Err(Error::InvalidUrl { .. })));
+
+        match FireblocksConfig::new_with_kv(b, &[], &["http.max_concurrency=0"]) {
+            Err(Error::InvalidValue { key, .. }) => assert_eq!(key, "http.max_concurrency"),
+            other => panic!("Expected InvalidValue error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test_log::test]
+    fn test_http_client_builder() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::new("examples/default.toml", &["examples/http.toml"])?;
+        cfg.http().client_builder()?.build()?;
+
+        let http = HttpConfig {
+            accept_invalid_certs: true,
+            ..Default::default()
+        };
+        http.client_builder()?.build()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "jwt")]
+    #[test_log::test]
+    fn test_sign_request() -> anyhow::Result<()> {
+        use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new_with_kv(b, &[], &["jwt_ttl=20"])?;
+        assert_eq!(cfg.jwt_ttl, Some(Duration::from_secs(20)));
+        let token = cfg.sign_request("/v1/vault/accounts_paged?limit=10", b"{}")?;
+
+        let public = DecodingKey::from_rsa_pem(&std::fs::read("examples/test.pub.pem")?)?;
+        let claims = jsonwebtoken::decode::<RequestClaims>(
+            &token,
+            &public,
+            &Validation::new(Algorithm::RS256),
+        )?
+        .claims;
+        assert_eq!(claims.uri, "/v1/vault/accounts_paged?limit=10");
+        assert_eq!(claims.sub, "blah");
+        assert_eq!(claims.exp - claims.iat, 20);
+        assert_eq!(
+            claims.body_hash,
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+
+        // every token gets a fresh nonce
+        let other = cfg.sign_request("/v1/vault/accounts_paged?limit=10", b"{}")?;
+        let other = jsonwebtoken::decode::<RequestClaims>(
+            &other,
+            &public,
+            &Validation::new(Algorithm::RS256),
+        )?
+        .claims;
+        assert_ne!(claims.nonce, other.nonce);
+
+        let mut cfg = cfg;
+        cfg.secret = Some("not a key".to_string());
+        assert!(matches!(
+            cfg.sign_request("/v1/users", b""),
+            Err(Error::Jwt(_))
+        ));
+        Ok(())
+    }
+
+    #[ignore]
+    #[test_log::test]
+    fn test_gpg_config() -> anyhow::Result<()> {
+        let b = "examples/config-gpg.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        cfg.get_key()?;
+        Ok(())
+    }
+
+    #[ignore]
+    #[test_log::test]
+    fn test_gpg_encrypted_config() -> anyhow::Result<()> {
+        let b = "examples/config.toml.gpg";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        cfg.get_key()?;
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_encrypted_config_detection() -> anyhow::Result<()> {
+        let armored = b"-----BEGIN PGP MESSAGE-----\n\nhQEMA...\n-----END PGP MESSAGE-----\n";
+        let toml = b"api_key = \"blah\"\n";
+        assert!(loader::is_encrypted_config(
+            Path::new("default.toml"),
+            armored
+        ));
+        assert!(loader::is_encrypted_config(
+            Path::new("default.toml"),
+            b"\n  -----BEGIN PGP MESSAGE-----\n"
+        ));
+        assert!(loader::is_encrypted_config(
+            Path::new("prod.toml.gpg"),
+            b"\x85\x01"
+        ));
+        assert!(loader::is_encrypted_config(
+            Path::new("prod.toml.GPG"),
+            toml
+        ));
+        assert!(!loader::is_encrypted_config(
+            Path::new("default.toml"),
+            toml
+        ));
+        assert!(!loader::is_encrypted_config(
+            Path::new("default.toml"),
+            b"# -----BEGIN PGP MESSAGE-----\n"
+        ));
+
+        #[cfg(not(feature = "gpg"))]
+        {
+            let dir = tempfile::tempdir()?;
+            let path = dir.path().join("default.toml");
+            std::fs::write(&path, armored)?;
+            assert!(matches!(
+                FireblocksConfig::new(&path, &[]),
+                Err(Error::ConfigDecryption { .. })
+            ));
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_config() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!("blah", cfg.api_key);
+        assert!(cfg.secret_path.is_some());
+        if let Some(p) = cfg.secret_path.as_ref() {
+            assert_eq!(PathBuf::from("examples/test.pem"), *p);
+        }
+        assert_eq!("https://sandbox-api.fireblocks.io/v1", cfg.url);
+        assert_eq!(OutputFormat::Table, cfg.display_config.output);
+        let cfg =
+            FireblocksConfig::new_with_env(b, &[], vars(&[("FIREBLOCKS_SECRET", "override")]))?;
+        assert!(cfg.secret.is_some());
+        assert_eq!(String::from("override").as_bytes(), cfg.get_key()?);
+        if let Some(ref k) = cfg.secret_path {
+            assert_eq!(PathBuf::from("examples/test.pem"), *k);
+        }
+
+        assert_eq!(cfg.signer.vault, "0");
+        assert!(!cfg.signer.broadcast);
+        assert!(!cfg.signer.sign_only);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_env_output_override() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new_with_env(
+            b,
+            &[],
+            vars(&[
+                ("FIREBLOCKS_DISPLAY__OUTPUT", "json"),
+                ("FIREBLOCKS_EXTRA__RPC_URL", "https://env"),
+            ]),
+        )?;
+        assert_eq!(cfg.display_config.output, OutputFormat::Json);
+        assert_eq!(cfg.get_extra::<String, _>("rpc_url")?, "https://env");
+        assert!(!cfg.has_extra("rpc__url"));
+
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!(cfg.display_config.output, OutputFormat::Table);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_display_summary() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new("examples/schema_full.toml", &[])?;
+        let summary = cfg.to_string();
+        assert_eq!(
+            summary,
+            "FireblocksConfig { url=https://sandbox-api.fireblocks.io/v1, vault=3, mainnet=false, \
+             output=Json, extra_keys=1 }"
+        );
+        assert!(!summary.contains(cfg.api_key.expose()));
+        assert!(!summary.contains("inline"));
+        assert!(!summary.contains("01aa"));
+        assert!(!summary.contains("test.pem"));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_unknown_keys() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new("examples/unknown_keys.toml", &[])?;
+        assert_eq!(cfg.unknown_keys(), ["displays", "signer.poll_timout"]);
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(180));
+        assert_eq!(cfg.display_config.output, OutputFormat::Table);
+
+        let cfg = FireblocksConfig::new("examples/schema_full.toml", &[])?;
+        assert!(cfg.unknown_keys().is_empty());
+
+        let result =
+            FireblocksConfig::new_with_kv("examples/unknown_keys.toml", &[] as &[&str], &[
+                "treat_unknown_as_error=true",
+            ]);
+        match result {
+            Err(Error::UnknownKeys { keys }) => {
+                assert_eq!(keys, ["displays", "signer.poll_timout"])
+            }
+            other => panic!("expected UnknownKeys, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_signer_with_key() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        let (signer, key) = cfg.signer_with_key()?;
+        assert_eq!(signer.vault, "0");
+        assert_eq!(signer, cfg.signer);
+        assert_eq!(key, std::fs::read("examples/test.pem")?);
+        assert_eq!(cfg.into_signer(), signer);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_extra_bytes() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::new("examples/extra_values.toml", &[])?;
+        assert_eq!(cfg.get_extra_bytes("raw")?, 2048);
+        assert_eq!(cfg.get_extra_bytes("max_upload")?, 25_000_000);
+        assert_eq!(cfg.get_extra_bytes("chunk")?, 4 * 1024 * 1024);
+        assert_eq!(cfg.get_extra_bytes("part")?, 1500);
+        assert_eq!(cfg.get_extra_bytes("gigs")?, 2_000_000_000);
+        assert!(matches!(
+            cfg.get_extra_bytes("missing"),
+            Err(Error::NotPresent { .. })
+        ));
+        match cfg.get_extra_bytes("bad_unit") {
+            Err(Error::InvalidValue { key, value, .. }) => {
+                assert_eq!(key, "bad_unit");
+                assert_eq!(value, "10 parsecs");
+            }
+            other => panic!("expected InvalidValue, got {other:?}"),
+        }
+        assert!(matches!(
+            cfg.get_extra_bytes("negative"),
+            Err(Error::InvalidValue { .. })
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_extra_path() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::new("examples/extra_values.toml", &[])?;
+        let home = dirs::home_dir().expect("home directory");
+        let keyfile = cfg.get_extra_path("keyfile")?;
+        assert!(keyfile.is_absolute());
+        assert_eq!(keyfile, home.join("keys/k.pem"));
+        // the literal value is unchanged through get_extra
+        assert_eq!(
+            cfg.get_extra::<PathBuf, _>("keyfile")?,
+            Path::new("~/keys/k.pem")
+        );
+
+        // relative to the base config like secret_path
+        assert_eq!(
+            cfg.get_extra_path("keyfile_relative")?,
+            Path::new("examples/test.pem")
+        );
+        assert_eq!(
+            cfg.get_extra_path("keyfile_env")?,
+            Path::new(&std::env::var("HOME")?).join("keys/$k.pem")
+        );
+        match cfg.get_extra_path("keyfile_unset") {
+            Err(Error::InvalidValue { key, reason, .. }) => {
+                assert_eq!(key, "keyfile_unset");
+                assert!(reason.contains("FIREBLOCKS_TEST_UNSET_DIR"), "{reason}");
+            }
+            other => panic!("expected InvalidValue, got {other:?}"),
+        }
+        assert!(matches!(
+            cfg.get_extra_path("raw"),
+            Err(Error::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            cfg.get_extra_path("missing"),
+            Err(Error::NotPresent { .. })
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test_log::test]
+    fn test_extra_datetime() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::new("examples/extra_values.toml", &[])?;
+        let expected = chrono::DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z")?;
+        for key in ["expires", "expires_offset", "expires_local", "expires_date"] {
+            assert_eq!(cfg.get_extra_datetime(key)?, expected, "{key}");
+        }
+        assert!(matches!(
+            cfg.get_extra_datetime("missing"),
+            Err(Error::NotPresent { .. })
+        ));
+        match cfg.get_extra_datetime("expires_bad") {
+            Err(Error::InvalidValue { key, value, .. }) => {
+                assert_eq!(key, "expires_bad");
+                assert_eq!(value, "next tuesday");
+            }
+            other => panic!("expected InvalidValue, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "pem")]
+    #[test_log::test]
+    fn test_validate_key_pem() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.validate_key_pem()?;
+
+        cfg.secret_path = Some("examples/default.toml".into());
+        assert!(matches!(cfg.validate_key_pem(), Err(Error::InvalidKey(_))));
+
+        cfg.secret_path = Some("examples/test.pub.pem".into());
+        assert!(matches!(cfg.validate_key_pem(), Err(Error::InvalidKey(_))));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_extra_aliases() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::new("examples/extra_aliases.toml", &[])?;
+
+        // normalization ignores case, `_` and `-`
+        assert_eq!(cfg.get_extra_ci::<i64, _>("timeout")?, 40);
+        assert_eq!(
+            cfg.get_extra_ci::<String, _>("RPC_URL")?,
+            "https://rpc.example.com"
+        );
+        assert!(matches!(
+            cfg.get_extra::<i64, _>("timeout"),
+            Err(Error::NotPresent { .. })
+        ));
+        match cfg.get_extra_ci::<i64, _>("chain-id") {
+            Err(Error::AmbiguousKey { key, candidates }) => {
+                assert_eq!(key, "chain-id");
+                assert_eq!(candidates, ["chainId", "chain_id"]);
+            }
+            other => panic!("expected AmbiguousKey, got {other:?}"),
+        }
+
+        // aliases are found by every extra getter
+        assert!(!cfg.has_extra("rpc_url"));
+        let cfg = cfg.with_extra_aliases(&[
+            ("rpc_url", &["rpcUrl", "rpc-endpoint"]),
+            ("chain", &["chain_id", "chainId"]),
+        ]);
+        assert!(cfg.has_extra("rpc_url"));
+        assert_eq!(
+            cfg.get_extra::<String, _>("rpc_url")?,
+            "https://rpc.example.com"
+        );
+        assert!(matches!(
+            cfg.get_extra::<i64, _>("chain"),
+            Err(Error::AmbiguousKey { .. })
+        ));
+        assert_eq!(cfg.get_extra::<i64, _>("chain_id")?, 1);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_from_file_section() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let path = "examples/combined.toml";
+        let cfg = FireblocksConfig::from_file_section(path, "fireblocks")?;
+        assert_eq!("section", cfg.api_key);
+        assert_eq!(cfg.signer.vault, "4");
+        assert_eq!(
+            cfg.get_extra::<String, _>("rpc_url")?,
+            "https://rpc.example.com"
+        );
+        assert!(cfg.unknown_keys().is_empty());
+
+        let cfg = FireblocksConfig::from_file_section(path, "services.fireblocks")?;
+        assert_eq!("nested", cfg.api_key);
+        assert_eq!(cfg.signer.vault, "9");
+
+        for section in ["missing", "database.url"] {
+            match FireblocksConfig::from_file_section(path, section) {
+                Err(Error::ConfigNotFound(msg)) => {
+                    assert!(msg.contains(section) && msg.contains(path), "{msg}")
+                }
+                other => panic!("expected ConfigNotFound, got {other:?}"),
+            }
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_sources() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let o = "examples/override.toml";
+        let cfg = FireblocksConfig::new(b, &[o])?;
+        assert_eq!(cfg.sources(), [
+            ConfigSource::File(b.into()),
+            ConfigSource::File(o.into())
+        ]);
+
+        let cfg = FireblocksConfig::new_with_env(b, &[o], vars(&[("FIREBLOCKS_DEBUG", "true")]))?;
+        let reloaded = cfg.reload()?;
+        let expected = [
+            ConfigSource::File(b.into()),
+            ConfigSource::File(o.into()),
+            ConfigSource::Environment,
+        ];
+        assert_eq!(cfg.sources(), expected);
+        assert_eq!(reloaded.sources(), expected);
+        assert!(format!("{cfg:?}").contains("Environment"));
+
+        let cfg = FireblocksConfig::from_file_section("examples/combined.toml", "fireblocks")?;
+        assert!(cfg.sources().is_empty());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_applied_sources() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new_with_env(
+            "examples/default.toml",
+            &["examples/override.toml"],
+            vars(&[("FIREBLOCKS_DEBUG", "true")]),
+        )?;
+        let applied: Vec<_> = cfg
+            .applied_sources()
+            .iter()
+            .map(|s| (s.index, s.kind, s.source.to_string()))
+            .collect();
+        assert_eq!(applied, [
+            (0, SourceKind::Base, "examples/default.toml".to_string()),
+            (
+                1,
+                SourceKind::Override,
+                "examples/override.toml".to_string()
+            ),
+            (2, SourceKind::Env, "environment".to_string()),
+        ]);
+        assert_eq!(SourceKind::Override.to_string(), "override");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_try_from_path() -> anyhow::Result<()> {
+        let path = "examples/default.toml";
+        let expected = FireblocksConfig::new(path, &[])?;
+
+        let cfg = FireblocksConfig::try_from(Path::new(path))?;
+        assert_eq!(cfg.signer.vault, expected.signer.vault);
+        let cfg = FireblocksConfig::try_from(PathBuf::from(path))?;
+        assert_eq!(cfg.signer.vault, expected.signer.vault);
+        let cfg: FireblocksConfig = path.parse()?;
+        assert_eq!(cfg.signer.vault, expected.signer.vault);
+        assert_eq!(cfg.sources(), expected.sources());
+
+        let not_a_path = "api_key = \"x\"";
+        match not_a_path.parse::<FireblocksConfig>() {
+            Err(Error::IOError { path, .. }) => assert_eq!(path, not_a_path),
+            other => panic!("expected IOError, got {other:?}"),
+        }
+        assert!(FireblocksConfig::try_from(Path::new("examples/missing.toml")).is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_apply_override() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let o = "examples/override.toml";
+        let expected = FireblocksConfig::with_overrides(b, [o])?;
+        let cfg = FireblocksConfig::new(b, &[])?.apply_override(o)?;
+        assert_eq!(cfg, expected);
+        assert_eq!(cfg.reload()?, expected);
+
+        let contents = std::fs::read_to_string(o)?;
+        let cfg = FireblocksConfig::new(b, &[])?.apply_override_str(&contents)?;
+        assert_eq!(cfg.mainnet, expected.mainnet);
+        assert_eq!(cfg.url, expected.url);
+        assert_eq!(cfg.api_key, expected.api_key);
+        assert_eq!(cfg.sources(), [ConfigSource::File(b.into())]);
+
+        let cfg = FireblocksConfig::new(b, &[])?;
+        let merged = cfg.apply_override_str("[extra]\nadded = 1\n")?;
+        assert_eq!(merged.get_extra::<i64, _>("added")?, 1);
+        for key in cfg.extra.keys() {
+            assert_eq!(merged.extra.get(key), cfg.extra.get(key), "{key}");
+        }
+        assert_eq!(merged.secret, cfg.secret);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_extra_vec() -> anyhow::Result<()> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Endpoint {
+            name: String,
+            url: String,
+            weight: u32,
+        }
+
+        let cfg = FireblocksConfig::new("examples/extra_values.toml", &[])?;
+        let endpoints: Vec<Endpoint> = cfg.get_extra_vec("endpoints")?;
+        assert_eq!(endpoints, [
+            Endpoint {
+                name: "primary".to_string(),
+                url: "https://rpc.example.com".to_string(),
+                weight: 3,
+            },
+            Endpoint {
+                name: "backup".to_string(),
+                url: "https://backup.example.com".to_string(),
+                weight: 1,
+            },
+        ]);
+        let names: Vec<serde_json::Value> = cfg.get_extra_vec("endpoints")?;
+        assert_eq!(names.len(), 2);
+
+        match cfg.get_extra_vec::<Endpoint, _>("raw") {
+            Err(Error::InvalidValue { key, reason, .. }) => {
+                assert_eq!(key, "raw");
+                assert_eq!(reason, "expected an array");
+            }
+            other => panic!("expected InvalidValue, got {other:?}"),
+        }
+        assert!(matches!(
+            cfg.get_extra_vec::<Endpoint, _>("missing"),
+            Err(Error::NotPresent { .. })
+        ));
+        assert!(cfg.get_extra_vec::<u32, _>("endpoints").is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_relative_secret_path() -> anyhow::Result<()> {
+        let _env = env_lock();
+        // `../test.pem` only exists relative to the config file, not the
+        // working directory
+        let cfg = FireblocksConfig::new("examples/relative/config.toml", &[])?;
+        assert!(!Path::new("../test.pem").exists());
+        assert_eq!(
+            cfg.expanded_secret_path()?,
+            PathBuf::from("examples/relative/../test.pem")
+        );
+        assert_eq!(cfg.get_key()?, std::fs::read("examples/test.pem")?);
+
+        // Paths written against the working directory keep resolving
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert_eq!(
+            cfg.expanded_secret_path()?,
+            PathBuf::from("examples/test.pem")
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test_log::test]
+    fn test_secret_path_root() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().join("keys");
+        std::fs::create_dir(&root)?;
+        std::fs::copy("examples/test.pem", root.join("key.pem"))?;
+        std::fs::write(dir.path().join("outside.pem"), "outside")?;
+        std::os::unix::fs::symlink(dir.path().join("outside.pem"), root.join("link.pem"))?;
+        let config = dir.path().join("config.toml");
+        let load = |secret_path: &str| -> anyhow::Result<FireblocksConfig> {
+            std::fs::write(
+                &config,
+                format!(
+                    "api_key = \"root\"\nallow_nonstandard_api_key = true\nsecret_path = \
+                     \"{secret_path}\"\nsecret_path_root = \"keys\"\n[signer]\nvault = \"0\"\n"
+                ),
+            )?;
+            Ok(FireblocksConfig::new(&config, &[])?)
+        };
+
+        let cfg = load("keys/key.pem")?;
+        assert_eq!(cfg.get_key()?, std::fs::read("examples/test.pem")?);
+
+        for escaping in ["keys/../outside.pem", "keys/link.pem"] {
+            match load(escaping)?.get_key() {
+                Err(Error::SecretPathOutsideRoot { path, root }) => {
+                    assert!(path.ends_with(escaping), "{path}");
+                    assert!(root.ends_with("keys"), "{root}");
+                }
+                other => panic!("expected SecretPathOutsideRoot for {escaping}, got {other:?}"),
+            }
+        }
+
+        // without a root the same paths are read as before
+        let mut cfg = load("keys/link.pem")?;
+        cfg.secret_path_root = None;
+        assert_eq!(cfg.get_key()?, b"outside");
+        Ok(())
+    }
+
+    #[cfg(feature = "interactive")]
+    #[test_log::test]
+    fn test_get_key_interactive() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert_eq!(cfg.prompt_key(false)?, std::fs::read("examples/test.pem")?);
+
+        cfg.secret_path = None;
+        cfg.secret = None;
+        assert!(matches!(cfg.prompt_key(false), Err(Error::MissingSecret)));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_field_aliases() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new("examples/aliases.toml", &[])?;
+        assert_eq!(cfg.api_key, "6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50");
+        assert_eq!(cfg.url, "https://api.fireblocks.io/v1");
+        assert_eq!(cfg.secret_path, Some(PathBuf::from("examples/test.pem")));
+        assert!(cfg.unknown_keys().is_empty());
+
+        let cfg = FireblocksConfig::from_contents(
+            "apiKey = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nbase_url = \"https://x.io\"\n\
+             secret_key = \"inline\"\n[signer]\nvault = \"0\"\n",
+            FileFormat::Toml,
+        )?;
+        assert_eq!(cfg.url, "https://x.io");
+        assert_eq!(cfg.secret.as_deref(), Some("inline"));
+
+        // the canonical name wins when both are set
+        let cfg = FireblocksConfig::new_with_env(
+            "examples/aliases.toml",
+            &[],
+            vars(&[("FIREBLOCKS_URL", "https://env.fireblocks.io/v1")]),
+        )?;
+        assert_eq!(cfg.url, "https://env.fireblocks.io/v1");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_legacy_aliases() -> anyhow::Result<()> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _env = env_lock();
+        let capture = CaptureLayer::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let cfg = tracing::subscriber::with_default(subscriber, || {
+            FireblocksConfig::new("examples/legacy.toml", &[])
+        })?;
+        assert_eq!(cfg.api_key, "6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50");
+        assert_eq!(cfg.url, "https://sandbox-api.fireblocks.io/v1");
+        assert_eq!(cfg.secret_path, Some(PathBuf::from("examples/test.pem")));
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(60));
+        assert_eq!(cfg.signer.poll_interval, Duration::from_secs(2));
+        assert_eq!(cfg.http.request_timeout, Duration::from_secs(15));
+        assert!(cfg.unknown_keys().is_empty());
+        let lines = capture.0.lock().unwrap().clone();
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.contains("signer.poll_timeout_secs is a legacy name")),
+            "{lines:?}"
+        );
+
+        let cfg = FireblocksConfig::from_contents(
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nkey_path = \"k.pem\"\n",
+            FileFormat::Toml,
+        )?;
+        assert_eq!(cfg.secret_path, Some(PathBuf::from("k.pem")));
+
+        // both names in one file with different values
+        match FireblocksConfig::new("examples/legacy_conflict.toml", &[]) {
+            Err(Error::ConflictingKeys { alias, key }) => {
+                assert_eq!(alias, "base_url");
+                assert_eq!(key, "url");
+            }
+            other => panic!("Expected ConflictingKeys error, got {other:?}"),
+        }
+        let err = FireblocksConfig::from_contents(
+            "[signer]\npoll_timeout = 5\npoll_timeout_secs = 6\n",
+            FileFormat::Toml,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("signer.poll_timeout_secs and signer.poll_timeout"),
+            "{err}"
+        );
+        // the same value under both names is fine
+        let cfg = FireblocksConfig::from_contents(
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nurl = \"https://x.io\"\n\
+             base_url = \"https://x.io\"\n",
+            FileFormat::Toml,
+        )?;
+        assert_eq!(cfg.url, "https://x.io");
+
+        // aliases are resolved per file, a later canonical key overrides
+        let cfg = FireblocksConfig::new("examples/aliases.toml", &["examples/url_override.toml"])?;
+        assert_eq!(cfg.url, "https://sandbox-api.fireblocks.io/v1");
+        let cfg = FireblocksConfig::new("examples/v1.toml", &["examples/embedded.toml"])?;
+        assert_eq!(cfg.secret.as_deref(), Some("i am a secret"));
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?
+            .apply_override_str("baseUrl = \"https://x.io\"\n")?;
+        assert_eq!(cfg.url, "https://x.io");
+
+        // the version 2 migration conflicts like an alias
+        match FireblocksConfig::new("examples/legacy_secret_conflict.toml", &[]) {
+            Err(Error::ConflictingKeys { alias, key }) => {
+                assert_eq!(alias, "secret_key");
+                assert_eq!(key, "secret");
+            }
+            other => panic!("Expected ConflictingKeys error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_secret_url() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.secret = None;
+        let pem = std::fs::canonicalize("examples/test.pem")?;
+        let url = url::Url::from_file_path(&pem).map_err(|()| anyhow::anyhow!("not absolute"))?;
+        cfg.secret_path = Some(PathBuf::from(url.as_str()));
+        assert_eq!(cfg.expanded_secret_path()?, pem);
+        assert_eq!(cfg.get_key()?, std::fs::read(&pem)?);
+
+        cfg.secret_path = Some(PathBuf::from("s3://bucket/fireblocks.pem"));
+        match cfg.get_key() {
+            Err(Error::UnsupportedSecretScheme(scheme)) => assert_eq!(scheme, "s3"),
+            other => panic!("expected UnsupportedSecretScheme, got {other:?}"),
+        }
+
+        // Windows drives are paths, not schemes
+        cfg.secret_path = Some(PathBuf::from("C://keys/fireblocks.pem"));
+        assert!(matches!(cfg.get_key(), Err(Error::IOError { .. })));
+        Ok(())
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test_log::test]
+    fn test_secret_url_https() -> anyhow::Result<()> {
+        use std::{
+            io::{Read, Write},
+            net::TcpListener,
+            sync::Arc,
+        };
+
+        let _env = env_lock();
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        let tls = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![cert.cert.der().clone()],
+            rustls::pki_types::PrivateKeyDer::Pkcs8(cert.signing_key.serialize_der().into()),
+        )?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let key = std::fs::read("examples/test.pem")?;
+        let body = key.clone();
+        let server = std::thread::spawn(move || -> anyhow::Result<String> {
+            let (tcp, _) = listener.accept()?;
+            let mut stream =
+                rustls::StreamOwned::new(rustls::ServerConnection::new(Arc::new(tls))?, tcp);
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf)?;
+                anyhow::ensure!(n > 0, "connection closed");
+                request.extend_from_slice(&buf[..n]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )?;
+            stream.write_all(&body)?;
+            stream.conn.send_close_notify();
+            stream.flush()?;
+            Ok(String::from_utf8(request)?.to_lowercase())
+        });
+
+        unsafe {
+            std::env::set_var("FIREBLOCKS_TEST_SECRET_TOKEN", "s3cr3t");
+        }
+        let result = FireblocksConfig::from_contents(
+            &format!(
+                "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nsecret_path = \
+                 \"https://127.0.0.1:{port}/fireblocks.pem\"\nsecret_url_auth_env = \
+                 \"FIREBLOCKS_TEST_SECRET_TOKEN\"\n[signer]\nvault = \"0\"\n[http]\n\
+                 accept_invalid_certs = true\n"
+            ),
+            FileFormat::Toml,
+        )
+        .and_then(|cfg| cfg.get_key());
+        unsafe {
+            std::env::remove_var("FIREBLOCKS_TEST_SECRET_TOKEN");
+        }
+        assert_eq!(result?, key);
+        let request = server
+            .join()
+            .map_err(|_| anyhow::anyhow!("server panicked"))??;
+        assert!(request.starts_with("get /fireblocks.pem "), "{request}");
+        assert!(
+            request.contains("authorization: bearer s3cr3t"),
+            "{request}"
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_from_config() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let config = Config::builder()
+            .add_source(::config::File::from_str(
+                &std::fs::read_to_string("examples/default.toml")?,
+                FileFormat::Toml,
+            ))
+            .set_override("signer.vault", "7")?
+            .build()?;
+        let cfg = FireblocksConfig::from_config(config)?;
+        let expected = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert_eq!(cfg.api_key, expected.api_key);
+        assert_eq!(cfg.signer.vault, "7");
+        assert!(cfg.sources().is_empty());
+
+        let config = Config::builder()
+            .add_source(::config::File::from_str(
+                "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n[signer]\nvault = \
+                 \"0\"\npoll_interval = 0\n",
+                FileFormat::Toml,
+            ))
+            .build()?;
+        assert!(matches!(
+            FireblocksConfig::from_config(config),
+            Err(Error::InvalidDuration(_))
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_validate_all() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert!(cfg.validate_all().is_empty(), "{:?}", cfg.validate_all());
+
+        cfg.mainnet = true;
+        cfg.signer.vault = "main".to_string();
+        cfg.signer.poll_interval = Duration::ZERO;
+        cfg.secret_path = None;
+        cfg.secret = None;
+        cfg.network.rpc_url = Some("ftp://rpc.example.com".to_string());
+        cfg.network.ws_url = Some("https://rpc.example.com".to_string());
+        let errors = cfg.validate_all();
+        assert_eq!(errors.len(), 6, "{errors:?}");
+        assert!(matches!(errors[0], Error::EnvironmentMismatch {
+            mainnet: true,
+            ..
+        }));
+        assert!(matches!(errors[1], Error::InvalidDuration(_)));
+        assert!(matches!(errors[2], Error::InvalidVault { .. }));
+        assert!(matches!(errors[3], Error::MissingSecret));
+        assert!(matches!(&errors[4], Error::InvalidUrl { key, .. } if key == "network.rpc_url"));
+        assert!(matches!(&errors[5], Error::InvalidUrl { key, .. } if key == "network.ws_url"));
+
+        cfg.url = "sandbox".to_string();
+        cfg.secret_path = Some(PathBuf::from("examples/missing.pem"));
+        let errors = cfg.validate_all();
+        assert!(matches!(&errors[0], Error::InvalidUrl { key, .. } if key == "url"));
+        assert!(errors.iter().any(|e| matches!(e, Error::IOError { .. })));
+        Ok(())
+    }
+
+    /// Records spans and events as `name field=value ...` lines
+    #[derive(Clone, Default)]
+    struct CaptureLayer(std::sync::Arc<Mutex<Vec<String>>>);
+
+    struct CaptureVisitor(String);
+
+    impl tracing::field::Visit for CaptureVisitor {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+    }
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for CaptureLayer {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _: &tracing::span::Id,
+            _: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut visitor = CaptureVisitor(format!("span {}", attrs.metadata().name()));
+            attrs.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+
+        fn on_record(
+            &self,
+            _: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut visitor = CaptureVisitor("record".to_string());
+            values.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut visitor = CaptureVisitor("event".to_string());
+            event.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+    }
+
+    #[test_log::test]
+    fn test_load_tracing() -> anyhow::Result<()> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _env = env_lock();
+        let secret = "super-secret-key-material";
+        let capture = CaptureLayer::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let result = tracing::subscriber::with_default(subscriber, || {
+            FireblocksConfig::new_with_env(
+                "examples/default.toml",
+                &["examples/override.toml"],
+                vars(&[("FIREBLOCKS_SECRET", secret)]),
+            )
+        });
+        assert_eq!(result?.secret.as_deref(), Some(secret));
+
+        let lines = capture.0.lock().unwrap().clone();
+        let has = |needle: &str| lines.iter().any(|line| line.contains(needle));
+        assert!(
+            has("span fireblocks_config.load base=examples/default.toml overrides=1"),
+            "{lines:#?}"
+        );
+        assert!(has("record env=true"), "{lines:#?}");
+        let base = std::path::absolute("examples/default.toml")?;
+        assert!(
+            has(&format!("kind=base path={}", base.display())),
+            "{lines:#?}"
+        );
+        assert!(has("kind=override path="), "{lines:#?}");
+        assert!(has("message=config loaded keys="), "{lines:#?}");
+        assert!(!has(secret), "{lines:#?}");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_new_warn_conflicts() -> anyhow::Result<()> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        let first = dir.path().join("first.toml");
+        let second = dir.path().join("second.toml");
+        std::fs::write(&first, "url = \"https://first.example.com/v1\"\n")?;
+        std::fs::write(
+            &second,
+            "url = \"https://second.example.com/v1\"\n[extra]\nonly_here = 1\n",
+        )?;
+        let base = PathBuf::from("examples/default.toml");
+
+        let capture = CaptureLayer::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let cfg = tracing::subscriber::with_default(subscriber, || {
+            FireblocksConfig::new_warn_conflicts(&base, &[&first, &second])
+        })?;
+        assert_eq!(cfg.url, "https://second.example.com/v1");
+
+        let lines = capture.0.lock().unwrap().clone();
+        let conflicts: Vec<&String> = lines
+            .iter()
+            .filter(|line| line.contains("config key set by more than one file"))
+            .collect();
+        assert!(
+            conflicts
+                .iter()
+                .any(|line| line
+                    .contains(&format!("key=\"url\" winner={} files=3", second.display()))),
+            "{lines:#?}"
+        );
+        assert!(
+            !conflicts.iter().any(|line| line.contains("only_here")),
+            "{lines:#?}"
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_init_from_config_env() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        let base = dir.path().join("app.toml");
+        std::fs::write(
+            &base,
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n[signer]\nvault = \"1\"\n",
+        )?;
+        std::fs::write(dir.path().join("production.toml"), "mainnet = true\n")?;
+        unsafe {
+            std::env::set_var(FireblocksConfig::CONFIG_ENV_VAR, &base);
+            std::env::set_var(FireblocksConfig::PROFILE_ENV_VAR, " production ,");
+        }
+        let result = FireblocksConfig::init_from_config_env();
+        unsafe {
+            std::env::remove_var(FireblocksConfig::PROFILE_ENV_VAR);
+        }
+        let without_profile = FireblocksConfig::init_from_config_env();
+        unsafe {
+            std::env::remove_var(FireblocksConfig::CONFIG_ENV_VAR);
+        }
+
+        let cfg = result?;
+        assert_eq!(cfg.signer.vault, "1");
+        assert!(cfg.mainnet);
+        assert_eq!(cfg.sources(), [
+            ConfigSource::File(base.clone()),
+            ConfigSource::File(dir.path().join("production.toml"))
+        ]);
+        // the loader variables are not read as config keys
+        assert!(cfg.unknown_keys().is_empty(), "{:?}", cfg.unknown_keys());
+        assert!(!without_profile?.mainnet);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_init_with_dir() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        let base = dir.path().join("default.toml");
+        std::fs::write(
+            &base,
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nsecret = \"x\"\n[signer]\nvault \
+             = \"1\"\npoll_timeout = 60\n",
+        )?;
+        std::fs::write(
+            dir.path().join("production.toml"),
+            "mainnet = true\n[signer]\nvault = \"2\"\n",
+        )?;
+
+        let cfg = FireblocksConfig::init_with_dir(dir.path(), &["production"])?;
+        assert!(cfg.mainnet);
+        assert_eq!(cfg.signer.vault, "2");
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(60));
+        assert_eq!(cfg.sources(), [
+            ConfigSource::File(base.clone()),
+            ConfigSource::File(dir.path().join("production.toml"))
+        ]);
+
+        let cfg = FireblocksConfig::init_with_dir::<&str>(dir.path(), &[])?;
+        assert!(!cfg.mainnet);
+        assert_eq!(cfg.signer.vault, "1");
+
+        assert!(matches!(
+            FireblocksConfig::init_with_dir(dir.path(), &["staging"]),
+            Err(Error::ProfileConfigNotFound(profile)) if profile == "staging.toml"
+        ));
+        let empty = tempfile::tempdir()?;
+        match FireblocksConfig::init_with_dir::<&str>(empty.path(), &[]) {
+            Err(Error::NoConfigFound { tried }) => {
+                assert_eq!(tried, [empty
+                    .path()
+                    .join("default.toml")
+                    .display()
+                    .to_string()])
+            }
+            other => panic!("Expected NoConfigFound error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_init_fallback() -> anyhow::Result<()> {
+        let xdg = tempfile::tempdir()?;
+        let other = tempfile::tempdir()?;
+        let system = other.path().join("system.toml");
+        let env_config = other.path().join("env.toml");
+        let find = |config_dir: Option<&Path>, env_config: Option<&Path>| {
+            FireblocksConfig::find_init_config(
+                config_dir.map(Path::to_path_buf),
+                env_config.map(Path::to_path_buf),
+                &system,
+            )
+        };
+
+        // nothing exists, every place tried is reported
+        match find(None, None) {
+            Err(Error::NoConfigFound { tried }) => assert_eq!(tried, vec![
+                "XDG config directory (unavailable)".to_string(),
+                "FIREBLOCKS_CONFIG (unavailable)".to_string(),
+                system.display().to_string(),
+            ]),
+            other => panic!("expected NoConfigFound, got {other:?}"),
+        }
+        let err = find(Some(xdg.path()), Some(&env_config))
+            .expect_err("no config exists")
+            .to_string();
+        assert!(err.contains(&xdg.path().join("fireblocks").display().to_string()));
+        assert!(err.contains(&env_config.display().to_string()));
+
+        std::fs::write(&system, "")?;
+        assert_eq!(find(None, Some(&env_config))?, system);
+        std::fs::write(&env_config, "")?;
+        assert_eq!(find(None, Some(&env_config))?, env_config);
+        let xdg_config = xdg.path().join("fireblocks").join("default.toml");
+        std::fs::create_dir(xdg.path().join("fireblocks"))?;
+        std::fs::write(&xdg_config, "")?;
+        assert_eq!(find(Some(xdg.path()), Some(&env_config))?, xdg_config);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_from_env() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let mut env = vars(&[
+            ("FIREBLOCKS_API_KEY", "6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50"),
+            ("FIREBLOCKS_URL", "https://env.fireblocks.io/v1"),
+            ("FIREBLOCKS_SIGNER__VAULT", "12"),
+            ("FIREBLOCKS_SIGNER__POLL_TIMEOUT", "60"),
+        ]);
+        let result = FireblocksConfig::from_env_vars(env.clone());
+        env.retain(|(key, _)| key != "FIREBLOCKS_SIGNER__VAULT");
+        let missing_vault = FireblocksConfig::from_env_vars(env);
+
+        let cfg = result?;
+        assert_eq!("6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50", cfg.api_key);
+        assert_eq!(cfg.url, "https://env.fireblocks.io/v1");
+        assert_eq!(cfg.signer.vault, "12");
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(60));
+        assert_eq!(cfg.signer.poll_interval, Duration::from_secs(5));
+        assert_eq!(cfg.retry, RetryConfig::default());
+        assert_eq!(cfg.sources(), [ConfigSource::Environment]);
+
+        // a missing vault loads empty and is left to validate_all
+        let missing_vault = missing_vault?;
+        assert!(missing_vault.signer.vault.is_empty());
+        assert!(
+            missing_vault
+                .validate_all()
+                .iter()
+                .any(|e| matches!(e, Error::InvalidVault { .. }))
+        );
+        // the process environment sets no api_key
+        assert!(FireblocksConfig::from_env().is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_output_format_from_extension() {
+        assert_eq!(
+            OutputFormat::from_extension("json"),
+            Some(OutputFormat::Json)
+        );
+        assert_eq!(
+            OutputFormat::from_extension(".JSON"),
+            Some(OutputFormat::Json)
+        );
+        assert_eq!(OutputFormat::from_extension("tsv"), Some(OutputFormat::Tsv));
+        assert_eq!(
+            OutputFormat::from_extension("txt"),
+            Some(OutputFormat::Table)
+        );
+        // no csv or yaml rendering to map to
+        assert_eq!(OutputFormat::from_extension("csv"), None);
+        assert_eq!(OutputFormat::from_extension("yaml"), None);
+        assert_eq!(OutputFormat::from_extension("xml"), None);
+        assert_eq!(OutputFormat::from_extension(""), None);
+    }
+
+    #[test_log::test]
+    fn test_output_format_content_type() {
+        assert_eq!(OutputFormat::Table.content_type(), "text/plain");
+        assert_eq!(
+            OutputFormat::Tsv.content_type(),
+            "text/tab-separated-values"
+        );
+        assert_eq!(OutputFormat::Json.content_type(), "application/json");
+    }
+
+    #[test_log::test]
+    fn test_to_display_string() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        let expected = [
+            "allow_nonstandard_api_key    true",
+            "api_key                      blah",
+            "asset_overrides              {}",
+            "asset_test_suffix            _TEST",
+            "assets_allowed               []",
+            "debug                        false",
+            "display.output               Table",
+            "extra.fail_fast              false",
+            "extra.rpc_url                https://rpc.com",
+            "extra.timeout                40",
+            "http.accept_invalid_certs    false",
+            "http.connect_timeout         10s",
+            "http.max_concurrency         8",
+            "http.request_timeout         30s",
+            "mainnet                      false",
+            "network.rpc_url              https://rpc.com",
+            "paging.order                 desc",
+            "paging.page_size             200",
+            "require_https                true",
+            "retry.backoff_multiplier     2.0",
+            "retry.initial_backoff        1s",
+            "retry.max_backoff            30s",
+            "retry.max_retries            5",
+            "retry.retry_on_rate_limit    true",
+            "secret_path                  examples/test.pem",
+            "signer.broadcast             false",
+            "signer.fail_on_fee_exceeded  true",
+            "signer.poll_backoff          1.0",
+            "signer.poll_interval         5s",
+            "signer.poll_timeout          2m",
+            "signer.sign_only             false",
+            "signer.vault                 0",
+            "treat_unknown_as_error       false",
+            "url                          https://sandbox-api.fireblocks.io/v1",
+            "version                      2",
+        ]
+        .map(|line| format!("{line}\n"))
+        .concat();
+        assert_eq!(cfg.to_display_string(OutputFormat::Table)?, expected);
+
+        let tsv = cfg.to_display_string(OutputFormat::Tsv)?;
+        assert!(tsv.contains("signer.poll_timeout\t2m\n"));
+        assert!(tsv.contains("extra.rpc_url\thttps://rpc.com\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&cfg.to_display_string(OutputFormat::Json)?)?;
+        assert_eq!(json["signer"]["poll_timeout"], "2m");
+        assert_eq!(json["extra"]["timeout"], 40);
+
+        let mut cfg = FireblocksConfig::new("examples/schema_full.toml", &[])?;
+        cfg.secret = Some("super secret key material".to_string());
+        cfg.api_key = ApiKey::new("6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50");
+        for format in [OutputFormat::Table, OutputFormat::Tsv, OutputFormat::Json] {
+            let out = cfg.to_display_string(format)?;
+            assert!(!out.contains("super secret"), "{format:?}");
+            assert!(!out.contains("01aa"), "{format:?}");
+            assert!(!out.contains(cfg.api_key.expose()), "{format:?}");
+            assert!(out.contains("<redacted>"), "{format:?}");
+        }
+        let json = cfg.to_display_string(OutputFormat::Json)?;
+        assert_eq!(json, cfg.to_display_string(OutputFormat::Json)?);
+        let keys: Vec<String> = serde_json::from_str::<serde_json::Value>(&json)?
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert_eq!(keys, sorted_keys);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_network() -> anyhow::Result<()> {
+        let _env = env_lock();
+        // explicit section wins, unset fields come from legacy extra keys
+        let cfg = FireblocksConfig::new("examples/network.toml", &[])?;
+        assert_eq!(cfg.network(), &NetworkConfig {
+            rpc_url: Some("https://api.mainnet-beta.solana.com".to_string()),
+            ws_url: Some("wss://legacy.example.com".to_string()),
+            commitment: Some("finalized".to_string()),
+            chain_id: Some(137),
+            explorer_url: None,
+        });
+
+        // legacy keys alone
+        let cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert_eq!(cfg.network().rpc_url.as_deref(), Some("https://rpc.com"));
+        assert_eq!(cfg.network().ws_url, None);
+
+        let result = FireblocksConfig::new_with_kv("examples/network.toml", &[], &[
+            "network.ws_url=https://not-a-websocket.example.com",
+        ]);
+        match result {
+            Err(Error::InvalidUrl { key, .. }) => assert_eq!(key, "network.ws_url"),
+            other => panic!("expected InvalidUrl, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_get_key_from() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new("examples/default.toml", &["examples/embedded.toml"])?;
+        assert_eq!(cfg.get_key_from(SecretSource::Embedded)?, b"i am a secret");
+        assert_eq!(
+            cfg.get_key_from(SecretSource::Path)?,
+            std::fs::read("examples/test.pem")?
+        );
+        assert!(matches!(
+            cfg.get_key_from(SecretSource::Env),
+            Err(Error::MissingSecret)
+        ));
+
+        let env = vars(&[(SecretSource::ENV_VAR, "from the env")]);
+        let cfg = FireblocksConfig::new_with_env("examples/default.toml", &[], env)?;
+        assert_eq!(cfg.get_key_from(SecretSource::Env)?, b"from the env");
+        let cfg = FireblocksConfig::new_no_env("examples/default.toml", &[])?;
+        assert!(matches!(
+            cfg.get_key_from(SecretSource::Env),
+            Err(Error::MissingSecret)
+        ));
+
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert!(matches!(
+            cfg.get_key_from(SecretSource::Embedded),
+            Err(Error::MissingSecret)
+        ));
+        cfg.secret_path = None;
+        assert!(matches!(
+            cfg.get_key_from(SecretSource::Path),
+            Err(Error::MissingSecret)
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_resolve_key() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &["examples/embedded.toml"])?;
+        assert_eq!(
+            cfg.resolve_key()?,
+            (b"i am a secret".to_vec(), SecretSource::Embedded)
+        );
+
+        let cfg = FireblocksConfig::new(b, &[])?;
+        let (key, source) = cfg.resolve_key()?;
+        assert_eq!(source, SecretSource::Path);
+        assert_eq!(key, std::fs::read("examples/test.pem")?);
+        assert_eq!(cfg.get_key()?, key);
+
+        let cfg = FireblocksConfig::new_with_env(b, &[], vars(&[("FIREBLOCKS_SECRET", "env")]))?;
+        assert_eq!(cfg.resolve_key()?, (b"env".to_vec(), SecretSource::Env));
+        // the source is recorded at load time, not guessed from the value
+        let layered = cfg.apply_override_str("[signer]\nvault = \"3\"")?;
+        assert_eq!(layered.resolve_key()?.1, SecretSource::Env);
+        let layered = cfg.apply_override_str("secret = \"env\"")?;
+        assert_eq!(layered.resolve_key()?.1, SecretSource::Embedded);
+
+        let mut cfg = FireblocksConfig::new(b, &[])?;
+        cfg.secret_path = None;
+        assert!(matches!(cfg.resolve_key(), Err(Error::MissingSecret)));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test_log::test]
+    fn test_secret_command() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.secret_command = Some("echo -n mysecret".to_string());
+        // secret_command takes precedence over secret_path
+        assert_eq!(cfg.get_key()?, b"mysecret");
+        assert_eq!(cfg.get_key_from(SecretSource::Command)?, b"mysecret");
+
+        cfg.secret = Some("embedded".to_string());
+        assert_eq!(cfg.get_key()?, b"embedded");
+
+        cfg.secret = None;
+        cfg.secret_command = Some("exit 3".to_string());
+        match cfg.get_key() {
+            Err(Error::SecretCommand { status }) => assert_eq!(status.code(), Some(3)),
+            other => panic!("expected SecretCommand, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_new_with_env_concurrent() -> anyhow::Result<()> {
+        // no lock, the loads never read the process environment
+        let load = |vault: &str| {
+            let env = vars(&[
+                ("FIREBLOCKS_SIGNER__VAULT", vault),
+                ("FIREBLOCKS_CONFIG", "ignored.toml"),
+            ]);
+            FireblocksConfig::new_with_env("examples/default.toml", &[], env)
+        };
+        let (a, b) = std::thread::scope(|scope| {
+            let a = scope.spawn(|| load("1"));
+            let b = scope.spawn(|| load("2"));
+            (a.join().unwrap(), b.join().unwrap())
+        });
+        let (a, b) = (a?, b?);
+        assert_eq!(a.signer.vault, "1");
+        assert_eq!(b.signer.vault, "2");
+        assert!(!a.unknown_keys().contains(&"config".to_string()));
+        assert_eq!(a.sources().last(), Some(&ConfigSource::Environment));
+        // reload reuses the synthetic environment
+        assert_eq!(b.reload()?.signer.vault, "2");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_new_no_env() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let env = vars(&[("FIREBLOCKS_API_KEY", "from-env")]);
+        let with_env = FireblocksConfig::new_with_env(b, &[], env.clone())?;
+        let no_env = FireblocksConfig::new_no_env(b, &[])?;
+        let reloaded = no_env.reload()?;
+
+        assert_eq!("from-env", with_env.api_key);
+        assert_eq!("from-env", with_env.reload()?.api_key);
+        assert_eq!("from-env", no_env.reload_with_env(env)?.api_key);
+        assert_eq!("blah", no_env.api_key);
+        assert!(!no_env.sources().contains(&ConfigSource::Environment));
+        assert_eq!("blah", reloaded.api_key);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_new_with_defaults() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let defaults = Defaults {
+            url: Some("https://defaults.example.com/v1".to_string()),
+            poll_timeout: Some(Duration::from_secs(600)),
+            poll_interval: Some(Duration::from_millis(1500)),
+            output: Some(OutputFormat::Json),
+        };
+
+        // nothing set by the file, every default applies
+        let cfg =
+            FireblocksConfig::new_with_defaults("examples/no_url.toml", &[], defaults.clone())?;
+        assert_eq!(cfg.url, "https://defaults.example.com/v1");
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(600));
+        assert_eq!(cfg.signer.poll_interval, Duration::from_millis(1500));
+        assert_eq!(cfg.display_config.output, OutputFormat::Json);
+
+        // the file and the environment win over the defaults
+        let cfg = FireblocksConfig::new_with_defaults("examples/default.toml", &[], defaults)?
+            .reload_with_env(vars(&[("FIREBLOCKS_SIGNER__POLL_INTERVAL", "7")]))?;
+        assert_eq!(cfg.url, "https://sandbox-api.fireblocks.io/v1");
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(120));
+        assert_eq!(cfg.signer.poll_interval, Duration::from_secs(7));
+        assert_eq!(cfg.display_config.output, OutputFormat::Table);
+
+        // unset fields keep the built-in defaults
+        let cfg =
+            FireblocksConfig::new_with_defaults("examples/no_url.toml", &[], Defaults::default())?;
+        assert_eq!(cfg.signer.poll_timeout, Signer::DEFAULT_POLL_TIMEOUT);
+        assert_eq!(cfg.signer.poll_interval, Signer::DEFAULT_POLL_INTERVAL);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_schema() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let schema = FireblocksConfig::schema();
+        let keys: std::collections::BTreeSet<&str> = schema.iter().map(|f| f.key).collect();
+        assert_eq!(keys.len(), schema.len());
+
+        // a config setting every listed key loads, and sets nothing else
+        let cfg = FireblocksConfig::new("examples/schema_full.toml", &[])?;
+        let mut loaded: std::collections::BTreeSet<String> = cfg
+            .flattened()?
+            .into_keys()
+            .map(|k| {
+                // map fields flatten to one key per entry
+                match ["extra", "asset_overrides"]
+                    .into_iter()
+                    .find(|map| k.starts_with(&format!("{map}.")))
+                {
+                    Some(map) => map.to_string(),
+                    None => k,
+                }
+            })
+            .collect();
+        loaded.insert("secret".to_string());
+        loaded.insert("secret_shares".to_string());
+        assert_eq!(loaded, keys.iter().map(|k| k.to_string()).collect());
+
+        // listed defaults match what a minimal config loads
+        let minimal = FireblocksConfig::from_contents(
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n[signer]\nvault = \"0\"\n",
+            FileFormat::Toml,
+        )?
+        .flattened()?;
+        for field in &schema {
+            if let (Some(default), Some(value)) = (field.default, minimal.get(field.key)) {
+                let value = value
+                    .as_str()
+                    .map_or_else(|| value.to_string(), str::to_string);
+                assert_eq!(default, value, "default of {}", field.key);
+            }
+        }
+
+        let poll_timeout = schema
+            .iter()
+            .find(|f| f.key == "signer.poll_timeout")
+            .unwrap();
+        assert_eq!(poll_timeout.type_name, "Duration");
+        assert!(poll_timeout.optional);
+        assert_eq!(poll_timeout.default, Some("180"));
+        Ok(())
+    }
+
+    #[cfg(feature = "schema")]
+    #[test_log::test]
+    fn test_schema_json() {
+        let schema = FireblocksConfig::schema_json();
+        let properties = schema["properties"].as_object().unwrap();
+        for field in FireblocksConfig::schema() {
+            let root = field.key.split('.').next().unwrap();
+            assert!(
+                properties.contains_key(root),
+                "{root} missing from JSON schema"
+            );
+        }
+        assert_eq!(
+            properties["jwt_ttl"]["type"],
+            serde_json::json!(["number", "string", "null"])
+        );
+    }
+
+    #[cfg(feature = "schema")]
+    #[test_log::test]
+    fn test_json_schema() -> anyhow::Result<()> {
+        let schema: serde_json::Value = serde_json::from_str(&FireblocksConfig::json_schema())?;
+        let properties = &schema["properties"];
+        assert!(properties["api_key"].is_object());
+        assert!(properties["signer"].is_object());
+        assert_eq!(properties["extra"]["type"], "object");
+
+        let signer = schema["$defs"]["Signer"]["properties"]
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("Signer missing from $defs"))?;
+        assert_eq!(
+            signer["poll_timeout"]["type"],
+            serde_json::json!(["number", "string"])
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_with_overrides_mixed() -> anyhow::Result<()> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let owned = "examples/override.toml".to_string();
+        let mixed: [&dyn AsRef<Path>; 2] = [&owned, &"examples/retry.toml"];
+        let cfg = FireblocksConfig::with_overrides(b, mixed)?;
+        assert_eq!(
+            cfg,
+            FireblocksConfig::new(b, &[owned.as_str(), "examples/retry.toml"])?
+        );
+        assert_eq!("production", cfg.api_key);
+        assert_eq!(cfg.retry.max_retries, 3);
+
+        // the base and overrides may differ in type
+        let cfg = FireblocksConfig::with_overrides(PathBuf::from(b), vec![owned.clone()])?;
+        assert_eq!("production", cfg.api_key);
+        let paths = [PathBuf::from(&owned), PathBuf::from("examples/retry.toml")];
+        let cfg = FireblocksConfig::with_overrides(b, &paths)?;
+        assert_eq!(cfg, FireblocksConfig::new(PathBuf::from(b), &paths)?);
+
+        let capture = CaptureLayer::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let cfg = tracing::subscriber::with_default(subscriber, || {
+            FireblocksConfig::with_overrides(b, Vec::<String>::new())
+        })?;
+        assert_eq!(cfg, FireblocksConfig::new(b, &[])?);
+        let lines = capture.0.lock().unwrap().clone();
+        assert!(
+            lines.iter().any(
+                |line| line.contains("no overrides given, loading examples/default.toml alone")
+            ),
+            "{lines:#?}"
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_config_override() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg_override = "examples/override.toml";
+        let cfg = FireblocksConfig::with_overrides(b, vec![cfg_override])?;
+        assert_eq!("production", cfg.api_key);
+        assert!(cfg.secret_path.is_some());
+        if let Some(p) = cfg.secret_path.as_ref() {
+            assert_eq!(PathBuf::from("examples/test.pem"), *p);
+        }
+        assert_eq!("https://api.fireblocks.io/v1", cfg.url);
+        assert_eq!(OutputFormat::Table, cfg.display_config.output);
+        assert!(cfg.debug);
+        assert!(cfg.mainnet);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_extends_chain() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let cfg = FireblocksConfig::new("examples/extends/customer.toml", &[])?;
+        assert_eq!("customer", cfg.api_key);
+        assert_eq!("https://sandbox-api.fireblocks.io/v1", cfg.url);
+        assert_eq!(cfg.signer.vault, "12");
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(60));
+        assert_eq!(cfg.signer.poll_interval, Duration::from_secs(2));
+        assert_eq!(cfg.get_extra::<String, _>("rpc_url")?, "https://rpc.com");
+        assert_eq!(cfg.get_extra::<i64, _>("timeout")?, 40);
+        // secret_path is relative to root.toml, which declared it
+        assert_eq!(
+            Some(PathBuf::from("examples/extends/keys/root.pem")),
+            cfg.secret_path
+        );
+
+        // explicit overrides still apply on top of the chain
+        let cfg =
+            FireblocksConfig::new(
+                "examples/extends/customer.toml",
+                &["examples/override.toml"],
+            )?;
+        assert_eq!("production", cfg.api_key);
+        assert_eq!(cfg.signer.vault, "12");
+
+        // URLs are not rebased onto the declaring file's directory
+        let dir = tempfile::tempdir()?;
+        let key = dir.path().join("key.pem");
+        std::fs::write(&key, "url key")?;
+        let key_url = url::Url::from_file_path(&key).expect("absolute path");
+        std::fs::write(
+            dir.path().join("root.toml"),
+            format!(
+                "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nsecret_path = \"{key_url}\"\n"
+            ),
+        )?;
+        let child = dir.path().join("child.toml");
+        std::fs::write(&child, "extends = \"root.toml\"\n[signer]\nvault = \"3\"\n")?;
+        let cfg = FireblocksConfig::new(&child, &[])?;
+        assert_eq!(cfg.secret_path, Some(PathBuf::from(key_url.as_str())));
+        assert_eq!(cfg.get_key()?, b"url key");
+        std::fs::write(
+            &child,
+            "extends = \"root.toml\"\nsecret_path = \"https://host/key\"\n",
+        )?;
+        let cfg = FireblocksConfig::new(&child, &[])?;
+        assert_eq!(cfg.secret_path, Some(PathBuf::from("https://host/key")));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_extends_errors() {
+        match FireblocksConfig::new("examples/extends/cycle_a.toml", &[]) {
+            Err(Error::ExtendsCycle { chain }) => {
+                assert_eq!(chain.len(), 3);
+                assert!(chain[0].ends_with("cycle_a.toml"));
+                assert!(chain[1].ends_with("cycle_b.toml"));
+                assert!(chain[2].ends_with("cycle_a.toml"));
+            }
+            other => panic!("Expected ExtendsCycle error, got {other:?}"),
+        }
+
+        match FireblocksConfig::new("examples/extends/orphan.toml", &[]) {
+            Err(Error::IOError { path, .. }) => assert!(path.ends_with("missing.toml")),
+            other => panic!("Expected IOError, got {other:?}"),
+        }
+    }
+
+    #[test_log::test]
+    fn test_fingerprint() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        let other = FireblocksConfig::new(b, &[])?;
+        let fingerprint = cfg.fingerprint();
+        assert_eq!(fingerprint.len(), 64);
+        assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(fingerprint, other.fingerprint());
+        let redacted = cfg.fingerprint_redacted();
+        assert_eq!(redacted, other.fingerprint_redacted());
+        assert_ne!(redacted, fingerprint);
+
+        // inline secrets only change the full fingerprint, other values both
+        let mut with_secret = cfg.clone();
+        with_secret.secret = Some("inline".to_string());
+        assert_eq!(with_secret.fingerprint_redacted(), redacted);
+        assert_ne!(with_secret.fingerprint(), fingerprint);
+        let changed = FireblocksConfig::new(b, &["examples/override.toml"])?;
+        assert_ne!(changed.fingerprint(), fingerprint);
+        assert_ne!(changed.fingerprint_redacted(), redacted);
+
+        // rotating the key file on disk
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nsecret_path = \"key.pem\"\n",
+        )?;
+        std::fs::write(dir.path().join("key.pem"), "first key")?;
+        let cfg = FireblocksConfig::new(&path, &[])?;
+        let (before, before_redacted) = (cfg.fingerprint(), cfg.fingerprint_redacted());
+        std::fs::write(dir.path().join("key.pem"), "second key")?;
+        // the key is read when fingerprinting, not at load
+        assert_ne!(cfg.fingerprint(), before);
+        let after = FireblocksConfig::new(&path, &[])?;
+        assert_ne!(after.fingerprint(), before);
+        assert_eq!(after.fingerprint_redacted(), before_redacted);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_load_error_sources() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let broken = "examples/broken_override.toml";
+        match FireblocksConfig::new_with_env(b, &["examples/retry.toml", broken], vars(&[])) {
+            Err(e @ Error::Load { .. }) => {
+                let msg = e.to_string();
+                assert!(
+                    msg.contains(&format!("from {b}, examples/retry.toml, {broken}:")),
+                    "{msg}"
+                );
+                assert!(!msg.contains("environment"), "{msg}");
+                assert_eq!(e.code(), "CONFIG_LOAD");
+            }
+            other => panic!("Expected Load error, got {other:?}"),
+        }
+
+        // the environment layer is listed when it applied
+        let err = FireblocksConfig::new_with_env(
+            b,
+            &[],
+            vars(&[("FIREBLOCKS_SIGNER__BROADCAST", "maybe")]),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, Error::Load { ref sources, .. }
+                if sources == &[b.to_string(), "environment".to_string()]),
+            "{err:?}"
+        );
+
+        let err =
+            FireblocksConfig::from_contents("[signer]\nbroadcast = \"maybe\"\n", FileFormat::Toml)
+                .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("failed to load config from memory"),
+            "{err}"
+        );
+
+        let cfg = FireblocksConfig::new(b, &[])?;
+        match cfg.get_extra::<u64, _>("rpc_url") {
+            Err(Error::ExtraDeserialize { key, source, .. }) => {
+                assert_eq!(key, "rpc_url");
+                assert!(source.to_string().contains("invalid type"), "{source}");
+            }
+            other => panic!("Expected ExtraDeserialize error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_error_source_chain() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let mut cfg = FireblocksConfig::new(b, &[])?;
+        cfg.secret = None;
+        cfg.secret_path = Some(PathBuf::from("examples/missing.pem"));
+        let err = cfg.get_key().unwrap_err();
+        assert!(matches!(err, Error::IOError { .. }), "{err:?}");
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(err.io_kind(), Some(std::io::ErrorKind::NotFound));
+
+        // parse failures keep the parser's error as their source
+        let parse_errors = [
+            FireblocksConfig::new("examples/invalid_syntax.toml", &[]).unwrap_err(),
+            FireblocksConfig::new_with_env(b, &["examples/broken_override.toml"], vars(&[]))
+                .unwrap_err(),
+            cfg.get_extra::<u64, _>("rpc_url").unwrap_err(),
+        ];
+        for err in &parse_errors {
+            assert!(std::error::Error::source(err).is_some(), "{err:?}");
+            assert_eq!(err.io_kind(), None, "{err:?}");
+        }
+
+        let err = Error::Workspace {
+            workspace: "ops".to_string(),
+            source: Box::new(Error::IOError {
+                source: std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+                path: "ops.pem".to_string(),
+                backtrace: None,
+            }),
+        };
+        assert_eq!(err.io_kind(), Some(std::io::ErrorKind::PermissionDenied));
+        assert!(err.backtrace().is_none());
+        assert_eq!(Error::MissingSecret.io_kind(), None);
+
+        #[cfg(feature = "backtrace")]
+        {
+            let captured = std::backtrace::Backtrace::capture().status()
+                == std::backtrace::BacktraceStatus::Captured;
+            assert_eq!(cfg.get_key().unwrap_err().backtrace().is_some(), captured);
+        }
+        #[cfg(not(feature = "backtrace"))]
+        assert!(cfg.get_key().unwrap_err().backtrace().is_none());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_parse_error_location() {
+        let bad = "examples/invalid_syntax.toml";
+        for result in [
+            FireblocksConfig::new(bad, &[]),
+            FireblocksConfig::new("examples/default.toml", &[bad]),
+        ] {
+            match result {
+                Err(e @ Error::ConfigParseAt { .. }) => {
+                    let msg = e.to_string();
+                    assert!(msg.contains(bad), "{msg}");
+                    assert!(msg.contains("line 4, column 8"), "{msg}");
+                }
+                other => panic!("Expected ConfigParseAt error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test_log::test]
+    fn test_from_reader() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let toml: &[u8] =
+            b"api_key = \"reader\"\nallow_nonstandard_api_key = true\n\n[signer]\nvault = \"3\"\n";
+        let cfg = FireblocksConfig::from_reader(std::io::Cursor::new(toml), FileFormat::Toml)?;
+        assert_eq!(cfg.api_key, "reader");
+        assert_eq!(cfg.signer.vault, "3");
+        assert_eq!(cfg.resolved_url(), FireblocksConfig::SANDBOX_URL);
+        assert!(matches!(cfg.reload(), Err(Error::NotReloadable)));
+
+        let json =
+            r#"{"api_key": "json", "allow_nonstandard_api_key": true, "signer": {"vault": "4"}}"#;
+        let cfg = FireblocksConfig::from_contents(json, FileFormat::Json)?;
+        assert_eq!(cfg.signer.vault, "4");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_from_dir() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        std::fs::copy("examples/default.toml", dir.path().join("default.toml"))?;
+        std::fs::write(
+            dir.path().join("10-override.toml"),
+            "api_key = \"fragment\"\n[signer]\nvault = \"10\"\n",
+        )?;
+        std::fs::write(
+            dir.path().join("20-override.toml"),
+            "[signer]\nvault = \"20\"\n",
+        )?;
+        std::fs::write(dir.path().join("notes.txt"), "not a config")?;
+
+        let cfg = FireblocksConfig::from_dir(dir.path())?;
+        assert_eq!(cfg.api_key, "fragment");
+        assert_eq!(cfg.signer.vault, "20");
+        assert_eq!(cfg.url, "https://sandbox-api.fireblocks.io/v1");
+        assert_eq!(cfg.reload()?, cfg);
+
+        let empty = tempfile::tempdir()?;
+        assert!(matches!(
+            FireblocksConfig::from_dir(empty.path()),
+            Err(Error::ConfigNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_require_https() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let load = |url: &str, require_https: Option<bool>| {
+            let mut contents =
+                format!("api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\nurl = \"{url}\"\n");
+            if let Some(require_https) = require_https {
+                contents.push_str(&format!("require_https = {require_https}\n"));
+            }
+            contents.push_str("[signer]\nvault = \"0\"\n");
+            FireblocksConfig::from_contents(&contents, FileFormat::Toml)
+        };
+
+        let cfg = load("https://api.fireblocks.io/v1", None)?;
+        assert!(cfg.require_https);
+        assert!(FireblocksConfig::default().require_https);
+
+        match load("http://api.fireblocks.io/v1", None) {
+            Err(Error::InsecureUrl { key, url }) => {
+                assert_eq!(key, "url");
+                assert_eq!(url, "http://api.fireblocks.io/v1");
+            }
+            other => panic!("Expected InsecureUrl error, got {other:?}"),
+        }
+
+        for local in [
+            "http://localhost:8080/v1",
+            "http://127.0.0.1:8080/v1",
+            "http://[::1]:8080/v1",
+        ] {
+            load(local, None)?;
+        }
+        let cfg = load("http://fireblocks.internal/v1", Some(false))?;
+        assert!(!cfg.require_https);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_callback_config() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert!(cfg.callback().is_none());
+
+        let cfg = FireblocksConfig::new(b, &["examples/callback.toml"])?;
+        let callback = cfg.callback().expect("[callback] is set");
+        assert_eq!(callback.listen.as_deref(), Some("127.0.0.1:8443"));
+        assert_eq!(callback.allowed_ips, vec![
+            "10.0.0.0/8",
+            "192.168.1.20",
+            "fd00::/8"
+        ]);
+        assert_eq!(
+            callback.public_key()?,
+            std::fs::read("examples/test.pub.pem")?
+        );
+
+        assert!(matches!(
+            CallbackConfig::default().public_key(),
+            Err(Error::NotPresent { .. })
+        ));
+        let missing = CallbackConfig {
+            public_key_path: Some(PathBuf::from("examples/missing.pub.pem")),
+            ..Default::default()
+        };
+        assert!(matches!(missing.public_key(), Err(Error::IOError { .. })));
+
+        for listen in ["0.0.0.0:8443", "[::1]:443", "callbacks.internal:80"] {
+            let callback = CallbackConfig {
+                listen: Some(listen.to_string()),
+                ..Default::default()
+            };
+            callback.validate()?;
+        }
+        for listen in ["8443", ":8443", "host:", "host:99999", "::1:443"] {
+            match FireblocksConfig::new_with_kv(b, &[], &[format!("callback.listen={listen}")]) {
+                Err(Error::InvalidValue { key, value, .. }) => {
+                    assert_eq!(key, "callback.listen");
+                    assert_eq!(value, listen);
+                }
+                other => panic!("Expected InvalidValue error, got {other:?}"),
+            }
+        }
+        let callback = CallbackConfig {
+            allowed_ips: vec!["10.0.0.0/33".to_string(), "example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(callback.validate().is_err());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_masked_api_key() -> anyhow::Result<()> {
+        let mut cfg = FireblocksConfig::default();
+        cfg.api_key = ApiKey::new("6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50");
+        let masked = cfg.masked_api_key();
+        assert_eq!(masked, "6f0c****************************4f50");
+        assert_eq!(masked.len(), cfg.api_key.expose().len());
+
+        // a partially revealed key keeps at least half of it hidden
+        cfg.api_key = ApiKey::new("0123456789abcdef");
+        assert_eq!(cfg.masked_api_key(), "0123********cdef");
+        cfg.api_key = ApiKey::new("0123456789abcde");
+        assert_eq!(cfg.masked_api_key(), "***************");
+
+        cfg.api_key = ApiKey::new("");
+        assert_eq!(cfg.masked_api_key(), "");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_webhook_key() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let pub_pem = std::fs::read("examples/test.pub.pem")?;
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        assert!(matches!(
+            cfg.get_webhook_key(),
+            Err(Error::NotPresent { .. })
+        ));
+
+        // the builtin key is chosen by mainnet
+        cfg.webhook_public_key = Some(FireblocksConfig::WEBHOOK_BUILTIN.to_string());
+        for mainnet in [false, true] {
+            cfg.mainnet = mainnet;
+            match cfg.get_webhook_key() {
+                Err(Error::BuiltinWebhookKeyUnavailable { mainnet: m }) => assert_eq!(m, mainnet),
+                other => panic!("expected BuiltinWebhookKeyUnavailable, got {other:?}"),
+            }
+        }
+
+        // a path wins over the builtin key, relative to the config directory
+        cfg.webhook_public_key_path = Some(PathBuf::from("test.pub.pem"));
+        assert_eq!(cfg.get_webhook_key()?, pub_pem);
+        cfg.webhook_public_key = None;
+        assert_eq!(cfg.get_webhook_key()?, pub_pem);
+        if let Some(home) = dirs::home_dir() {
+            cfg.webhook_public_key_path = Some(PathBuf::from("~/missing-webhook.pem"));
+            match cfg.get_webhook_key() {
+                Err(Error::IOError { path, .. }) => {
+                    assert_eq!(Path::new(&path), home.join("missing-webhook.pem"))
+                }
+                other => panic!("expected IOError, got {other:?}"),
+            }
+        }
+
+        // an inline key wins over everything
+        cfg.webhook_public_key = Some("-----BEGIN PUBLIC KEY-----".to_string());
+        assert_eq!(cfg.get_webhook_key()?, b"-----BEGIN PUBLIC KEY-----");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_extra_merges_per_key() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        let base = dir.path().join("default.toml");
+        std::fs::write(
+            &base,
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n[signer]\nvault = \
+             \"0\"\n[extra]\nregion = \"eu\"\ntimeout = 30\nlimits = { daily = 10, weekly = 50 \
+             }\nhosts = [\"a\", \"b\"]\n[profiles.prod.extra]\nfail_fast = true\n",
+        )?;
+        let profile = dir.path().join("prod.toml");
+        std::fs::write(
+            &profile,
+            "[extra]\nfail_fast = true\nlimits = { daily = 20 }\nhosts = [\"c\"]\n",
+        )?;
+
+        let check = |cfg: &FireblocksConfig| -> anyhow::Result<()> {
+            assert_eq!(cfg.get_extra::<String, _>("region")?, "eu");
+            assert_eq!(cfg.get_extra::<u64, _>("timeout")?, 30);
+            assert!(cfg.get_extra::<bool, _>("fail_fast")?);
+            Ok(())
+        };
+        let cfg = FireblocksConfig::new(&base, &[&profile])?;
+        check(&cfg)?;
+        // nested tables merge per key as well, arrays are replaced
+        assert_eq!(
+            cfg.get_extra::<serde_json::Value, _>("limits")?,
+            serde_json::json!({"daily": 20, "weekly": 50})
+        );
+        assert_eq!(cfg.get_extra::<Vec<String>, _>("hosts")?, vec!["c"]);
+
+        check(&FireblocksConfig::with_profile(&base, "prod")?)?;
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_asset_ids() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!(cfg.asset_test_suffix, "_TEST");
+        assert_eq!(cfg.asset_id("SOL"), "SOL_TEST");
+        assert_eq!(cfg.base_asset("SOL_TEST"), "SOL");
+        assert_eq!(cfg.base_asset("SOL"), "SOL");
+        assert_eq!(cfg.base_asset("_TEST"), "_TEST");
+        // no allow-list accepts any asset
+        assert_eq!(cfg.require_asset("DOGE")?, "DOGE_TEST");
+
+        let cfg = FireblocksConfig::new_with_kv(b, &[], &["asset_test_suffix=-DEV"])?;
+        assert_eq!(cfg.asset_id("SOL"), "SOL-DEV");
+        assert_eq!(cfg.base_asset("SOL-DEV"), "SOL");
+
+        let mut cfg = FireblocksConfig::new(b, &["examples/assets.toml"])?;
+        // overrides win over the suffix rule
+        assert_eq!(cfg.asset_id("ETH"), "ETH_TEST5");
+        assert_eq!(cfg.base_asset("ETH_TEST5"), "ETH");
+        assert_eq!(cfg.asset_id("BTC"), "BTC_TEST");
+        assert_eq!(cfg.require_asset("ETH")?, "ETH_TEST5");
+        match cfg.require_asset("DOGE") {
+            Err(Error::AssetNotFound { asset }) => assert_eq!(asset, "DOGE"),
+            other => panic!("Expected AssetNotFound error, got {other:?}"),
+        }
+
+        // mainnet ids are the base assets
+        cfg.mainnet = true;
+        assert_eq!(cfg.asset_id("ETH"), "ETH");
+        assert_eq!(cfg.base_asset("ETH"), "ETH");
+        assert_eq!(cfg.require_asset("SOL")?, "SOL");
+        assert!(matches!(
+            cfg.require_asset("DOGE"),
+            Err(Error::AssetNotFound { .. })
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_log_level() -> anyhow::Result<()> {
+        use tracing::Level;
+
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new(b, &[])?;
+        assert_eq!(cfg.log_level, None);
+        assert_eq!(cfg.log_level(), Level::INFO);
+        let cfg = FireblocksConfig::new_with_kv(b, &[], &["debug=true"])?;
+        assert_eq!(cfg.log_level(), Level::DEBUG);
+
+        for (name, level) in [
+            ("error", Level::ERROR),
+            ("warn", Level::WARN),
+            ("info", Level::INFO),
+            ("debug", Level::DEBUG),
+            ("TRACE", Level::TRACE),
+        ] {
+            // log_level wins over debug
+            let cfg = FireblocksConfig::new_with_kv(b, &[], &[
+                format!("log_level={name}"),
+                "debug=true".into(),
+            ])?;
+            assert_eq!(cfg.log_level(), level);
+        }
+
+        let mut cfg = FireblocksConfig::default();
+        cfg.log_level = Some("trace".to_string());
+        let filter = cfg.env_filter();
+        assert!(filter.starts_with("trace,"), "{filter}");
+        assert!(filter.contains(",hyper=warn"), "{filter}");
+        cfg.log_level = Some("error".to_string());
+        assert!(cfg.env_filter().contains(",reqwest=error"));
+
+        for invalid in ["verbose", "1", ""] {
+            match FireblocksConfig::new_with_kv(b, &[], &[format!("log_level={invalid}")]) {
+                Err(Error::InvalidValue { key, reason, .. }) => {
+                    assert_eq!(key, "log_level");
+                    assert_eq!(reason, "expected one of error, warn, info, debug, trace");
+                }
+                other => panic!("Expected InvalidValue error, got {other:?}"),
+            }
+        }
+        Ok(())
+    }
+
     #[test_log::test]
-    fn test_signer_config_default() -> anyhow::Result<()> {
-        let cfg = super::Signer::default();
-        assert!(!cfg.broadcast);
-        assert_eq!(cfg.vault.len(), 0);
+    fn test_partial_config_defaults() -> anyhow::Result<()> {
+        let _env = env_lock();
+        // deserializing a partial table never fails on missing keys
+        let cfg: FireblocksConfig = toml::from_str("mainnet = true\n")?;
+        assert!(cfg.api_key.is_empty());
+        assert!(cfg.url.is_empty());
+        assert!(cfg.signer.vault.is_empty());
+        assert_eq!(cfg.signer, Signer::default());
+
+        // validation decides what is required
+        match FireblocksConfig::from_contents("url = \"\"\n", FileFormat::Toml) {
+            Err(Error::InvalidApiKey { reason, .. }) => assert!(reason.contains("empty")),
+            other => panic!("Expected InvalidApiKey error, got {other:?}"),
+        }
+        let cfg = FireblocksConfig::from_contents(
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n",
+            FileFormat::Toml,
+        )?;
+        assert!(cfg.signer.vault.is_empty());
+        assert!(
+            cfg.validate_all()
+                .iter()
+                .any(|e| matches!(e, Error::InvalidVault { .. }))
+        );
         Ok(())
     }
 
-    #[ignore]
     #[test_log::test]
-    fn test_gpg_config() -> anyhow::Result<()> {
-        let b = "examples/config-gpg.toml";
+    fn test_loaded_mtime() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        std::fs::copy("examples/default.toml", &path)?;
+        let cfg = FireblocksConfig::new(path.clone(), &[PathBuf::from("examples/override.toml")])?;
+        let mtime = std::fs::metadata(&path)?.modified()?;
+        assert_eq!(cfg.loaded_mtime(), Some(mtime));
+        assert_eq!(cfg.reload()?.loaded_mtime(), Some(mtime));
+
+        let cfg = FireblocksConfig::from_contents(
+            "api_key = \"6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50\"\n",
+            FileFormat::Toml,
+        )?;
+        assert_eq!(cfg.loaded_mtime(), None);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_config_version() -> anyhow::Result<()> {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let _env = env_lock();
+        let capture = CaptureLayer::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let cfg = tracing::subscriber::with_default(subscriber, || {
+            FireblocksConfig::new("examples/v1.toml", &[])
+        })?;
+        assert_eq!(cfg.secret.as_deref(), Some("v1-secret"));
+        assert_eq!(cfg.version, FireblocksConfig::CURRENT_VERSION);
+        let lines = capture.0.lock().unwrap().clone();
+        assert!(
+            lines.iter().any(|line| line.contains("found=1")),
+            "{lines:?}"
+        );
+
+        // migrate_file rewrites the old key and stamps the version
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        std::fs::copy("examples/v1.toml", &path)?;
+        FireblocksConfig::migrate_file(&path)?;
+        let migrated = std::fs::read_to_string(&path)?;
+        assert!(!migrated.contains("secret_key"), "{migrated}");
+        assert!(migrated.contains("secret = \"v1-secret\""), "{migrated}");
+        assert!(migrated.contains("version = 2"), "{migrated}");
+        let cfg = FireblocksConfig::new(&path, &[])?;
+        assert_eq!(cfg.secret.as_deref(), Some("v1-secret"));
+        FireblocksConfig::migrate_file(&path)?;
+        assert_eq!(std::fs::read_to_string(&path)?, migrated);
+
+        match FireblocksConfig::from_contents("version = 3\n", FileFormat::Toml) {
+            Err(Error::UnsupportedConfigVersion { found, supported }) => {
+                assert_eq!(found, 3);
+                assert_eq!(supported, FireblocksConfig::CURRENT_VERSION);
+            }
+            other => panic!("Expected UnsupportedConfigVersion error, got {other:?}"),
+        }
+        std::fs::write(&path, "version = 3\n")?;
+        assert!(matches!(
+            FireblocksConfig::migrate_file(&path),
+            Err(Error::UnsupportedConfigVersion { found: 3, .. })
+        ));
+        assert!(matches!(
+            FireblocksConfig::from_contents("version = 0\n", FileFormat::Toml),
+            Err(Error::InvalidValue { .. })
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_resolved_url() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/no_url.toml";
         let cfg = FireblocksConfig::new(b, &[])?;
-        cfg.get_key()?;
+        assert!(cfg.url.is_empty());
+        assert_eq!(cfg.resolved_url(), FireblocksConfig::SANDBOX_URL);
+
+        let cfg = FireblocksConfig::new_with_kv(b, &[], &["mainnet=true"])?;
+        assert_eq!(cfg.resolved_url(), FireblocksConfig::PRODUCTION_URL);
+
+        // an empty url is treated as missing
+        let cfg = FireblocksConfig::new_with_kv(b, &[], &["url=", "mainnet=true"])?;
+        assert_eq!(cfg.resolved_url(), FireblocksConfig::PRODUCTION_URL);
+
+        // an explicit url wins over mainnet
+        let cfg = FireblocksConfig::new_with_kv("examples/default.toml", &[], &["mainnet=true"])?;
+        assert_eq!(cfg.resolved_url(), FireblocksConfig::SANDBOX_URL);
         Ok(())
     }
 
     #[test_log::test]
-    fn test_config() -> anyhow::Result<()> {
+    fn test_override_globs() -> anyhow::Result<()> {
+        let _env = env_lock();
         let b = "examples/default.toml";
-        let cfg = FireblocksConfig::new(b, &[])?;
-        assert_eq!("blah", cfg.api_key);
-        assert!(cfg.secret_path.is_some());
-        if let Some(p) = cfg.secret_path.as_ref() {
-            assert_eq!(PathBuf::from("examples/test.pem"), *p);
+        let cfg = FireblocksConfig::new_with_globs(b, &["examples/overrides/*.toml"])?;
+        assert_eq!(cfg.api_key, "glob-production");
+        assert_eq!(cfg.signer.vault, "20");
+        assert_eq!(cfg.reload()?, cfg);
+
+        // blank patterns are skipped
+        let cfg = FireblocksConfig::new_with_globs(b, &["", "examples/overrides/10-*.toml"])?;
+        assert_eq!(cfg.signer.vault, "10");
+
+        assert!(matches!(
+            FireblocksConfig::new_with_globs(b, &["examples/overrides/*.missing"]),
+            Err(Error::ConfigNotFound(_))
+        ));
+        assert!(matches!(
+            FireblocksConfig::new_with_globs(b, &["examples/[overrides"]),
+            Err(Error::InvalidGlob { .. })
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_kv_overrides() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new_with_kv(b, &[], &[
+            "signer.vault=9",
+            "signer.poll_timeout=30",
+            "display.output=json",
+            "mainnet=true",
+            "extra.rpc_url=https://x",
+            "extra.retries=3",
+            "extra.ratio=0.5",
+            "extra.enabled=false",
+        ])?;
+        assert_eq!(cfg.signer.vault, "9");
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(30));
+        assert_eq!(cfg.display_config.output, OutputFormat::Json);
+        assert!(cfg.mainnet);
+        assert_eq!(cfg.get_extra::<String, _>("rpc_url")?, "https://x");
+        assert_eq!(cfg.extra["retries"], serde_json::json!(3));
+        assert_eq!(cfg.extra["ratio"], serde_json::json!(0.5));
+        assert_eq!(cfg.extra["enabled"], serde_json::json!(false));
+        assert_eq!(cfg.reload()?, cfg);
+
+        // kv pairs sit above override files
+        let cfg =
+            FireblocksConfig::new_with_kv(b, &["examples/override.toml"], &["api_key=from-kv"])?;
+        assert_eq!(cfg.api_key, "from-kv");
+
+        match FireblocksConfig::new_with_kv(b, &[], &["signer.vault"]) {
+            Err(Error::InvalidOverride(pair)) => assert_eq!(pair, "signer.vault"),
+            other => panic!("Expected InvalidOverride error, got {other:?}"),
         }
-        assert_eq!("https://sandbox-api.fireblocks.io/v1", cfg.url);
-        assert_eq!(OutputFormat::Table, cfg.display_config.output);
-        unsafe {
-            std::env::set_var("FIREBLOCKS_SECRET", "override");
+        assert!(matches!(
+            FireblocksConfig::new_with_kv(b, &[], &["=9"]),
+            Err(Error::InvalidOverride(_))
+        ));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_cli_overrides() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cli = [
+            ("url".to_string(), "https://flag".to_string()),
+            ("signer.vault".to_string(), "8".to_string()),
+        ];
+        let cfg = FireblocksConfig::new_with_cli(b, &["examples/override.toml"], &cli)?;
+        assert_eq!(cfg.url, "https://flag");
+        assert_eq!(cfg.resolved_url(), "https://flag");
+        assert_eq!(cfg.signer.vault, "8");
+        assert_eq!(cfg.reload()?, cfg);
+
+        let env = vars(&[("FIREBLOCKS_URL", "https://env")]);
+        let below = FireblocksConfig::new_with_cli(b, &[], &cli)?.reload_with_env(env.clone())?;
+        let above =
+            FireblocksConfig::new_with_cli_precedence(b, &[], &cli, OverridePrecedence::AboveEnv)?
+                .reload_with_env(env)?;
+        let reloaded = above.reload()?;
+        assert_eq!(below.url, "https://env");
+        assert_eq!(above.url, "https://flag");
+        assert_eq!(reloaded.url, "https://flag");
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_reload_and_diff() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("default.toml");
+        let base = std::fs::read_to_string("examples/default.toml")?;
+        std::fs::write(&path, &base)?;
+
+        let cfg = FireblocksConfig::new(&path, &[])?;
+        assert!(cfg.diff(&cfg.reload()?)?.is_empty());
+
+        let changed = base
+            .replace("vault = \"0\"", "vault = \"4\"")
+            .replace("timeout = 40", "timeout = 41")
+            .replace(
+                "api_key = \"blah\"",
+                "api_key = \"blah-rotated-key\"\nsecret = \"rotated\"",
+            );
+        std::fs::write(&path, changed)?;
+        let fresh = cfg.reload()?;
+        assert_eq!(fresh.signer.vault, "4");
+
+        let changes = cfg.diff(&fresh)?;
+        let keys: Vec<&str> = changes.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, vec![
+            "api_key",
+            "extra.timeout",
+            "secret",
+            "signer.vault"
+        ]);
+        assert_eq!(changes[0].new, Some("blah-rot********".into()));
+        let secret = &changes[2];
+        assert_eq!(secret.old, None);
+        assert_eq!(secret.new, Some("<redacted>".into()));
+        assert_eq!(changes[3].old, Some("0".into()));
+        assert_eq!(changes[3].new, Some("4".into()));
+
+        let manual = FireblocksConfig::default();
+        assert!(matches!(manual.reload(), Err(Error::NotReloadable)));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_serialize_skips_secrets() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &["examples/embedded.toml"])?;
+        cfg.secret_shares = Some(vec!["01deadbeef".to_string()]);
+        assert_eq!(cfg.secret.as_deref(), Some("i am a secret"));
+
+        let toml = toml::to_string(&cfg)?;
+        let json = serde_json::to_string(&cfg)?;
+        for out in [&toml, &json] {
+            assert!(!out.contains("i am a secret"), "{out}");
+            assert!(!out.contains("01deadbeef"), "{out}");
+            assert!(out.contains("examples/test.pem"), "{out}");
         }
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_config_eq() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
         let cfg = FireblocksConfig::new(b, &[])?;
-        assert!(cfg.secret.is_some());
-        assert_eq!(String::from("override").as_bytes(), cfg.get_key()?);
-        if let Some(ref k) = cfg.secret_path {
-            assert_eq!(PathBuf::from("examples/test.pem"), *k);
+        assert_eq!(cfg, FireblocksConfig::new(b, &[])?);
+        assert_ne!(cfg, FireblocksConfig::new(b, &["examples/override.toml"])?);
+
+        let embedded = FireblocksConfig::new(b, &["examples/embedded.toml"])?;
+        assert_ne!(cfg, embedded);
+        assert!(!cfg.eq_ignoring_secrets(&FireblocksConfig::new(b, &["examples/override.toml"])?));
+
+        let mut cfg = FireblocksConfig::new(b, &[])?;
+        cfg.secret = Some("something else".to_string());
+        assert_ne!(cfg, FireblocksConfig::new(b, &[])?);
+        assert!(cfg.eq_ignoring_secrets(&FireblocksConfig::new(b, &[])?));
+
+        let formats: std::collections::HashSet<OutputFormat> =
+            [OutputFormat::Json, OutputFormat::Json, OutputFormat::Tsv].into();
+        assert_eq!(formats.len(), 2);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_workspaces() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let workspaces = FireblocksWorkspaces::new("examples/workspaces.toml")?;
+        assert_eq!(workspaces.names(), vec!["customer_a", "customer_b"]);
+
+        let a = workspaces.get("customer_a")?;
+        assert_eq!(a.api_key, "0b6f2c1e-8d4a-4f3b-9c2e-1a5d7e9f0b3c");
+        assert_eq!(a.secret_path, Some(PathBuf::from("keys/customer_a.pem")));
+        assert_eq!(a.resolved_url(), FireblocksConfig::PRODUCTION_URL);
+        assert_eq!(a.signer.vault, "0");
+        assert_eq!(a.signer.poll_timeout, Duration::from_secs(60));
+        assert_eq!(workspaces.default_workspace()?, a);
+
+        let b = workspaces.get("customer_b")?;
+        assert_eq!(b.secret_path, Some(PathBuf::from("keys/customer_b.pem")));
+        assert_eq!(b.resolved_url(), FireblocksConfig::SANDBOX_URL);
+        assert!(!b.mainnet);
+        assert_eq!(b.signer.vault, "7");
+        assert_eq!(b.signer.poll_timeout, Duration::from_secs(60));
+
+        match workspaces.get("customer_c") {
+            Err(Error::WorkspaceNotFound {
+                workspace,
+                available,
+            }) => {
+                assert_eq!(workspace, "customer_c");
+                assert_eq!(available, vec!["customer_a", "customer_b"]);
+            }
+            other => panic!("Expected WorkspaceNotFound error, got {other:?}"),
         }
 
-        assert_eq!(cfg.signer.vault, "0");
-        assert!(!cfg.signer.broadcast);
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("workspaces.toml");
+        std::fs::write(
+            &path,
+            "[signer]\nvault = \"0\"\n[workspaces.broken]\napi_key = \"not-a-uuid\"\n",
+        )?;
+        match FireblocksWorkspaces::new(&path) {
+            Err(e @ Error::Workspace { .. }) => assert!(e.to_string().contains("'broken'")),
+            other => panic!("Expected Workspace error, got {other:?}"),
+        }
+
+        // paths resolve against the workspace file, the environment is ignored
+        std::fs::write(
+            &path,
+            "secret_path = \"shared.pem\"\n[signer]\nvault = \"0\"\n[workspaces.a]\napi_key = \
+             \"0b6f2c1e-8d4a-4f3b-9c2e-1a5d7e9f0b3c\"\nsecret_path = \"key.pem\"\n",
+        )?;
+        std::fs::write(dir.path().join("key.pem"), "workspace key")?;
+        unsafe {
+            std::env::set_var("FIREBLOCKS_API_KEY", "7e1d9a3b-2c4f-4e6a-8b0d-3f5a7c9e1b2d");
+        }
+        let workspaces = FireblocksWorkspaces::new(&path);
         unsafe {
-            std::env::remove_var("FIREBLOCKS_SECRET");
+            std::env::remove_var("FIREBLOCKS_API_KEY");
         }
+        let a = workspaces?.default_workspace()?.clone();
+        assert_eq!(a.api_key, "0b6f2c1e-8d4a-4f3b-9c2e-1a5d7e9f0b3c");
+        assert_eq!(a.get_key()?, b"workspace key");
+        assert_eq!(a.sources(), [ConfigSource::File(path.clone())]);
+        assert!(a.unknown_keys().is_empty());
+        assert_eq!(a.reload()?, a);
         Ok(())
     }
 
     #[test_log::test]
-    fn test_config_override() -> anyhow::Result<()> {
-        let b = "examples/default.toml";
-        let cfg_override = "examples/override.toml";
-        let cfg = FireblocksConfig::with_overrides(b, vec![cfg_override])?;
+    fn test_inline_profiles() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/profiles.toml";
+        assert_eq!(
+            vec!["production".to_string(), "sandbox".to_string()],
+            FireblocksConfig::list_inline_profiles(b)?
+        );
+
+        // partial profile only touches the signer vault
+        let cfg = FireblocksConfig::with_profile(b, "sandbox")?;
+        assert_eq!("blah", cfg.api_key);
+        assert_eq!("https://sandbox-api.fireblocks.io/v1", cfg.url);
+        assert_eq!(cfg.signer.vault, "3");
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(120));
+        assert!(!cfg.mainnet);
+
+        let cfg = FireblocksConfig::with_profile(b, "production")?;
         assert_eq!("production", cfg.api_key);
-        assert!(cfg.secret_path.is_some());
-        if let Some(p) = cfg.secret_path.as_ref() {
-            assert_eq!(PathBuf::from("examples/test.pem"), *p);
-        }
         assert_eq!("https://api.fireblocks.io/v1", cfg.url);
-        assert_eq!(OutputFormat::Table, cfg.display_config.output);
-        assert!(cfg.debug);
         assert!(cfg.mainnet);
+        assert_eq!(cfg.signer.vault, "7");
+        assert!(cfg.signer.broadcast);
+        assert_eq!(cfg.signer.poll_interval, Duration::from_secs(5));
+        assert_eq!(
+            cfg.get_extra::<String, _>("rpc_url")?,
+            "https://mainnet.rpc.com"
+        );
+        assert_eq!(cfg.get_extra::<i64, _>("timeout")?, 40);
+
+        match FireblocksConfig::with_profile(b, "staging") {
+            Err(Error::ProfileNotFound { profile, available }) => {
+                assert_eq!(profile, "staging");
+                assert_eq!(available, vec!["production", "sandbox"]);
+            }
+            other => panic!("Expected ProfileNotFound error, got {other:?}"),
+        }
+
+        // profiles load like new, following extends and relative paths
+        let b = "examples/relative/profiled.toml";
+        let cfg = FireblocksConfig::with_profile(b, "local")?;
+        assert_eq!("profiled", cfg.api_key);
+        assert_eq!(cfg.signer.vault, "5");
+        assert_eq!(cfg.get_key()?, std::fs::read("examples/test.pem")?);
+        assert_eq!(cfg.sources(), [ConfigSource::File(PathBuf::from(b))]);
+        assert!(cfg.loaded_mtime().is_some());
+        assert_eq!(cfg.reload()?.signer.vault, "5");
         Ok(())
     }
 
     #[test_log::test]
     fn test_embedded_key() -> anyhow::Result<()> {
+        let _env = env_lock();
         let b = "examples/default.toml";
         let cfg_override = "examples/embedded.toml";
         let cfg = FireblocksConfig::new(b, &[cfg_override])?;
         assert!(cfg.secret.is_some());
+        // secret_path is set too, loading warns and the embedded secret wins
+        assert!(cfg.secret_path.is_some());
+        assert_eq!(cfg.get_key()?, b"i am a secret");
         let secret = cfg.secret.unwrap();
         assert_eq!(String::from("i am a secret").as_bytes(), secret.as_bytes());
         Ok(())
     }
 
+    #[cfg(feature = "async")]
+    #[allow(clippy::await_holding_lock)]
+    #[test_log::test(tokio::test)]
+    async fn test_config_async() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new_async(b, &[]).await?;
+        assert_eq!("blah", cfg.api_key);
+        assert_eq!(Some(PathBuf::from("examples/test.pem")), cfg.secret_path);
+        assert_eq!("https://sandbox-api.fireblocks.io/v1", cfg.url);
+        assert_eq!(OutputFormat::Table, cfg.display_config.output);
+        assert_eq!(cfg.signer.vault, "0");
+        assert!(!cfg.signer.broadcast);
+
+        let mut cfg = cfg;
+        cfg.secret = None;
+        assert_eq!(
+            std::fs::read("examples/test.pem")?,
+            cfg.get_key_async().await?
+        );
+
+        let cfg = FireblocksConfig::new_async(b, &["examples/override.toml"]).await?;
+        assert_eq!("production", cfg.api_key);
+        assert!(cfg.mainnet);
+        // same layering as the blocking constructor, and reloadable
+        assert_eq!(cfg, FireblocksConfig::new(b, &["examples/override.toml"])?);
+        assert_eq!(cfg.reload()?, cfg);
+
+        // encrypted overrides are decoded off the runtime thread
+        #[cfg(not(feature = "gpg"))]
+        {
+            let dir = tempfile::tempdir()?;
+            let path = dir.path().join("override.toml");
+            std::fs::write(&path, "-----BEGIN PGP MESSAGE-----\n\nhQEMA...\n")?;
+            let path = path.to_string_lossy().to_string();
+            assert!(matches!(
+                FireblocksConfig::new_async(b.to_string(), &[path]).await,
+                Err(Error::ConfigDecryption { .. })
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[ignore]
+    #[test_log::test(tokio::test(flavor = "current_thread"))]
+    async fn test_gpg_config_async() -> anyhow::Result<()> {
+        let b = "examples/default.toml";
+        let cfg = FireblocksConfig::new_async(b, &["examples/override.toml.asc"]).await?;
+        assert_eq!(
+            cfg,
+            FireblocksConfig::new(b, &["examples/override.toml.asc"])?
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test_log::test(tokio::test)]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_embedded_key_async() -> anyhow::Result<()> {
+        let _env = env_lock();
+        let b = "examples/default.toml";
+        let cfg_override = "examples/embedded.toml";
+        let cfg = FireblocksConfig::new_async(b, &[cfg_override]).await?;
+        assert!(cfg.secret.is_some());
+        assert_eq!(
+            String::from("i am a secret").as_bytes(),
+            cfg.get_key_async().await?
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "watch")]
+    #[test_log::test]
+    fn test_watch_reload() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("default.toml");
+        let base = std::fs::read_to_string("examples/default.toml")?;
+        std::fs::write(&path, &base)?;
+
+        let (cfg, handle) = FireblocksConfig::watch(&path, &[])?;
+        assert_eq!(cfg.load().signer.vault, "0");
+
+        std::fs::write(&path, base.replace("vault = \"0\"", "vault = \"9\""))?;
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while cfg.load().signer.vault != "9" && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(cfg.load().signer.vault, "9");
+
+        // a broken file keeps the last good config
+        std::fs::write(&path, "api_key = ")?;
+        std::thread::sleep(Duration::from_secs(1));
+        assert_eq!(cfg.load().signer.vault, "9");
+
+        drop(handle);
+        std::fs::write(&path, &base)?;
+        std::thread::sleep(Duration::from_secs(1));
+        assert_eq!(cfg.load().signer.vault, "9");
+        Ok(())
+    }
+
+    /// Requires AWS credentials from the default chain (e.g. `AWS_PROFILE`
+    /// or `AWS_ACCESS_KEY_ID`/`AWS_SECRET_ACCESS_KEY`), `AWS_REGION`, and
+    /// `FIREBLOCKS_TEST_AWS_SECRET_ARN` naming a readable secret
+    #[cfg(feature = "aws")]
+    #[ignore]
+    #[test_log::test]
+    fn test_aws_secret() -> anyhow::Result<()> {
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.secret_aws_arn = Some(std::env::var("FIREBLOCKS_TEST_AWS_SECRET_ARN")?);
+        assert!(!cfg.get_key()?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "kms")]
+    #[test_log::test]
+    fn test_kms_decrypt_request() -> anyhow::Result<()> {
+        let request = kms::decrypt_request("AQID\n  BAU=\n", Some(" alias/fireblocks "))?;
+        assert_eq!(request.ciphertext_blob, vec![1, 2, 3, 4, 5]);
+        assert_eq!(request.key_id.as_deref(), Some("alias/fireblocks"));
+
+        let request = kms::decrypt_request("AQIDBAU=", Some(""))?;
+        assert_eq!(request.key_id, None);
+
+        assert!(matches!(
+            kms::decrypt_request("not base64!", None),
+            Err(Error::Kms(_))
+        ));
+        assert!(matches!(
+            kms::decrypt_request(" \n", None),
+            Err(Error::Kms(_))
+        ));
+
+        // The ciphertext takes priority over local sources
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.secret_kms_ciphertext = Some("not base64!".to_string());
+        assert!(matches!(cfg.get_key(), Err(Error::Kms(_))));
+        Ok(())
+    }
+
+    /// Requires AWS credentials from the default chain, `AWS_REGION`, and
+    /// `FIREBLOCKS_TEST_KMS_CIPHERTEXT` holding a base64 ciphertext the
+    /// caller may decrypt, with `FIREBLOCKS_TEST_KMS_KEY_ID` optionally naming
+    /// its key
+    #[cfg(feature = "aws-kms")]
+    #[ignore]
+    #[test_log::test]
+    fn test_aws_kms_secret() -> anyhow::Result<()> {
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.secret_kms_ciphertext = Some(std::env::var("FIREBLOCKS_TEST_KMS_CIPHERTEXT")?);
+        cfg.secret_kms_key_id = std::env::var("FIREBLOCKS_TEST_KMS_KEY_ID").ok();
+        assert!(!cfg.get_key()?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "vault")]
+    #[test_log::test]
+    fn test_vault_location() -> anyhow::Result<()> {
+        let location = vault::kv2_location("https://vault.local:8200/", "secret/fireblocks/key")?;
+        assert_eq!(
+            location.url,
+            "https://vault.local:8200/v1/secret/data/fireblocks/key"
+        );
+        assert_eq!(location.field, "key");
+
+        let location = vault::kv2_location("https://vault.local", "/kv/team/fireblocks#pem")?;
+        assert_eq!(
+            location.url,
+            "https://vault.local/v1/kv/data/team/fireblocks"
+        );
+        assert_eq!(location.field, "pem");
+
+        assert!(matches!(
+            vault::kv2_location("https://vault.local", "secret"),
+            Err(Error::Vault(_))
+        ));
+        assert!(matches!(
+            vault::kv2_location("", "secret/fireblocks"),
+            Err(Error::Vault(_))
+        ));
+        Ok(())
+    }
+
+    /// Requires a Vault server at `VAULT_ADDR`, a token in `VAULT_TOKEN`, and
+    /// a KV v2 secret at `secret/fireblocks-test` with a `key` field
+    #[cfg(feature = "vault")]
+    #[ignore]
+    #[test_log::test]
+    fn test_vault_secret() -> anyhow::Result<()> {
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.vault_addr = Some(std::env::var("VAULT_ADDR")?);
+        cfg.vault_secret_path = Some("secret/fireblocks-test".to_string());
+        assert!(!cfg.get_key()?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "remote")]
+    #[test_log::test]
+    fn test_remote_file_format() -> anyhow::Result<()> {
+        let toml = url::Url::parse("https://config.local/fireblocks.toml")?;
+        let json = url::Url::parse("https://config.local/fireblocks.json?rev=2")?;
+        let bare = url::Url::parse("https://config.local/fireblocks")?;
+        assert_eq!(
+            remote::file_format(Some("application/json; charset=utf-8"), &toml),
+            FileFormat::Json
+        );
+        assert_eq!(
+            remote::file_format(Some("application/vnd.api+json"), &toml),
+            FileFormat::Json
+        );
+        assert_eq!(
+            remote::file_format(Some("Application/TOML"), &json),
+            FileFormat::Toml
+        );
+        assert_eq!(
+            remote::file_format(Some("text/x-yaml"), &bare),
+            FileFormat::Yaml
+        );
+        // generic types fall back to the extension, then TOML
+        assert_eq!(
+            remote::file_format(Some("text/plain"), &json),
+            FileFormat::Json
+        );
+        assert_eq!(
+            remote::file_format(None, &url::Url::parse("https://c.local/a.YML")?),
+            FileFormat::Yaml
+        );
+        assert_eq!(remote::file_format(None, &bare), FileFormat::Toml);
+        Ok(())
+    }
+
+    /// Requires `FIREBLOCKS_TEST_CONFIG_URL` serving a complete config
+    #[cfg(feature = "remote")]
+    #[ignore]
+    #[test_log::test]
+    fn test_from_url() -> anyhow::Result<()> {
+        let cfg = FireblocksConfig::from_url(&std::env::var("FIREBLOCKS_TEST_CONFIG_URL")?)?;
+        assert!(!cfg.api_key.is_empty());
+        assert!(matches!(
+            FireblocksConfig::from_url("not a url"),
+            Err(Error::RemoteFetch { .. })
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test_log::test]
+    fn test_testing_fixtures() -> anyhow::Result<()> {
+        use testing::{EnvVars, FAKE_RSA_KEY, TempConfig, fake_config, fake_config_with};
+
+        let cfg = fake_config();
+        assert_eq!(cfg.get_key()?, FAKE_RSA_KEY.as_bytes());
+        assert!(!cfg.mainnet);
+        #[cfg(feature = "jwt")]
+        cfg.sign_request("/v1/vault/accounts_paged", b"")?;
+        let cfg = fake_config_with(|c| c.signer.vault = "9".to_string());
+        assert_eq!(cfg.signer.vault_id()?, 9);
+
+        let dir;
+        {
+            let temp = TempConfig::new()?;
+            dir = temp.dir().to_path_buf();
+            let cfg = {
+                let _env = env_lock();
+                temp.load()?
+            };
+            assert_eq!(cfg.get_key()?, FAKE_RSA_KEY.as_bytes());
+            assert_eq!(cfg.expanded_secret_path()?, temp.key_path());
+            #[cfg(feature = "pem")]
+            cfg.validate_key_pem()?;
+
+            let prod = temp.write("prod.toml", "mainnet = true\n")?;
+            let _env = env_lock();
+            assert!(FireblocksConfig::new(temp.path(), &[prod])?.mainnet);
+        }
+        assert!(!dir.exists());
+
+        // variables are restored on drop
+        {
+            let _env = env_lock();
+            let temp = TempConfig::new()?;
+            {
+                let mut vars = EnvVars::lock();
+                vars.set("FIREBLOCKS_SIGNER__VAULT", "4")
+                    .remove("FIREBLOCKS_URL");
+                assert_eq!(temp.load()?.signer.vault, "4");
+            }
+            assert!(std::env::var_os("FIREBLOCKS_SIGNER__VAULT").is_none());
+            assert_eq!(temp.load()?.signer.vault, "0");
+        }
+        Ok(())
+    }
+
+    /// The only test touching the global config, its steps depend on order
+    #[cfg(feature = "global")]
+    #[test_log::test]
+    fn test_global() -> anyhow::Result<()> {
+        assert!(global::try_get().is_none());
+        assert!(matches!(global::get(), Err(Error::GlobalNotInitialized)));
+        assert!(matches!(
+            global::with_global(|c| c.url.clone()),
+            Err(Error::GlobalNotInitialized)
+        ));
+
+        // racing inits install exactly one config
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|vault| {
+                    scope.spawn(move || {
+                        let mut cfg = FireblocksConfig::new_no_env("examples/default.toml", &[])?;
+                        cfg.signer.vault = vault.to_string();
+                        global::init(cfg).map(|c| c.signer.vault.clone())
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let winners: Vec<&String> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        assert_eq!(winners.len(), 1);
+        assert!(
+            results
+                .iter()
+                .filter_map(|r| r.as_ref().err())
+                .all(|e| matches!(e, Error::GlobalAlreadyInitialized))
+        );
+
+        let vaults: Vec<String> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| global::with_global(|c| c.signer.vault.clone())))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Result<_>>()
+        })?;
+        assert!(vaults.iter().all(|v| v == winners[0]));
+        assert_eq!(&global::get()?.signer.vault, winners[0]);
+        assert!(matches!(
+            global::init_from_xdg::<&str>(&[]),
+            Err(Error::GlobalAlreadyInitialized)
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test_log::test]
+    fn test_from_cli() -> anyhow::Result<()> {
+        let _env = env_lock();
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            fireblocks: FireblocksCliArgs,
+        }
+
+        let cli = Cli::try_parse_from([
+            "test",
+            "--config",
+            "examples/default.toml",
+            "--profile",
+            "override",
+            "--output",
+            "json",
+            "--vault",
+            "42",
+            "--extra",
+            "timeout=90",
+            "--extra",
+            "rpc_url=https://cli.rpc",
+            "--extra",
+            "chains=[\"sol\",\"eth\"]",
+        ])?;
+        let cfg = FireblocksConfig::from_cli(&cli.fireblocks)?;
+        // the override profile sets these
+        assert_eq!("production", cfg.api_key);
+        assert!(cfg.mainnet);
+        assert!(cfg.debug);
+        assert_eq!(OutputFormat::Json, cfg.display_config.output);
+        assert_eq!(cfg.signer.vault, "42");
+        assert_eq!(cfg.get_extra::<i64, _>("timeout")?, 90);
+        assert_eq!(cfg.get_extra::<String, _>("rpc_url")?, "https://cli.rpc");
+        assert_eq!(cfg.get_extra::<Vec<String>, _>("chains")?, vec![
+            "sol", "eth"
+        ]);
+        assert!(!cfg.get_extra::<bool, _>("fail_fast")?);
+
+        let cli = Cli::try_parse_from(["test", "--config", "examples/default.toml", "--mainnet"])?;
+        let cfg = FireblocksConfig::from_cli(&cli.fireblocks)?;
+        assert!(cfg.mainnet);
+        assert!(!cfg.debug);
+        assert_eq!(OutputFormat::Table, cfg.display_config.output);
+
+        assert!(Cli::try_parse_from(["test", "--extra", "novalue"]).is_err());
+        assert!(Cli::try_parse_from(["test", "--output", "yaml"]).is_err());
+
+        let cli = Cli::try_parse_from([
+            "test",
+            "--config",
+            "examples/default.toml",
+            "--profile",
+            "missing",
+        ])?;
+        assert!(matches!(
+            FireblocksConfig::from_cli(&cli.fireblocks),
+            Err(Error::ProfileConfigNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "sss")]
+    #[test_log::test]
+    fn test_secret_shares() -> anyhow::Result<()> {
+        let secret = b"i am a secret";
+        let shares: Vec<String> = sharks::Sharks(3)
+            .dealer(secret)
+            .take(5)
+            .map(|share| hex::encode(Vec::from(&share)))
+            .collect();
+
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.extra
+            .insert("secret_shares_threshold".to_string(), 3.into());
+        cfg.secret_shares = Some(shares[1..4].to_vec());
+        assert_eq!(secret.to_vec(), cfg.get_key()?);
+
+        cfg.secret_shares = Some(shares[..2].to_vec());
+        assert!(matches!(cfg.get_key(), Err(Error::SecretReconstruction(_))));
+        Ok(())
+    }
+
     #[test_log::test]
     fn test_duration_parsing() -> anyhow::Result<()> {
+        let _env = env_lock();
         let b = "examples/default.toml";
         let cfg = FireblocksConfig::new(b, &[])?;
 
@@ -106,6 +3637,46 @@ mod tests {
         assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(120));
         assert_eq!(cfg.signer.poll_interval, Duration::from_secs(5));
 
+        // integers, floats and strings of either
+        let cfg = FireblocksConfig::new("examples/durations.toml", &[])?;
+        assert_eq!(cfg.jwt_ttl, Some(Duration::from_secs(45)));
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(120));
+        assert_eq!(cfg.signer.poll_interval, Duration::from_millis(2500));
+        assert_eq!(
+            cfg.signer.poll_max_interval,
+            Some(Duration::from_millis(1250))
+        );
+        assert_eq!(cfg.retry.initial_backoff, Duration::from_secs(3));
+        assert_eq!(cfg.retry.max_backoff, Duration::from_secs(60));
+        assert_eq!(cfg.http.connect_timeout, Duration::from_secs(7));
+        assert_eq!(cfg.http.request_timeout, Duration::from_millis(500));
+
+        // fractional seconds survive a round trip
+        let toml = toml::to_string(&cfg)?;
+        assert!(toml.contains("poll_interval = \"2.5\""), "{toml}");
+
+        // null falls back to the field default
+        let json = r#"{
+            "api_key": "6f0c2a44-1d3b-4c5e-9f7a-0b1c2d3e4f50",
+            "jwt_ttl": null,
+            "signer": {"vault": "0", "poll_timeout": null, "poll_interval": null},
+            "http": {"connect_timeout": null}
+        }"#;
+        let cfg = FireblocksConfig::from_contents(json, FileFormat::Json)?;
+        assert_eq!(cfg.jwt_ttl, None);
+        assert_eq!(cfg.signer.poll_timeout, Duration::from_secs(180));
+        assert_eq!(cfg.signer.poll_interval, Duration::from_secs(5));
+        assert_eq!(cfg.http.connect_timeout, Duration::from_secs(10));
+
+        for bad in [
+            "signer.poll_timeout=-1",
+            "signer.poll_interval=-0.5",
+            "retry.max_backoff=soon",
+        ] {
+            let result = FireblocksConfig::new_with_kv("examples/durations.toml", &[], &[bad]);
+            let err = result.expect_err(bad).to_string();
+            assert!(err.contains("Invalid duration"), "{bad}: {err}");
+        }
         Ok(())
     }
 
@@ -161,8 +3732,85 @@ mod tests {
 
     #[test_log::test]
     fn test_tilde() -> anyhow::Result<()> {
-        let expanded = format!("{}", expand_tilde("~/blah/default.toml").display());
-        assert!(expanded.contains("/home"));
+        let home = Path::new("/mock/home");
+        let expand = |path: &str| expand_path_with(Path::new(path), Some(home), false);
+        assert_eq!(expand("~")?, home);
+        assert_eq!(expand("~/")?, home);
+        assert_eq!(expand("~/x")?.to_str(), Some("/mock/home/x"));
+        assert_eq!(
+            expand("~/blah/default.toml")?,
+            home.join("blah/default.toml")
+        );
+        assert_eq!(expand("~\\keys\\id.pem")?, home.join("keys\\id.pem"));
+        for unchanged in ["", "keys/id.pem", "/etc/key.pem", "~user/key.pem", "a~/b"] {
+            assert_eq!(expand(unchanged)?, PathBuf::from(unchanged));
+            assert_eq!(
+                expand_path_with(Path::new(unchanged), None, false)?,
+                PathBuf::from(unchanged)
+            );
+        }
+        match expand_path_with(Path::new("~/key.pem"), None, false) {
+            Err(Error::HomeDirUnavailable { path }) => assert_eq!(path, "~/key.pem"),
+            other => panic!("expected HomeDirUnavailable, got {other:?}"),
+        }
+
+        let windows = |path: &str| expand_path_with(Path::new(path), Some(home), true);
+        assert_eq!(windows("%USERPROFILE%\\key.pem")?, home.join("key.pem"));
+        assert_eq!(windows("%userprofile%")?, home);
+        assert_eq!(windows("%USER")?, PathBuf::from("%USER"));
+        assert_eq!(
+            expand("%USERPROFILE%\\key.pem")?,
+            PathBuf::from("%USERPROFILE%\\key.pem")
+        );
+
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_path("~/key.pem")?, home.join("key.pem"));
+            assert_eq!(expand_path("~")?, home);
+        }
+
+        // a bare `~` is the home directory, which is not a key file
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.secret = None;
+        cfg.secret_path = Some(PathBuf::from("~"));
+        assert!(cfg.get_key().is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test_log::test]
+    fn test_tilde_non_utf8() -> anyhow::Result<()> {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let home = Path::new("/mock/home");
+        let name = OsStr::from_bytes(b"keys/\xffid.pem");
+        let mut tilde = b"~/".to_vec();
+        tilde.extend_from_slice(name.as_bytes());
+        let tilde = Path::new(OsStr::from_bytes(&tilde));
+        assert_eq!(expand_path_with(tilde, Some(home), false)?, home.join(name));
+        let non_utf8_first = Path::new(OsStr::from_bytes(b"\xff/~"));
+        assert_eq!(
+            expand_path_with(non_utf8_first, Some(home), false)?,
+            non_utf8_first
+        );
+
+        // a relative non-UTF-8 secret_path is kept byte for byte
+        let mut cfg = FireblocksConfig::new("examples/default.toml", &[])?;
+        cfg.secret_path = Some(PathBuf::from(name));
+        assert_eq!(cfg.expanded_secret_path()?.as_os_str(), name);
+
+        // rebasing onto a non-UTF-8 directory cannot be written back as TOML
+        let tmp = tempfile::tempdir()?;
+        let dir = tmp.path().join(OsStr::from_bytes(b"\xff"));
+        std::fs::create_dir(&dir)?;
+        std::fs::write(
+            dir.join("root.toml"),
+            "api_key = \"root\"\nsecret_path = \"key.pem\"\n",
+        )?;
+        std::fs::write(dir.join("child.toml"), "extends = \"root.toml\"\n")?;
+        assert!(matches!(
+            FireblocksConfig::new(dir.join("child.toml"), &[]),
+            Err(Error::InvalidPath(_))
+        ));
         Ok(())
     }
 
@@ -201,4 +3849,274 @@ mod tests {
             }
         }
     }
+
+    #[test_log::test]
+    fn test_error_codes() -> anyhow::Result<()> {
+        let io = || std::io::Error::from(std::io::ErrorKind::NotFound);
+        let text = || "x".to_string();
+        let samples = vec![
+            Error::ConfigNotFound(text()),
+            Error::ConfigParseError(::config::ConfigError::Message(text())),
+            Error::Load {
+                sources: vec![text()],
+                source: Box::new(::config::ConfigError::Message(text())),
+                backtrace: None,
+            },
+            Error::IO(io()),
+            Error::ConfigParseAt {
+                path: text(),
+                detail: text(),
+                source: Box::new("=".parse::<toml::Table>().unwrap_err()),
+                backtrace: None,
+            },
+            Error::MissingSecret,
+            Error::AssetNotFound { asset: text() },
+            Error::IOError {
+                source: io(),
+                path: text(),
+                backtrace: None,
+            },
+            Error::InvalidDuration(text()),
+            Error::InvalidVault { value: text() },
+            Error::InvalidGlob {
+                pattern: text(),
+                source: glob::Pattern::new("[").unwrap_err(),
+            },
+            Error::InvalidUrl {
+                key: text(),
+                value: text(),
+                reason: text(),
+            },
+            Error::InvalidValue {
+                key: text(),
+                value: text(),
+                reason: text(),
+            },
+            Error::InvalidOverride(text()),
+            Error::InvalidApiKey {
+                key: text(),
+                reason: text(),
+            },
+            Error::UnknownKeys { keys: vec![text()] },
+            #[cfg(feature = "pem")]
+            Error::InvalidKey(text()),
+            Error::AmbiguousKey {
+                key: text(),
+                candidates: vec![text()],
+            },
+            Error::NotPresent { key: text() },
+            Error::ExtraDeserialize {
+                key: text(),
+                source: serde_json::from_str::<u8>("x").unwrap_err(),
+                backtrace: None,
+            },
+            #[cfg(feature = "gpg")]
+            Error::GpgError(gpgme::Error::GENERAL),
+            #[cfg(feature = "sss")]
+            Error::SecretReconstruction(text()),
+            #[cfg(feature = "watch")]
+            Error::Watch(notify::Error::generic("x")),
+            Error::NotReloadable,
+            #[cfg(feature = "aws")]
+            Error::AwsSecretsManager(text()),
+            #[cfg(feature = "kms")]
+            Error::Kms(text()),
+            #[cfg(feature = "vault")]
+            Error::Vault(text()),
+            #[cfg(feature = "reqwest")]
+            Error::SecretFetch {
+                url: text(),
+                reason: text(),
+            },
+            #[cfg(feature = "remote")]
+            Error::RemoteFetch {
+                url: text(),
+                reason: text(),
+            },
+            #[cfg(feature = "jwt")]
+            Error::Jwt(jsonwebtoken::errors::ErrorKind::InvalidToken.into()),
+            Error::XdgConfigNotFound,
+            Error::ProfileConfigNotFound(text()),
+            Error::ProfileNotFound {
+                profile: text(),
+                available: vec![text()],
+            },
+            Error::Workspace {
+                workspace: text(),
+                source: Box::new(Error::MissingSecret),
+            },
+            Error::WorkspaceNotFound {
+                workspace: text(),
+                available: vec![text()],
+            },
+            Error::NoDefaultWorkspace {
+                available: vec![text()],
+            },
+            Error::TomlError(toml::from_str::<toml::Table>("=").unwrap_err()),
+            Error::JsonError(serde_json::from_str::<u8>("x").unwrap_err()),
+            Error::ExtendsCycle {
+                chain: vec![text()],
+            },
+            Error::SecretPathOutsideRoot {
+                path: text(),
+                root: text(),
+            },
+            Error::InvalidPath(text()),
+            Error::UnsupportedSecretScheme(text()),
+            Error::HomeDirUnavailable { path: text() },
+            Error::NoConfigFound {
+                tried: vec![text()],
+            },
+            Error::InsecureUrl {
+                key: text(),
+                url: text(),
+            },
+            Error::InvalidTemplate {
+                template: text(),
+                placeholder: text(),
+            },
+            Error::BuiltinWebhookKeyUnavailable { mainnet: true },
+            Error::SecretCommand {
+                status: std::process::Command::new("false").status()?,
+            },
+            Error::EnvironmentMismatch {
+                url: text(),
+                mainnet: false,
+            },
+            Error::ConfigDecryption {
+                path: text(),
+                reason: text(),
+            },
+            Error::UnsupportedConfigVersion {
+                found: 3,
+                supported: 2,
+            },
+            #[cfg(feature = "global")]
+            Error::GlobalNotInitialized,
+            #[cfg(feature = "global")]
+            Error::GlobalAlreadyInitialized,
+            Error::ConflictingKeys {
+                alias: text(),
+                key: text(),
+            },
+        ];
+
+        let mut codes = std::collections::BTreeSet::new();
+        for err in &samples {
+            let code = err.code();
+            assert!(codes.insert(code), "duplicate code {code}");
+            assert!(
+                !code.is_empty()
+                    && code
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'),
+                "{code}"
+            );
+            // Exhaustive on purpose: a new variant does not compile until it
+            // is listed here, and then needs a sample above. Only errors
+            // wrapping another library's error may go without a hint.
+            let exempt = match err {
+                Error::IO(_) | Error::JsonError(_) => true,
+                #[cfg(feature = "watch")]
+                Error::Watch(_) => true,
+                #[cfg(feature = "jwt")]
+                Error::Jwt(_) => true,
+                Error::ConfigNotFound(_)
+                | Error::ConfigParseError(_)
+                | Error::Load { .. }
+                | Error::ConfigParseAt { .. }
+                | Error::MissingSecret
+                | Error::AssetNotFound { .. }
+                | Error::IOError { .. }
+                | Error::InvalidDuration(_)
+                | Error::InvalidVault { .. }
+                | Error::InvalidGlob { .. }
+                | Error::InvalidUrl { .. }
+                | Error::InvalidValue { .. }
+                | Error::InvalidOverride(_)
+                | Error::InvalidApiKey { .. }
+                | Error::UnknownKeys { .. }
+                | Error::AmbiguousKey { .. }
+                | Error::NotPresent { .. }
+                | Error::ExtraDeserialize { .. }
+                | Error::NotReloadable
+                | Error::XdgConfigNotFound
+                | Error::ProfileConfigNotFound(_)
+                | Error::ProfileNotFound { .. }
+                | Error::Workspace { .. }
+                | Error::WorkspaceNotFound { .. }
+                | Error::NoDefaultWorkspace { .. }
+                | Error::TomlError(_)
+                | Error::ExtendsCycle { .. }
+                | Error::SecretPathOutsideRoot { .. }
+                | Error::InvalidPath(_)
+                | Error::UnsupportedSecretScheme(_)
+                | Error::HomeDirUnavailable { .. }
+                | Error::NoConfigFound { .. }
+                | Error::InsecureUrl { .. }
+                | Error::InvalidTemplate { .. }
+                | Error::BuiltinWebhookKeyUnavailable { .. }
+                | Error::SecretCommand { .. }
+                | Error::EnvironmentMismatch { .. }
+                | Error::ConfigDecryption { .. }
+                | Error::UnsupportedConfigVersion { .. }
+                | Error::ConflictingKeys { .. } => false,
+                #[cfg(feature = "pem")]
+                Error::InvalidKey(_) => false,
+                #[cfg(feature = "gpg")]
+                Error::GpgError(_) => false,
+                #[cfg(feature = "sss")]
+                Error::SecretReconstruction(_) => false,
+                #[cfg(feature = "aws")]
+                Error::AwsSecretsManager(_) => false,
+                #[cfg(feature = "kms")]
+                Error::Kms(_) => false,
+                #[cfg(feature = "vault")]
+                Error::Vault(_) => false,
+                #[cfg(feature = "reqwest")]
+                Error::SecretFetch { .. } => false,
+                #[cfg(feature = "remote")]
+                Error::RemoteFetch { .. } => false,
+                #[cfg(feature = "global")]
+                Error::GlobalNotInitialized | Error::GlobalAlreadyInitialized => false,
+            };
+            assert_eq!(err.hint().is_none(), exempt, "hint of {code}");
+        }
+
+        let missing = Error::MissingSecret;
+        assert_eq!(missing.code(), "MISSING_SECRET");
+        assert!(missing.hint().unwrap().contains("FIREBLOCKS_SECRET"));
+        assert!(missing.is_user_error());
+        assert!(!Error::IO(io()).is_user_error());
+        assert!(
+            Error::IOError {
+                source: io(),
+                path: text(),
+                backtrace: None,
+            }
+            .is_user_error()
+        );
+        assert!(
+            !Error::IOError {
+                source: std::io::Error::other("disk"),
+                path: text(),
+                backtrace: None,
+            }
+            .is_user_error()
+        );
+        // workspace errors defer to the error they wrap
+        let err = Error::Workspace {
+            workspace: text(),
+            source: Box::new(Error::MissingSecret),
+        };
+        assert_eq!(err.hint(), missing.hint());
+        assert!(err.is_user_error());
+        let err = Error::InvalidValue {
+            key: "paging.page_size".to_string(),
+            value: "0".to_string(),
+            reason: text(),
+        };
+        assert!(err.hint().unwrap().contains("FIREBLOCKS_PAGING__PAGE_SIZE"));
+        Ok(())
+    }
 }
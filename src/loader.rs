@@ -0,0 +1,347 @@
+//! Raw TOML handling shared by the sync and async loaders
+use {
+    crate::{
+        Error,
+        Result,
+        config::{expand_path, has_url_scheme},
+        error::capture_backtrace,
+        migrate::migrate_file_table,
+        schema::resolve_aliases,
+    },
+    config::{Config, ConfigBuilder, File, FileFormat, FileSourceString, builder::DefaultState},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Maximum number of files allowed in an `extends` chain
+const MAX_EXTENDS_DEPTH: usize = 16;
+
+/// First line of an ASCII-armored GPG message
+const PGP_ARMOR_HEADER: &str = "-----BEGIN PGP MESSAGE-----";
+
+/// Read a TOML file, returning both the raw contents and the parsed table
+pub(crate) fn read_toml(path: &Path) -> Result<(String, toml::Table)> {
+    let contents = read_config(path)?;
+    let table = parse_toml(path, &contents)?;
+    Ok((contents, table))
+}
+
+/// Read a TOML file to layer over others, normalized by [`normalize_table`]
+pub(crate) fn read_layer(path: &Path) -> Result<toml::Table> {
+    let (_, mut table) = read_toml(path)?;
+    normalize_table(&mut table)?;
+    Ok(table)
+}
+
+/// Bring one file to the current layout before it is layered: run the
+/// migrations its `version` asks for, then move aliases to their canonical
+/// keys
+///
+/// Legacy and canonical names are only compared within the file, so a
+/// canonical key set by a later file overrides an alias in an earlier one
+/// like any other key.
+pub(crate) fn normalize_table(table: &mut toml::Table) -> Result<()> {
+    migrate_file_table(table)?;
+    resolve_aliases(table)
+}
+
+/// A config source holding `table`
+pub(crate) fn table_source(table: &toml::Table) -> Result<File<FileSourceString, FileFormat>> {
+    Ok(File::from_str(&to_toml_string(table)?, FileFormat::Toml))
+}
+
+/// Read a config file as text, decrypting it first when it is GPG encrypted
+pub(crate) fn read_config(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).map_err(|e| Error::IOError {
+        source: e,
+        path: path.to_string_lossy().to_string(),
+        backtrace: capture_backtrace(),
+    })?;
+    decode_config(path, bytes)
+}
+
+/// Read a config file like [`read_config`] with `tokio::fs`, decrypting it
+/// on the blocking thread pool
+#[cfg(feature = "async")]
+pub(crate) async fn read_config_async(path: &Path) -> Result<String> {
+    let bytes = tokio::fs::read(path).await.map_err(|e| Error::IOError {
+        source: e,
+        path: path.to_string_lossy().to_string(),
+        backtrace: capture_backtrace(),
+    })?;
+    if !is_encrypted_config(path, &bytes) {
+        return decode_config(path, bytes);
+    }
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || decode_config(&path, bytes))
+        .await
+        .map_err(std::io::Error::from)?
+}
+
+/// Turn the bytes of a config file into text, see [`read_config`]
+pub(crate) fn decode_config(path: &Path, bytes: Vec<u8>) -> Result<String> {
+    let bytes = if is_encrypted_config(path, &bytes) {
+        decrypt_config(path, &bytes)?
+    } else {
+        bytes
+    };
+    String::from_utf8(bytes).map_err(|e| Error::IOError {
+        source: std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+        path: path.to_string_lossy().to_string(),
+        backtrace: capture_backtrace(),
+    })
+}
+
+/// Whether a config file is GPG encrypted, going by a `.gpg` extension or an
+/// armored message header
+pub(crate) fn is_encrypted_config(path: &Path, contents: &[u8]) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gpg"))
+        || contents
+            .trim_ascii_start()
+            .starts_with(PGP_ARMOR_HEADER.as_bytes())
+}
+
+#[cfg(feature = "gpg")]
+fn decrypt_config(path: &Path, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    tracing::debug!("decrypting config {}", path.display());
+    let decryption_error = |e: gpgme::Error| Error::ConfigDecryption {
+        path: path.to_string_lossy().to_string(),
+        reason: e.to_string(),
+    };
+    let mut ctx =
+        gpgme::Context::from_protocol(gpgme::Protocol::OpenPgp).map_err(decryption_error)?;
+    let mut plaintext = Vec::new();
+    ctx.decrypt(ciphertext, &mut plaintext)
+        .map_err(decryption_error)?;
+    Ok(plaintext)
+}
+
+#[cfg(not(feature = "gpg"))]
+fn decrypt_config(path: &Path, _ciphertext: &[u8]) -> Result<Vec<u8>> {
+    Err(Error::ConfigDecryption {
+        path: path.to_string_lossy().to_string(),
+        reason: "enable the gpg feature to load encrypted configs".to_string(),
+    })
+}
+
+/// Parse TOML read from `path`, reporting syntax errors with the file and
+/// line/column they occur at
+pub(crate) fn parse_toml(path: &Path, contents: &str) -> Result<toml::Table> {
+    contents.parse::<toml::Table>().map_err(|e| {
+        let message = e.message().trim_end();
+        let detail = match e.span() {
+            Some(span) => {
+                let before = &contents[..span.start.min(contents.len())];
+                let line = before.matches('\n').count() + 1;
+                let column = before
+                    .rsplit('\n')
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .count()
+                    + 1;
+                format!("line {line}, column {column}: {message}")
+            }
+            None => message.to_string(),
+        };
+        Error::ConfigParseAt {
+            path: path.to_string_lossy().to_string(),
+            detail,
+            source: Box::new(e),
+            backtrace: capture_backtrace(),
+        }
+    })
+}
+
+pub(crate) fn to_toml_string(table: &toml::Table) -> Result<String> {
+    toml::to_string(table).map_err(|e| {
+        Error::IO(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Failed to serialize config table: {e}"),
+        ))
+    })
+}
+
+/// Remove the `[profiles]` table, keeping only entries that are tables
+pub(crate) fn take_profiles(table: &mut toml::Table) -> toml::Table {
+    match table.remove("profiles") {
+        Some(toml::Value::Table(profiles)) => {
+            profiles.into_iter().filter(|(_, v)| v.is_table()).collect()
+        }
+        _ => toml::Table::new(),
+    }
+}
+
+/// Files visited while following `extends` keys
+///
+/// `extends` is resolved relative to the directory of the file declaring it
+/// and may start with `~`. Within a chain, relative `secret_path` values are
+/// rewritten against the directory of the file that declared them.
+#[derive(Default)]
+pub(crate) struct ExtendsChain {
+    visited: Vec<PathBuf>,
+    layers: Vec<(PathBuf, toml::Table)>,
+}
+
+impl ExtendsChain {
+    /// Record the canonical path of the next file, rejecting cycles and
+    /// chains deeper than [`MAX_EXTENDS_DEPTH`]
+    pub(crate) fn visit(&mut self, canonical: PathBuf) -> Result<()> {
+        let cyclic = self.visited.contains(&canonical) || self.visited.len() >= MAX_EXTENDS_DEPTH;
+        self.visited.push(canonical);
+        if cyclic {
+            return Err(Error::ExtendsCycle {
+                chain: self
+                    .visited
+                    .iter()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .collect(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Add the contents of `path`, returning the parent file it extends
+    pub(crate) fn push(&mut self, path: &Path, contents: &str) -> Result<Option<PathBuf>> {
+        let mut table = parse_toml(path, contents)?;
+        normalize_table(&mut table)?;
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let parent = match table.remove("extends") {
+            Some(toml::Value::String(parent)) => {
+                tracing::debug!("{} extends {parent}", path.display());
+                Some(dir.join(expand_path(&parent)?))
+            }
+            Some(other) => {
+                return Err(Error::InvalidValue {
+                    key: "extends".to_string(),
+                    value: other.to_string(),
+                    reason: format!("expected a string in {}", path.display()),
+                });
+            }
+            None => None,
+        };
+        self.layers.push((dir, table));
+        Ok(parent)
+    }
+
+    /// Parsed tables ordered from the root ancestor down to the first file
+    ///
+    /// Fails with [`Error::InvalidPath`] when a rewritten `secret_path` is not
+    /// valid UTF-8.
+    pub(crate) fn into_layers(mut self) -> Result<Vec<toml::Table>> {
+        if self.layers.len() > 1 {
+            for (dir, table) in &mut self.layers {
+                if let Some(toml::Value::String(secret_path)) = table.get_mut("secret_path")
+                    // URLs and `~` paths are never rebased, even when the
+                    // home directory is unknown
+                    && !has_url_scheme(secret_path)
+                    && expand_path(&*secret_path).is_ok_and(|path| path.is_relative())
+                {
+                    let rebased = dir.join(&*secret_path);
+                    *secret_path = rebased
+                        .to_str()
+                        .ok_or_else(|| Error::InvalidPath(rebased.to_string_lossy().to_string()))?
+                        .to_string();
+                }
+            }
+        }
+        Ok(self
+            .layers
+            .into_iter()
+            .rev()
+            .map(|(_, table)| table)
+            .collect())
+    }
+}
+
+/// Start a config builder with the `extends` layers of the base file
+pub(crate) fn layered_builder(layers: Vec<toml::Table>) -> Result<ConfigBuilder<DefaultState>> {
+    let mut config_builder = Config::builder();
+    for layer in layers {
+        config_builder = config_builder.add_source(table_source(&layer)?);
+    }
+    Ok(config_builder)
+}
+
+/// Build an override layer from `key=value` strings
+///
+/// Keys are dotted paths such as `signer.vault`. `true`/`false` become
+/// booleans, numeric values become numbers and everything else is kept as a
+/// string.
+pub(crate) fn kv_layer<S: AsRef<str>>(pairs: &[S]) -> Result<Config> {
+    let mut config_builder = Config::builder();
+    for pair in pairs {
+        let pair = pair.as_ref();
+        let invalid = || Error::InvalidOverride(pair.to_string());
+        let (key, value) = pair
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(invalid)?;
+        config_builder = config_builder
+            .set_override(key, coerce_kv_value(value))
+            .map_err(|_| invalid())?;
+    }
+    config_builder.build().map_err(|source| Error::Load {
+        sources: vec!["key=value overrides".to_string()],
+        source: Box::new(source),
+        backtrace: capture_backtrace(),
+    })
+}
+
+fn coerce_kv_value(value: &str) -> config::Value {
+    match value {
+        "true" => true.into(),
+        "false" => false.into(),
+        _ => {
+            if let Ok(int) = value.parse::<i64>() {
+                int.into()
+            } else if let Ok(float) = value.parse::<f64>()
+                && float.is_finite()
+                && value.bytes().any(|b| b.is_ascii_digit())
+            {
+                float.into()
+            } else {
+                value.into()
+            }
+        }
+    }
+}
+
+/// Follow the `extends` chain of a config file
+pub(crate) fn resolve_extends(path: &Path) -> Result<Vec<toml::Table>> {
+    let mut chain = ExtendsChain::default();
+    let mut next = Some(path.to_path_buf());
+    while let Some(current) = next.take() {
+        let io_error = |e| Error::IOError {
+            source: e,
+            path: current.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        };
+        chain.visit(fs::canonicalize(&current).map_err(io_error)?)?;
+        let contents = read_config(&current)?;
+        next = chain.push(&current, &contents)?;
+    }
+    chain.into_layers()
+}
+
+/// Follow the `extends` chain of a config file using `tokio::fs`
+#[cfg(feature = "async")]
+pub(crate) async fn resolve_extends_async(path: &Path) -> Result<Vec<toml::Table>> {
+    let mut chain = ExtendsChain::default();
+    let mut next = Some(path.to_path_buf());
+    while let Some(current) = next.take() {
+        let io_error = |e| Error::IOError {
+            source: e,
+            path: current.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        };
+        chain.visit(tokio::fs::canonicalize(&current).await.map_err(io_error)?)?;
+        let contents = read_config_async(&current).await?;
+        next = chain.push(&current, &contents)?;
+    }
+    chain.into_layers()
+}
@@ -0,0 +1,261 @@
+//! Config layout versions and the migrations between them
+use {
+    crate::{
+        Error,
+        FireblocksConfig,
+        Result,
+        error::capture_backtrace,
+        loader::{decode_config, is_encrypted_config, parse_toml, to_toml_string},
+    },
+    config::{Map, Value, ValueKind},
+    std::path::Path,
+};
+
+/// The key operations renames need, over both the merged sources and a
+/// single TOML file
+pub(crate) trait KeyTable {
+    type Value;
+
+    /// The table under the root key `name`, if it is one
+    fn section_mut(&mut self, name: &str) -> Option<&mut Self>;
+    fn remove_key(&mut self, key: &str) -> Option<Self::Value>;
+    fn get_key(&self, key: &str) -> Option<&Self::Value>;
+    fn insert_key(&mut self, key: String, value: Self::Value);
+    fn same_value(a: &Self::Value, b: &Self::Value) -> bool;
+    /// Whether both values were set by the same source, always the case
+    /// within one file
+    fn same_source(a: &Self::Value, b: &Self::Value) -> bool;
+
+    /// Move the value of the dotted key `from` to `to`, returning whether it
+    /// moved
+    ///
+    /// When `to` is already set `from` is dropped, unless one source set both
+    /// to different values, which is an [`Error::ConflictingKeys`].
+    fn rename(&mut self, from: &str, to: &str) -> Result<bool>
+    where
+        Self: Sized,
+    {
+        let (table, from_name, to_name) = match (from.rsplit_once('.'), to.rsplit_once('.')) {
+            (Some((section, from_name)), Some((_, to_name))) => match self.section_mut(section) {
+                Some(table) => (table, from_name, to_name),
+                None => return Ok(false),
+            },
+            _ => (self, from, to),
+        };
+        let Some(value) = table.remove_key(from_name) else {
+            return Ok(false);
+        };
+        match table.get_key(to_name) {
+            Some(current) if Self::same_value(current, &value) => Ok(false),
+            Some(current) if Self::same_source(current, &value) => Err(Error::ConflictingKeys {
+                alias: from.to_string(),
+                key: to.to_string(),
+            }),
+            Some(_) => {
+                tracing::warn!("ignoring {from}, {to} is also set");
+                Ok(false)
+            }
+            None => {
+                table.insert_key(to_name.to_string(), value);
+                Ok(true)
+            }
+        }
+    }
+}
+
+impl KeyTable for Map<String, Value> {
+    type Value = Value;
+
+    fn section_mut(&mut self, name: &str) -> Option<&mut Self> {
+        match self.get_mut(name).map(|value| &mut value.kind) {
+            Some(ValueKind::Table(section)) => Some(section),
+            _ => None,
+        }
+    }
+
+    fn remove_key(&mut self, key: &str) -> Option<Value> {
+        self.remove(key)
+    }
+
+    fn get_key(&self, key: &str) -> Option<&Value> {
+        self.get(key)
+    }
+
+    fn insert_key(&mut self, key: String, value: Value) {
+        self.insert(key, value);
+    }
+
+    fn same_value(a: &Value, b: &Value) -> bool {
+        a.kind == b.kind
+    }
+
+    fn same_source(a: &Value, b: &Value) -> bool {
+        a.origin() == b.origin()
+    }
+}
+
+impl KeyTable for toml::Table {
+    type Value = toml::Value;
+
+    fn section_mut(&mut self, name: &str) -> Option<&mut Self> {
+        self.get_mut(name).and_then(toml::Value::as_table_mut)
+    }
+
+    fn remove_key(&mut self, key: &str) -> Option<toml::Value> {
+        self.remove(key)
+    }
+
+    fn get_key(&self, key: &str) -> Option<&toml::Value> {
+        self.get(key)
+    }
+
+    fn insert_key(&mut self, key: String, value: toml::Value) {
+        self.insert(key, value);
+    }
+
+    fn same_value(a: &toml::Value, b: &toml::Value) -> bool {
+        a == b
+    }
+
+    fn same_source(_: &toml::Value, _: &toml::Value) -> bool {
+        true
+    }
+}
+
+/// `MIGRATIONS[n]` lists the keys renamed moving a table from version `n + 1`
+/// to `n + 2`
+const MIGRATIONS: &[&[(&str, &str)]] = &[
+    // Version 2 settled on `secret` and `secret_path` for the signing key
+    &[("secret_key", "secret"), ("secret_key_path", "secret_path")],
+];
+
+/// Check `found` against [`FireblocksConfig::CURRENT_VERSION`] and run the
+/// migrations it is missing
+fn migrate<T: KeyTable>(found: u32, table: &mut T) -> Result<()> {
+    let supported = FireblocksConfig::CURRENT_VERSION;
+    if found > supported {
+        return Err(Error::UnsupportedConfigVersion { found, supported });
+    }
+    for renames in &MIGRATIONS[found as usize - 1..] {
+        for (from, to) in *renames {
+            table.rename(from, to)?;
+        }
+    }
+    Ok(())
+}
+
+/// A `version` value, which starts at 1
+fn version(value: i64) -> Result<u32> {
+    u32::try_from(value)
+        .ok()
+        .filter(|v| *v > 0)
+        .ok_or_else(|| Error::InvalidValue {
+            key: "version".to_string(),
+            value: value.to_string(),
+            reason: "expected a positive integer".to_string(),
+        })
+}
+
+/// Bring the merged sources up to [`FireblocksConfig::CURRENT_VERSION`],
+/// warning when they are older
+pub(crate) fn migrate_table(table: &mut Map<String, Value>) -> Result<()> {
+    let found = match table.get("version") {
+        Some(value) => version(value.clone().into_int().map_err(|e| Error::InvalidValue {
+            key: "version".to_string(),
+            value: value.to_string(),
+            reason: e.to_string(),
+        })?)?,
+        None => 1,
+    };
+    migrate(found, table)?;
+    if found < FireblocksConfig::CURRENT_VERSION {
+        tracing::warn!(
+            found,
+            current = FireblocksConfig::CURRENT_VERSION,
+            "config version {found} is outdated, see FireblocksConfig::migrate_file"
+        );
+        table.insert(
+            "version".to_string(),
+            Value::new(
+                None,
+                ValueKind::U64(FireblocksConfig::CURRENT_VERSION.into()),
+            ),
+        );
+    }
+    Ok(())
+}
+
+/// Bring a single file's table up to [`FireblocksConfig::CURRENT_VERSION`],
+/// returning the version it declared
+///
+/// The `version` key is left as found, the merged sources are checked again
+/// by [`migrate_table`].
+pub(crate) fn migrate_file_table(table: &mut toml::Table) -> Result<u32> {
+    let found = match table.get("version") {
+        Some(toml::Value::Integer(n)) => version(*n)?,
+        Some(other) => {
+            return Err(Error::InvalidValue {
+                key: "version".to_string(),
+                value: other.to_string(),
+                reason: "expected a positive integer".to_string(),
+            });
+        }
+        None => 1,
+    };
+    migrate(found, table)?;
+    Ok(found)
+}
+
+impl FireblocksConfig {
+    /// Layout version written by [`FireblocksConfig::migrate_file`], files
+    /// without a `version` key are version 1
+    pub const CURRENT_VERSION: u32 = 2;
+
+    /// Rewrite a config file in place at
+    /// [`FireblocksConfig::CURRENT_VERSION`]
+    ///
+    /// Loading migrates older files on the fly, this makes the change
+    /// permanent. A file already at the current version is left untouched.
+    /// Rewriting drops comments and sorts keys, and GPG encrypted files are
+    /// refused rather than written back in plain text.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// FireblocksConfig::migrate_file("config.toml")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn migrate_file<P: AsRef<Path>>(path: P) -> Result<()> {
+        let path = path.as_ref();
+        let io_error = |e: std::io::Error| Error::IOError {
+            source: e,
+            path: path.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        };
+        let bytes = std::fs::read(path).map_err(io_error)?;
+        if is_encrypted_config(path, &bytes) {
+            return Err(io_error(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "refusing to write an encrypted config back in plain text",
+            )));
+        }
+        let contents = decode_config(path, bytes)?;
+        let mut table = parse_toml(path, &contents)?;
+        let found = migrate_file_table(&mut table)?;
+        if found == Self::CURRENT_VERSION {
+            return Ok(());
+        }
+        table.insert(
+            "version".to_string(),
+            toml::Value::Integer(Self::CURRENT_VERSION.into()),
+        );
+        std::fs::write(path, to_toml_string(&table)?).map_err(io_error)?;
+        tracing::info!(
+            "migrated {} from version {found} to {}",
+            path.display(),
+            Self::CURRENT_VERSION
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+//! Loading a config served over HTTP(S), enabled with the `remote` feature
+use {
+    crate::{Error, FireblocksConfig, HttpConfig, Result},
+    config::FileFormat,
+};
+
+/// The format of a fetched config, from its `Content-Type` when that names
+/// one, else from the URL's extension, else TOML
+pub(crate) fn file_format(content_type: Option<&str>, url: &url::Url) -> FileFormat {
+    let essence = content_type
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase());
+    match essence.as_deref() {
+        Some("application/toml" | "text/toml" | "text/x-toml") => return FileFormat::Toml,
+        Some("application/json" | "text/json") => return FileFormat::Json,
+        Some(essence) if essence.ends_with("+json") => return FileFormat::Json,
+        Some("application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml") => {
+            return FileFormat::Yaml;
+        }
+        _ => {}
+    }
+    let extension = url
+        .path()
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("json") => FileFormat::Json,
+        Some("yaml" | "yml") => FileFormat::Yaml,
+        _ => FileFormat::Toml,
+    }
+}
+
+impl FireblocksConfig {
+    /// Load configuration served at `url`, then apply the environment overlay
+    ///
+    /// The format comes from the response's `Content-Type`, e.g.
+    /// `application/json`, falling back to the URL's extension and then TOML.
+    /// The request uses the default [`HttpConfig`] timeouts. Failing to fetch
+    /// the body is an [`Error::RemoteFetch`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let config = FireblocksConfig::from_url("https://config.internal/fireblocks.toml")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_url(url: &str) -> Result<Self> {
+        let fetch_error = |reason: String| Error::RemoteFetch {
+            url: url.to_string(),
+            reason,
+        };
+        let parsed = url::Url::parse(url).map_err(|e| fetch_error(e.to_string()))?;
+        tracing::debug!("fetching config {parsed}");
+        let response = HttpConfig::default()
+            .blocking_client_builder()?
+            .build()
+            .and_then(|client| client.get(parsed.clone()).send())
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| fetch_error(e.to_string()))?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.text().map_err(|e| fetch_error(e.to_string()))?;
+        let format = file_format(content_type.as_deref(), &parsed);
+        Self::from_contents(&body, format)
+    }
+}
@@ -0,0 +1,117 @@
+use {
+    crate::{
+        FireblocksConfig,
+        OutputFormat,
+        Result,
+        diff::{REDACTED, flatten},
+        schema::FIELDS,
+    },
+    serde_json::Value,
+    std::collections::BTreeMap,
+};
+
+/// Render whole seconds as e.g. `45s`, `2m` or `1h30m`
+fn human_duration(mut secs: u64) -> String {
+    if secs == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    for (unit, size) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        if secs >= size {
+            out.push_str(&format!("{}{unit}", secs / size));
+            secs %= size;
+        }
+    }
+    out
+}
+
+/// Rebuild objects with their keys in sorted order
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let map: BTreeMap<String, Value> =
+                map.into_iter().map(|(k, v)| (k, sorted(v))).collect();
+            Value::Object(map.into_iter().collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sorted).collect()),
+        value => value,
+    }
+}
+
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+impl FireblocksConfig {
+    /// The config as shown to users: secrets redacted, the API key masked,
+    /// `secret_path` expanded and durations in human form
+    fn display_value(&self) -> Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        value["api_key"] = Value::from(self.api_key.to_string());
+        if self.secret.is_some() {
+            value["secret"] = Value::from(REDACTED);
+        }
+        if self.secret_shares.is_some() {
+            value["secret_shares"] = Value::from(REDACTED);
+        }
+        if self.secret_path.is_some() {
+            let path = self.expanded_secret_path()?;
+            value["secret_path"] = Value::from(path.to_string_lossy());
+        }
+        for field in FIELDS.iter().filter(|f| f.type_name.contains("Duration")) {
+            let pointer = format!("/{}", field.key.replace('.', "/"));
+            if let Some(slot) = value.pointer_mut(&pointer)
+                && let Some(secs) = slot.as_str().and_then(|s| s.parse::<f64>().ok())
+            {
+                *slot = Value::from(if secs.fract() == 0.0 {
+                    human_duration(secs as u64)
+                } else {
+                    format!("{secs}s")
+                });
+            }
+        }
+        Ok(sorted(value))
+    }
+
+    /// Render the effective config for a `config show` style command
+    ///
+    /// Every field is included, with `secret` and `secret_shares` replaced by
+    /// `<redacted>`, `api_key` masked like its `Display`, `secret_path`
+    /// expanded and durations shown as e.g. `2m`.
+    /// [`OutputFormat::Table`] aligns dotted keys and values,
+    /// [`OutputFormat::Tsv`] separates them with a tab and
+    /// [`OutputFormat::Json`] keeps the nesting. Keys are always sorted.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::{FireblocksConfig, OutputFormat};
+    ///
+    /// let config = FireblocksConfig::new("config.toml", &[])?;
+    /// println!("{}", config.to_display_string(OutputFormat::Table)?);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn to_display_string(&self, format: OutputFormat) -> Result<String> {
+        let value = self.display_value()?;
+        if format == OutputFormat::Json {
+            return Ok(serde_json::to_string_pretty(&value)?);
+        }
+
+        let mut flat = BTreeMap::new();
+        flatten("", value, &mut flat);
+        let width = flat.keys().map(String::len).max().unwrap_or_default();
+        let mut out = String::new();
+        for (key, value) in &flat {
+            let value = render_value(value);
+            let line = match format {
+                OutputFormat::Tsv => format!("{key}\t{value}"),
+                _ => format!("{key:<width$}  {value}"),
+            };
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
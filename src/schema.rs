@@ -0,0 +1,599 @@
+use {
+    crate::{FireblocksConfig, migrate::KeyTable},
+    config::{Map, Value, ValueKind},
+    serde::Serialize,
+};
+
+/// A recognized config key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct FieldDescriptor {
+    /// Dotted key, e.g. `signer.poll_timeout`
+    pub key: &'static str,
+    /// Rust type of the field
+    pub type_name: &'static str,
+    /// Whether the key may be left out
+    pub optional: bool,
+    /// Value used when the key is left out, `None` when there is none
+    pub default: Option<&'static str>,
+    /// What the key does
+    pub doc: &'static str,
+}
+
+const fn field(
+    key: &'static str,
+    type_name: &'static str,
+    optional: bool,
+    default: Option<&'static str>,
+    doc: &'static str,
+) -> FieldDescriptor {
+    FieldDescriptor {
+        key,
+        type_name,
+        optional,
+        default,
+        doc,
+    }
+}
+
+/// Every key [`FireblocksConfig`] understands, in file order
+///
+/// Kept in sync with the structs by `test_schema` in `lib.rs`.
+pub(crate) const FIELDS: &[FieldDescriptor] = &[
+    field(
+        "version",
+        "u32",
+        true,
+        None,
+        "Layout version of the config, 1 when left out. Older files are migrated on load",
+    ),
+    field("api_key", "ApiKey", false, None, "Fireblocks API key"),
+    field(
+        "allow_nonstandard_api_key",
+        "bool",
+        true,
+        Some("false"),
+        "Accept API keys that are not UUIDs, for on-prem setups",
+    ),
+    field(
+        "url",
+        "String",
+        true,
+        None,
+        "API base URL, defaults to the production or sandbox API based on mainnet",
+    ),
+    field(
+        "require_https",
+        "bool",
+        true,
+        Some("true"),
+        "Reject a plain http url unless it points at localhost",
+    ),
+    field(
+        "secret_path",
+        "Option<PathBuf>",
+        true,
+        None,
+        "Path or file/https URL of the private key, may be GPG encrypted",
+    ),
+    field(
+        "secret_url_auth_env",
+        "Option<String>",
+        true,
+        None,
+        "Environment variable holding a bearer token for an https secret_path",
+    ),
+    field(
+        "secret_path_root",
+        "Option<PathBuf>",
+        true,
+        None,
+        "Directory secret_path must resolve into, symlinks included",
+    ),
+    field(
+        "secret",
+        "Option<String>",
+        true,
+        None,
+        "The private key itself",
+    ),
+    field(
+        "secret_command",
+        "Option<String>",
+        true,
+        None,
+        "Shell command printing the private key, run after secret and before secret_path",
+    ),
+    field(
+        "secret_shares",
+        "Option<Vec<String>>",
+        true,
+        None,
+        "Hex encoded Shamir secret shares of the private key",
+    ),
+    field(
+        "secret_aws_arn",
+        "Option<String>",
+        true,
+        None,
+        "ARN of an AWS Secrets Manager secret holding the private key",
+    ),
+    field(
+        "secret_kms_ciphertext",
+        "Option<String>",
+        true,
+        None,
+        "Base64 private key encrypted under a KMS key",
+    ),
+    field(
+        "secret_kms_key_id",
+        "Option<String>",
+        true,
+        None,
+        "KMS key the ciphertext was encrypted under",
+    ),
+    field(
+        "vault_addr",
+        "Option<String>",
+        true,
+        None,
+        "HashiCorp Vault server address",
+    ),
+    field(
+        "vault_token_env",
+        "Option<String>",
+        true,
+        Some("VAULT_TOKEN"),
+        "Environment variable holding the Vault token",
+    ),
+    field(
+        "vault_secret_path",
+        "Option<String>",
+        true,
+        None,
+        "Vault KV v2 secret holding the private key as <mount>/<path>[#field]",
+    ),
+    field(
+        "webhook_public_key",
+        "Option<String>",
+        true,
+        None,
+        "PEM public key verifying Fireblocks webhooks, or \"builtin\"",
+    ),
+    field(
+        "webhook_public_key_path",
+        "Option<PathBuf>",
+        true,
+        None,
+        "File holding the webhook public key",
+    ),
+    field(
+        "treat_unknown_as_error",
+        "bool",
+        true,
+        Some("false"),
+        "Fail loading when the config has keys that are not listed here",
+    ),
+    field(
+        "jwt_ttl",
+        "Option<Duration>",
+        true,
+        Some("30"),
+        "Lifetime of signed request tokens in seconds",
+    ),
+    field(
+        "display.output",
+        "OutputFormat",
+        true,
+        Some("Table"),
+        "Output format: Table, Tsv or Json",
+    ),
+    field(
+        "signer.poll_timeout",
+        "Duration",
+        true,
+        Some("180"),
+        "How long to poll for a transaction in seconds, zero or \"none\" disables polling",
+    ),
+    field(
+        "signer.poll_interval",
+        "Duration",
+        true,
+        Some("5"),
+        "Delay between polls in seconds, greater than zero and at most poll_timeout",
+    ),
+    field(
+        "signer.poll_backoff",
+        "Option<f64>",
+        true,
+        Some("1.0"),
+        "Multiplier applied to poll_interval after each attempt",
+    ),
+    field(
+        "signer.poll_max_interval",
+        "Option<Duration>",
+        true,
+        None,
+        "Upper bound for the backed-off poll interval in seconds",
+    ),
+    field(
+        "signer.vault",
+        "String",
+        true,
+        None,
+        "The vault id, checked by validate_all",
+    ),
+    field(
+        "signer.broadcast",
+        "bool",
+        true,
+        Some("false"),
+        "Have Fireblocks broadcast the transaction",
+    ),
+    field(
+        "signer.sign_only",
+        "bool",
+        true,
+        Some("false"),
+        "Only produce a signature and never broadcast the transaction",
+    ),
+    field(
+        "signer.max_fee",
+        "Option<String>",
+        true,
+        None,
+        "Highest fee a transaction may pay, as a non-negative decimal string",
+    ),
+    field(
+        "signer.fee_level",
+        "Option<FeeLevel>",
+        true,
+        None,
+        "Fee level for new transactions: Low, Medium or High",
+    ),
+    field(
+        "signer.confirmations",
+        "Option<u32>",
+        true,
+        None,
+        "Confirmations to wait for before a transaction counts as completed",
+    ),
+    field(
+        "signer.fail_on_fee_exceeded",
+        "bool",
+        true,
+        Some("true"),
+        "Reject transactions whose fee exceeds max_fee",
+    ),
+    field(
+        "signer.external_tx_id_template",
+        "Option<String>",
+        true,
+        None,
+        "externalTxId template using {uuid}, {timestamp}, {vault} and {nonce}",
+    ),
+    field(
+        "retry.max_retries",
+        "u32",
+        true,
+        Some("5"),
+        "Number of retries after the first attempt",
+    ),
+    field(
+        "retry.initial_backoff",
+        "Duration",
+        true,
+        Some("1"),
+        "Delay before the first retry in seconds",
+    ),
+    field(
+        "retry.max_backoff",
+        "Duration",
+        true,
+        Some("30"),
+        "Upper bound for any single retry delay in seconds",
+    ),
+    field(
+        "retry.backoff_multiplier",
+        "f64",
+        true,
+        Some("2.0"),
+        "Factor applied to the retry delay after each retry",
+    ),
+    field(
+        "retry.retry_on_rate_limit",
+        "bool",
+        true,
+        Some("true"),
+        "Retry requests rejected with HTTP 429",
+    ),
+    field(
+        "paging.page_size",
+        "u32",
+        true,
+        Some("200"),
+        "Items requested per page by listing operations",
+    ),
+    field(
+        "paging.max_items",
+        "Option<u32>",
+        true,
+        None,
+        "Stop listing after this many items, at least page_size",
+    ),
+    field(
+        "paging.order",
+        "SortOrder",
+        true,
+        Some("desc"),
+        "Order of listed items: asc or desc",
+    ),
+    field(
+        "http.connect_timeout",
+        "Duration",
+        true,
+        Some("10"),
+        "Timeout for establishing a connection in seconds",
+    ),
+    field(
+        "http.request_timeout",
+        "Duration",
+        true,
+        Some("30"),
+        "Timeout for a whole request in seconds",
+    ),
+    field(
+        "http.max_concurrency",
+        "usize",
+        true,
+        Some("8"),
+        "Upper bound for requests in flight at once, must be greater than zero",
+    ),
+    field(
+        "http.proxy",
+        "Option<String>",
+        true,
+        None,
+        "Proxy URL for all requests",
+    ),
+    field(
+        "http.no_proxy",
+        "Option<Vec<String>>",
+        true,
+        None,
+        "Hosts, domains or CIDR ranges reached without the proxy",
+    ),
+    field(
+        "http.user_agent",
+        "Option<String>",
+        true,
+        None,
+        "User-Agent header sent with every request",
+    ),
+    field(
+        "http.accept_invalid_certs",
+        "bool",
+        true,
+        Some("false"),
+        "Skip TLS certificate validation",
+    ),
+    field(
+        "network.rpc_url",
+        "Option<String>",
+        true,
+        None,
+        "JSON-RPC endpoint of the blockchain network",
+    ),
+    field(
+        "network.ws_url",
+        "Option<String>",
+        true,
+        None,
+        "Websocket endpoint of the blockchain network",
+    ),
+    field(
+        "network.commitment",
+        "Option<String>",
+        true,
+        None,
+        "Solana commitment level",
+    ),
+    field(
+        "network.chain_id",
+        "Option<u64>",
+        true,
+        None,
+        "EVM chain id",
+    ),
+    field(
+        "network.explorer_url",
+        "Option<String>",
+        true,
+        None,
+        "Block explorer base URL",
+    ),
+    field(
+        "callback.public_key_path",
+        "Option<PathBuf>",
+        true,
+        None,
+        "Public key verifying co-signer callbacks",
+    ),
+    field(
+        "callback.listen",
+        "Option<String>",
+        true,
+        None,
+        "Address the callback handler listens on as host:port",
+    ),
+    field(
+        "callback.allowed_ips",
+        "Vec<String>",
+        true,
+        Some("[]"),
+        "IP addresses or CIDR ranges allowed to call the handler",
+    ),
+    field(
+        "extra",
+        "HashMap<String, Value>",
+        true,
+        Some("{}"),
+        "Arbitrary extra configuration values",
+    ),
+    field("debug", "bool", true, Some("false"), "Enable debug mode"),
+    field(
+        "log_level",
+        "Option<String>",
+        true,
+        None,
+        "Log verbosity: error, warn, info, debug or trace, overrides debug",
+    ),
+    field(
+        "mainnet",
+        "bool",
+        true,
+        Some("false"),
+        "Use the production API when url is not set",
+    ),
+    field(
+        "asset_test_suffix",
+        "String",
+        true,
+        Some("_TEST"),
+        "Suffix appended to asset ids when mainnet is false",
+    ),
+    field(
+        "asset_overrides",
+        "HashMap<String, String>",
+        true,
+        Some("{}"),
+        "Testnet asset ids by base asset where the suffix rule does not apply",
+    ),
+    field(
+        "assets_allowed",
+        "Vec<String>",
+        true,
+        Some("[]"),
+        "Base assets require_asset accepts, empty allows any",
+    ),
+];
+
+/// Root keys consumed by the loader rather than by a field
+const LOADER_KEYS: &[&str] = &["extends", "profiles"];
+
+/// Alternative names of root keys, as written by other Fireblocks tooling
+pub(crate) const ALIASES: &[(&str, &str)] = &[
+    ("apiKey", "api_key"),
+    ("base_url", "url"),
+    ("baseUrl", "url"),
+    ("secretPath", "secret_path"),
+    ("private_key_path", "secret_path"),
+    ("key_path", "secret_path"),
+    ("secretKey", "secret"),
+];
+
+/// Suffix accepted on every duration key, e.g. `signer.poll_timeout_secs`
+const SECS_SUFFIX: &str = "_secs";
+
+/// Every dotted alias with its canonical key: [`ALIASES`] plus the
+/// [`SECS_SUFFIX`] spelling of each duration
+fn aliases() -> impl Iterator<Item = (String, &'static str)> {
+    ALIASES
+        .iter()
+        .map(|(alias, key)| (alias.to_string(), *key))
+        .chain(
+            FIELDS
+                .iter()
+                .filter(|f| f.type_name.contains("Duration"))
+                .map(|f| (format!("{}{SECS_SUFFIX}", f.key), f.key)),
+        )
+}
+
+/// Move values set under an alias to their canonical key, warning that the
+/// alias is a legacy name
+///
+/// Each file is resolved on its own before it is layered, so a canonical key
+/// in one file wins over an alias in another. The merged sources are resolved
+/// again for the environment and in-memory sources, where the canonical key
+/// wins when both names are set by different sources, e.g. `FIREBLOCKS_URL`
+/// over `baseUrl`. Both set to different values by the same source is an
+/// [`Error::ConflictingKeys`](crate::Error::ConflictingKeys).
+///
+/// `secret_key` and `secret_key_path` are renamed by the version 2 migration
+/// rather than here.
+pub(crate) fn resolve_aliases<T: KeyTable>(table: &mut T) -> crate::Result<()> {
+    for (alias, key) in aliases() {
+        if table.rename(&alias, key)? {
+            tracing::warn!("{alias} is a legacy name, use {key}");
+        }
+    }
+    Ok(())
+}
+
+/// Keys of the merged sources that no field recognizes, with the origin of
+/// each when known
+///
+/// Only the root table and the sections of [`FIELDS`] are checked, `[extra]`
+/// takes any key.
+pub(crate) fn unknown_keys(table: &Map<String, Value>) -> Vec<(String, Option<String>)> {
+    let mut unknown = Vec::new();
+    for (key, value) in table {
+        if FIELDS.iter().any(|f| f.key == key) || LOADER_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let prefix = format!("{key}.");
+        let is_section = FIELDS.iter().any(|f| f.key.starts_with(&prefix));
+        match &value.kind {
+            ValueKind::Table(section) if is_section => {
+                for (sub, value) in section {
+                    let dotted = format!("{prefix}{sub}");
+                    if !FIELDS.iter().any(|f| f.key == dotted) {
+                        unknown.push((dotted, value.origin().map(str::to_string)));
+                    }
+                }
+            }
+            _ if is_section => {}
+            _ => unknown.push((key.clone(), value.origin().map(str::to_string))),
+        }
+    }
+    unknown.sort();
+    unknown
+}
+
+impl FireblocksConfig {
+    /// Describe every recognized config key, e.g. for `config keys` commands
+    /// and shell completion
+    pub fn schema() -> Vec<FieldDescriptor> {
+        FIELDS.to_vec()
+    }
+
+    /// JSON Schema of the config file
+    #[cfg(feature = "schema")]
+    pub fn schema_json() -> serde_json::Value {
+        schemars::schema_for!(FireblocksConfig).to_value()
+    }
+
+    /// [`FireblocksConfig::schema_json`] pretty printed, ready to be written
+    /// out for editors
+    #[cfg(feature = "schema")]
+    pub fn json_schema() -> String {
+        format!("{:#}", Self::schema_json())
+    }
+}
+
+/// Durations are seconds, written as a number or a numeric string
+#[cfg(feature = "schema")]
+pub(crate) fn duration_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+        "type": ["number", "string"],
+        "description": "Seconds, e.g. 30, 1.5 or \"30\""
+    })
+}
+
+/// [`duration_schema`] for optional durations
+#[cfg(feature = "schema")]
+pub(crate) fn optional_duration_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+        "type": ["number", "string", "null"],
+        "description": "Seconds, e.g. 30, 1.5 or \"30\""
+    })
+}
@@ -0,0 +1,191 @@
+//! Fixtures for tests of crates using [`FireblocksConfig`], enabled with the
+//! `test-util` feature
+//!
+//! ```rust
+//! use fireblocks_config::testing::{EnvVars, TempConfig, fake_config_with};
+//!
+//! let config = fake_config_with(|c| c.signer.vault = "7".to_string());
+//! assert_eq!(config.signer.vault_id()?, 7);
+//!
+//! let temp = TempConfig::new()?;
+//! let mut env = EnvVars::lock();
+//! env.set("FIREBLOCKS_SIGNER__VAULT", "3");
+//! let config = temp.load()?;
+//! assert_eq!(config.signer.vault, "3");
+//! assert!(!config.get_key()?.is_empty());
+//! # Ok::<(), fireblocks_config::Error>(())
+//! ```
+use {
+    crate::{Error, FireblocksConfig, Result, Signer, error::capture_backtrace},
+    std::{
+        ffi::{OsStr, OsString},
+        path::{Path, PathBuf},
+        sync::{Mutex, MutexGuard, PoisonError},
+    },
+};
+
+/// A well-formed API key that belongs to no account
+pub const FAKE_API_KEY: &str = "00000000-0000-4000-8000-000000000000";
+
+/// A throwaway 2048 bit RSA private key in PKCS#8 PEM form
+pub const FAKE_RSA_KEY: &str = include_str!("../examples/test.pem");
+
+const SANDBOX_URL: &str = "https://sandbox-api.fireblocks.io/v1";
+
+/// A valid sandbox config with [`FAKE_API_KEY`], [`FAKE_RSA_KEY`] as the
+/// inline `secret` and vault `0`
+///
+/// Nothing is read from files or the environment.
+pub fn fake_config() -> FireblocksConfig {
+    fake_config_with(|_| {})
+}
+
+/// [`fake_config`] with `edit` applied
+///
+/// # Panics
+///
+/// When the edited config fails [`FireblocksConfig::validate`].
+pub fn fake_config_with(edit: impl FnOnce(&mut FireblocksConfig)) -> FireblocksConfig {
+    let mut config = FireblocksConfig::default();
+    config.api_key = FAKE_API_KEY.into();
+    config.url = SANDBOX_URL.to_string();
+    config.secret = Some(FAKE_RSA_KEY.to_string());
+    config.signer = Signer {
+        vault: "0".to_string(),
+        ..Default::default()
+    };
+    edit(&mut config);
+    if let Err(e) = config.validate() {
+        panic!("fake config is invalid: {e}");
+    }
+    config
+}
+
+/// A config file and a [`FAKE_RSA_KEY`] file in a temporary directory,
+/// removed on drop
+pub struct TempConfig {
+    dir: tempfile::TempDir,
+}
+
+impl TempConfig {
+    /// Name of the config file in [`TempConfig::dir`]
+    pub const CONFIG_FILE: &str = "config.toml";
+    /// Name of the key file in [`TempConfig::dir`]
+    pub const KEY_FILE: &str = "key.pem";
+
+    /// A sandbox config with [`FAKE_API_KEY`], vault `0` and `secret_path`
+    /// pointing at the key file
+    pub fn new() -> Result<Self> {
+        Self::with_contents(&format!(
+            "api_key = \"{FAKE_API_KEY}\"\nurl = \"{SANDBOX_URL}\"\nsecret_path = \
+             \"{}\"\n\n[signer]\nvault = \"0\"\n",
+            Self::KEY_FILE
+        ))
+    }
+
+    /// Write `contents` as the config file, next to the key file
+    ///
+    /// A relative `secret_path` resolves against the directory, so
+    /// `secret_path = "key.pem"` names the fake key.
+    pub fn with_contents(contents: &str) -> Result<Self> {
+        let dir = tempfile::tempdir()?;
+        let temp = Self { dir };
+        temp.write(Self::KEY_FILE, FAKE_RSA_KEY)?;
+        temp.write(Self::CONFIG_FILE, contents)?;
+        Ok(temp)
+    }
+
+    /// Write another file into the directory, e.g. an override or profile,
+    /// returning its path
+    pub fn write(&self, name: &str, contents: &str) -> Result<PathBuf> {
+        let path = self.dir.path().join(name);
+        std::fs::write(&path, contents).map_err(|e| Error::IOError {
+            source: e,
+            path: path.to_string_lossy().to_string(),
+            backtrace: capture_backtrace(),
+        })?;
+        Ok(path)
+    }
+
+    /// Load the config file with [`FireblocksConfig::new`], environment
+    /// overlay included
+    pub fn load(&self) -> Result<FireblocksConfig> {
+        FireblocksConfig::new(self.path(), &[])
+    }
+
+    /// The temporary directory
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The config file
+    pub fn path(&self) -> PathBuf {
+        self.dir.path().join(Self::CONFIG_FILE)
+    }
+
+    /// The key file
+    pub fn key_path(&self) -> PathBuf {
+        self.dir.path().join(Self::KEY_FILE)
+    }
+}
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Exclusive access to environment variables such as `FIREBLOCKS_*`, which
+/// are restored to their previous values on drop
+///
+/// Every [`EnvVars`] shares one lock, so tests holding it run one at a time.
+/// The lock only protects code that takes it: any test that reads the
+/// environment, including loading a config with the `FIREBLOCKS_*` overlay,
+/// must hold an [`EnvVars`] too while another test may be changing it.
+pub struct EnvVars {
+    saved: Vec<(OsString, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvVars {
+    /// Wait for the environment lock, a panicked holder does not poison it
+    pub fn lock() -> Self {
+        Self {
+            saved: Vec::new(),
+            _lock: ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner),
+        }
+    }
+
+    /// Set `key` until this is dropped
+    pub fn set(&mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> &mut Self {
+        self.save(key.as_ref());
+        // SAFETY: sound only if every reader of the environment also holds
+        // the lock, which serializes the changes made through `EnvVars`
+        unsafe { std::env::set_var(key, value) };
+        self
+    }
+
+    /// Unset `key` until this is dropped
+    pub fn remove(&mut self, key: impl AsRef<OsStr>) -> &mut Self {
+        self.save(key.as_ref());
+        // SAFETY: sound only if every reader of the environment also holds
+        // the lock, which serializes the changes made through `EnvVars`
+        unsafe { std::env::remove_var(key) };
+        self
+    }
+
+    fn save(&mut self, key: &OsStr) {
+        self.saved.push((key.to_os_string(), std::env::var_os(key)));
+    }
+}
+
+impl Drop for EnvVars {
+    fn drop(&mut self) {
+        for (key, value) in self.saved.drain(..).rev() {
+            // SAFETY: the lock is still held, the same caveat as in `set`
+            // applies to readers that do not take it
+            unsafe {
+                match value {
+                    Some(value) => std::env::set_var(&key, value),
+                    None => std::env::remove_var(&key),
+                }
+            }
+        }
+    }
+}
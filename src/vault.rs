@@ -0,0 +1,71 @@
+//! HashiCorp Vault KV v2 key source, enabled with the `vault` feature
+use crate::{Error, Result};
+
+/// Token variable used when `vault_token_env` is not set
+pub(crate) const DEFAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+
+/// Field of the KV secret holding the key when the path has no `#field`
+pub(crate) const DEFAULT_FIELD: &str = "key";
+
+/// Where a KV v2 secret lives on the server
+#[derive(Debug, PartialEq)]
+pub(crate) struct KvLocation {
+    pub url: String,
+    pub field: String,
+}
+
+/// Build the KV v2 read URL for `secret_path`
+///
+/// `secret_path` is `<mount>/<path>[#field]`, e.g. `secret/fireblocks#key`
+/// reads the `key` field of `fireblocks` under the `secret` mount.
+pub(crate) fn kv2_location(addr: &str, secret_path: &str) -> Result<KvLocation> {
+    let (path, field) = match secret_path.split_once('#') {
+        Some((path, field)) if !field.is_empty() => (path, field),
+        Some((path, _)) => (path, DEFAULT_FIELD),
+        None => (secret_path, DEFAULT_FIELD),
+    };
+    let (mount, path) = path
+        .trim_matches('/')
+        .split_once('/')
+        .filter(|(mount, path)| !mount.is_empty() && !path.is_empty())
+        .ok_or_else(|| {
+            Error::Vault(format!(
+                "vault_secret_path '{secret_path}' must be <mount>/<path>"
+            ))
+        })?;
+    let addr = addr.trim_end_matches('/');
+    if addr.is_empty() {
+        return Err(Error::Vault("vault_addr is empty".to_string()));
+    }
+    Ok(KvLocation {
+        url: format!("{addr}/v1/{mount}/data/{}", path.trim_matches('/')),
+        field: field.to_string(),
+    })
+}
+
+/// Read the key from a KV v2 secret using the token in `token_env`
+pub(crate) fn fetch_secret(addr: &str, token_env: &str, secret_path: &str) -> Result<Vec<u8>> {
+    let location = kv2_location(addr, secret_path)?;
+    let token = std::env::var(token_env)
+        .map_err(|_| Error::Vault(format!("vault token variable {token_env} is not set")))?;
+    tracing::debug!("reading vault secret {}", location.url);
+
+    let response: serde_json::Value = reqwest::blocking::Client::new()
+        .get(&location.url)
+        .header("X-Vault-Token", token)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.json())
+        .map_err(|e| Error::Vault(e.to_string()))?;
+
+    response
+        .pointer(&format!("/data/data/{}", location.field))
+        .and_then(serde_json::Value::as_str)
+        .map(|value| value.as_bytes().to_vec())
+        .ok_or_else(|| {
+            Error::Vault(format!(
+                "field '{}' not found in {}",
+                location.field, location.url
+            ))
+        })
+}
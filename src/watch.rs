@@ -0,0 +1,120 @@
+//! Hot-reload of config files, enabled with the `watch` feature
+use {
+    crate::{Error, FireblocksConfig, Result, error::capture_backtrace},
+    arc_swap::ArcSwap,
+    notify::{RecommendedWatcher, RecursiveMode, Watcher},
+    std::{
+        path::{Path, PathBuf},
+        sync::{Arc, mpsc},
+        thread::JoinHandle,
+        time::Duration,
+    },
+};
+
+/// Quiet period after the last file event before reloading, so editors that
+/// write a file in several steps trigger a single reload
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Keeps a config watcher alive; watching stops when the handle is dropped
+pub struct WatchHandle {
+    watcher: Option<RecommendedWatcher>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        // Dropping the watcher closes the event channel, which ends the worker
+        self.watcher.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl FireblocksConfig {
+    /// Load a config and reload it whenever the base or override files change
+    ///
+    /// Each reload runs the same pipeline as [`FireblocksConfig::new`] and
+    /// swaps the shared config on success. A failed reload is logged and the
+    /// last good config is kept.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use fireblocks_config::FireblocksConfig;
+    ///
+    /// let (config, _handle) = FireblocksConfig::watch("config.toml", &["prod.toml"])?;
+    /// let interval = config.load().signer.poll_interval;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn watch<P: AsRef<Path>>(
+        cfg: P,
+        cfg_overrides: &[P],
+    ) -> Result<(Arc<ArcSwap<FireblocksConfig>>, WatchHandle)> {
+        let base = cfg.as_ref().to_path_buf();
+        let overrides: Vec<PathBuf> = cfg_overrides
+            .iter()
+            .map(|p| p.as_ref().to_path_buf())
+            .collect();
+        let config = Arc::new(ArcSwap::from_pointee(Self::new(base.clone(), &overrides)?));
+
+        let mut files = Vec::with_capacity(overrides.len() + 1);
+        for path in std::iter::once(&base).chain(&overrides) {
+            files.push(path.canonicalize().map_err(|e| Error::IOError {
+                source: e,
+                path: path.to_string_lossy().to_string(),
+                backtrace: capture_backtrace(),
+            })?);
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        // Watch the parent directories: editors often replace files by rename
+        let mut dirs: Vec<&Path> = files.iter().filter_map(|f| f.parent()).collect();
+        dirs.sort();
+        dirs.dedup();
+        for dir in dirs {
+            tracing::debug!("watching {}", dir.display());
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+
+        let shared = Arc::clone(&config);
+        let worker = std::thread::spawn(move || {
+            let is_relevant = |event: notify::Result<notify::Event>| match event {
+                Ok(event) => {
+                    !event.kind.is_access() && event.paths.iter().any(|p| files.contains(p))
+                }
+                Err(e) => {
+                    tracing::warn!("config watcher error: {e}");
+                    false
+                }
+            };
+            while let Ok(event) = rx.recv() {
+                if !is_relevant(event) {
+                    continue;
+                }
+                // Wait for the writes to settle
+                loop {
+                    match rx.recv_timeout(DEBOUNCE) {
+                        Ok(_) => continue,
+                        Err(mpsc::RecvTimeoutError::Timeout) => break,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                match Self::new(base.clone(), &overrides) {
+                    Ok(cfg) => {
+                        tracing::debug!("reloaded config {}", base.display());
+                        shared.store(Arc::new(cfg));
+                    }
+                    Err(e) => {
+                        tracing::error!("failed to reload config {}: {e}", base.display());
+                    }
+                }
+            }
+        });
+
+        Ok((config, WatchHandle {
+            watcher: Some(watcher),
+            worker: Some(worker),
+        }))
+    }
+}
@@ -0,0 +1,106 @@
+use {
+    crate::{Error, FireblocksConfig, Result, loader::read_toml},
+    std::{collections::BTreeMap, path::Path},
+};
+
+/// Several Fireblocks workspaces loaded from one file
+///
+/// Each `[workspaces.<name>]` table is deep merged over the top-level keys,
+/// which act as shared defaults, and deserialized into its own
+/// [`FireblocksConfig`]. The optional top-level `default_workspace` names the
+/// workspace returned by [`FireblocksWorkspaces::default_workspace`].
+///
+/// Each workspace loads like [`FireblocksConfig::new`] on the file, so
+/// `extends` is followed and relative paths such as `secret_path` resolve
+/// against the file's directory. The `FIREBLOCKS_*` environment overlay is
+/// not applied, since one variable would override every workspace alike.
+///
+/// # Example
+/// ```rust,no_run
+/// use fireblocks_config::FireblocksWorkspaces;
+///
+/// let workspaces = FireblocksWorkspaces::new("workspaces.toml")?;
+/// for name in workspaces.names() {
+///     let config = workspaces.get(name)?;
+///     println!("{name}: {}", config.resolved_url());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FireblocksWorkspaces {
+    workspaces: BTreeMap<String, FireblocksConfig>,
+    default_workspace: Option<String>,
+}
+
+impl FireblocksWorkspaces {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let (_, mut shared) = read_toml(path)?;
+        let default_workspace = match shared.remove("default_workspace") {
+            Some(toml::Value::String(name)) => Some(name),
+            Some(other) => {
+                return Err(Error::InvalidValue {
+                    key: "default_workspace".to_string(),
+                    value: other.to_string(),
+                    reason: format!("expected a string in {}", path.display()),
+                });
+            }
+            None => None,
+        };
+        let tables = match shared.remove("workspaces") {
+            Some(toml::Value::Table(tables)) => tables,
+            _ => toml::Table::new(),
+        };
+
+        let mut workspaces = BTreeMap::new();
+        for (name, table) in tables {
+            if !table.is_table() {
+                continue;
+            }
+            let config =
+                FireblocksConfig::load_workspace(path, &name).map_err(|e| Error::Workspace {
+                    workspace: name.clone(),
+                    source: Box::new(e),
+                })?;
+            workspaces.insert(name, config);
+        }
+
+        let workspaces = Self {
+            workspaces,
+            default_workspace,
+        };
+        if let Some(ref name) = workspaces.default_workspace {
+            workspaces.get(name)?;
+        }
+        Ok(workspaces)
+    }
+
+    /// The config of the named workspace
+    pub fn get(&self, name: &str) -> Result<&FireblocksConfig> {
+        self.workspaces
+            .get(name)
+            .ok_or_else(|| Error::WorkspaceNotFound {
+                workspace: name.to_string(),
+                available: self.workspaces.keys().cloned().collect(),
+            })
+    }
+
+    /// Workspace names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        self.workspaces.keys().map(String::as_str).collect()
+    }
+
+    /// The workspace named by `default_workspace`, or the only workspace
+    /// when the file defines exactly one
+    pub fn default_workspace(&self) -> Result<&FireblocksConfig> {
+        match self.default_workspace {
+            Some(ref name) => self.get(name),
+            None if self.workspaces.len() == 1 => {
+                Ok(self.workspaces.values().next().expect("one workspace"))
+            }
+            None => Err(Error::NoDefaultWorkspace {
+                available: self.workspaces.keys().cloned().collect(),
+            }),
+        }
+    }
+}